@@ -0,0 +1,43 @@
+//! Cost of checking a session signature, which happens on every authorized request
+//!
+//! ```ignore
+//! cargo bench -p uchat_crypto
+//! ```
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use uchat_crypto::sign::{generate_key, Algorithm};
+
+// NOTE Sessions sign their uuid, so the data is as long as one
+const SESSION_ID: [u8; 16] = *b"0123456789abcdef";
+
+fn verify(c: &mut Criterion) {
+    let mut rng = uchat_crypto::new_rng();
+    let mut group = c.benchmark_group("verify session signature");
+
+    for algorithm in [Algorithm::RsaPss, Algorithm::Ed25519, Algorithm::HmacSha256] {
+        let (_, key) = generate_key(algorithm, &mut rng).unwrap();
+        let signature = key.sign(&mut rng, &SESSION_ID);
+
+        group.bench_function(algorithm.as_str(), |b| {
+            b.iter(|| key.verify(black_box(&SESSION_ID), black_box(&signature)))
+        });
+    }
+    group.finish();
+}
+
+fn sign(c: &mut Criterion) {
+    let mut rng = uchat_crypto::new_rng();
+    let mut group = c.benchmark_group("sign session");
+
+    for algorithm in [Algorithm::RsaPss, Algorithm::Ed25519, Algorithm::HmacSha256] {
+        let (_, key) = generate_key(algorithm, &mut rng).unwrap();
+
+        group.bench_function(algorithm.as_str(), |b| {
+            b.iter(|| key.sign(&mut rng, black_box(&SESSION_ID)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, verify, sign);
+criterion_main!(benches);
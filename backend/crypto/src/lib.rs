@@ -2,6 +2,10 @@ pub mod password;
 
 pub mod sign;
 
+pub mod token;
+
+pub mod totp;
+
 pub use password::{hash_password, verify_password};
 
 pub fn new_rng() -> rand::rngs::StdRng {
@@ -1,8 +1,15 @@
-use rand_core::{CryptoRng, RngCore};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use hmac::{Hmac, Mac};
+use rand_core::{CryptoRng, CryptoRngCore, RngCore};
 use rsa::pss::{BlindedSigningKey, Signature, VerifyingKey};
 use rsa::sha2::Sha256;
 use rsa::signature::{Keypair, RandomizedSigner, Verifier};
 use rsa::RsaPrivateKey;
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -17,6 +24,92 @@ pub enum Error {
 
     #[error("decoding error: {0}")]
     DecodingError(String),
+
+    #[error("unknown signing key: {0}")]
+    UnknownKey(KeyId),
+
+    #[error("unknown signing algorithm: {0}")]
+    UnknownAlgorithm(String),
+
+    #[error("invalid signature")]
+    InvalidSignature,
+}
+
+/// The ways a `Keyring` can sign, RSA-PSS is slow and only kept to verify what older keys signed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Algorithm {
+    RsaPss,
+    Ed25519,
+    HmacSha256,
+}
+
+impl Algorithm {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::RsaPss => "rsa-pss",
+            Self::Ed25519 => "ed25519",
+            Self::HmacSha256 => "hmac-sha256",
+        }
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [Self::RsaPss, Self::Ed25519, Self::HmacSha256]
+            .into_iter()
+            .find(|algorithm| algorithm.as_str() == s)
+            .ok_or_else(|| Error::UnknownAlgorithm(s.to_owned()))
+    }
+}
+
+/// A key the `Keyring` signs and verifies with, regardless of its algorithm
+pub trait SignatureScheme: Send + Sync {
+    fn algorithm(&self) -> Algorithm;
+
+    fn sign(&self, rng: &mut dyn CryptoRngCore, data: &[u8]) -> Vec<u8>;
+
+    fn verify(&self, data: &[u8], signature: &[u8]) -> Result<(), Error>;
+}
+
+pub type SharedKey = Arc<dyn SignatureScheme>;
+
+/// Generates a key, along with the encoding `decode_key` loads it back from
+pub fn generate_key<R>(algorithm: Algorithm, rng: &mut R) -> Result<(String, SharedKey), Error>
+where
+    R: CryptoRng + RngCore,
+{
+    Ok(match algorithm {
+        Algorithm::RsaPss => {
+            let (private_key, keys) = Keys::generate(rng)?;
+            let encoded = encode_private_key(private_key)?;
+            (encoded.as_str().to_owned(), Arc::new(keys))
+        }
+        Algorithm::Ed25519 => {
+            let key = Ed25519Key::generate(rng);
+            (key.encode(), Arc::new(key))
+        }
+        Algorithm::HmacSha256 => {
+            let key = HmacKey::generate(rng);
+            (key.encode(), Arc::new(key))
+        }
+    })
+}
+
+pub fn decode_key(algorithm: Algorithm, encoded: &str) -> Result<SharedKey, Error> {
+    Ok(match algorithm {
+        Algorithm::RsaPss => Arc::new(Keys::from_encoded(encoded)?),
+        Algorithm::Ed25519 => Arc::new(Ed25519Key::from_encoded(encoded)?),
+        Algorithm::HmacSha256 => Arc::new(HmacKey::from_encoded(encoded)?),
+    })
 }
 
 pub fn new_private_key<R>(rng: &mut R) -> Result<RsaPrivateKey, Error>
@@ -84,6 +177,250 @@ impl Keys {
     }
 }
 
+impl SignatureScheme for Keys {
+    fn algorithm(&self) -> Algorithm {
+        Algorithm::RsaPss
+    }
+
+    fn sign(&self, mut rng: &mut dyn CryptoRngCore, data: &[u8]) -> Vec<u8> {
+        let signature: Signature = self.signing_key.sign_with_rng(&mut rng, data);
+        signature.as_ref().to_vec()
+    }
+
+    fn verify(&self, data: &[u8], signature: &[u8]) -> Result<(), Error> {
+        Keys::verify(self, data, signature_from_bytes(signature)?)
+    }
+}
+
+const SECRET_KEY_LENGTH: usize = 32;
+
+fn decode_secret_key(encoded: &str) -> Result<[u8; SECRET_KEY_LENGTH], Error> {
+    crate::decode_base64(encoded)
+        .map_err(|e| Error::DecodingError(e.to_string()))?
+        .try_into()
+        .map_err(|_| Error::DecodingError("wrong key length".to_owned()))
+}
+
+// NOTE Ed25519 signatures are as safe as RSA-PSS ones, but keys are made and checked far faster
+pub struct Ed25519Key(ed25519_dalek::SigningKey);
+
+impl Ed25519Key {
+    pub fn generate<R>(rng: &mut R) -> Self
+    where
+        R: CryptoRng + RngCore,
+    {
+        Self(ed25519_dalek::SigningKey::generate(rng))
+    }
+
+    pub fn from_encoded(encoded: &str) -> Result<Self, Error> {
+        let key = decode_secret_key(encoded)?;
+        Ok(Self(ed25519_dalek::SigningKey::from_bytes(&key)))
+    }
+
+    pub fn encode(&self) -> String {
+        crate::encode_base64(self.0.to_bytes())
+    }
+}
+
+impl SignatureScheme for Ed25519Key {
+    fn algorithm(&self) -> Algorithm {
+        Algorithm::Ed25519
+    }
+
+    fn sign(&self, _: &mut dyn CryptoRngCore, data: &[u8]) -> Vec<u8> {
+        use ed25519_dalek::Signer;
+
+        self.0.sign(data).to_bytes().to_vec()
+    }
+
+    fn verify(&self, data: &[u8], signature: &[u8]) -> Result<(), Error> {
+        let signature =
+            ed25519_dalek::Signature::from_slice(signature).map_err(|_| Error::InvalidSignature)?;
+        self.0
+            .verifying_key()
+            .verify_strict(data, &signature)
+            .map_err(|_| Error::InvalidSignature)
+    }
+}
+
+// NOTE The same secret signs and verifies, so it's only an option when the api is the only verifier
+pub struct HmacKey([u8; SECRET_KEY_LENGTH]);
+
+impl HmacKey {
+    pub fn generate<R>(rng: &mut R) -> Self
+    where
+        R: CryptoRng + RngCore,
+    {
+        let mut key = [0u8; SECRET_KEY_LENGTH];
+        rng.fill_bytes(&mut key);
+        Self(key)
+    }
+
+    pub fn from_encoded(encoded: &str) -> Result<Self, Error> {
+        Ok(Self(decode_secret_key(encoded)?))
+    }
+
+    pub fn encode(&self) -> String {
+        crate::encode_base64(self.0)
+    }
+
+    fn mac(&self, data: &[u8]) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.0).expect("hmac accepts keys of any length");
+        mac.update(data);
+        mac
+    }
+}
+
+impl SignatureScheme for HmacKey {
+    fn algorithm(&self) -> Algorithm {
+        Algorithm::HmacSha256
+    }
+
+    fn sign(&self, _: &mut dyn CryptoRngCore, data: &[u8]) -> Vec<u8> {
+        self.mac(data).finalize().into_bytes().to_vec()
+    }
+
+    // ? verify_slice compares in constant time, so the expected mac can't be guessed byte by byte
+    fn verify(&self, data: &[u8], signature: &[u8]) -> Result<(), Error> {
+        self.mac(data)
+            .verify_slice(signature)
+            .map_err(|_| Error::InvalidSignature)
+    }
+}
+
+// NOTE Key ids are written next to signatures, so they can't contain the separator used there
+const KEY_ID_SEPARATOR: char = '.';
+
+// NOTE The key from `API_PRIVATE_KEY` is known by this id, so it keeps working once a keyring is made
+pub const LEGACY_KEY_ID: &str = "default";
+
+/// Names a key in a `Keyring`, so signatures can be checked with the key that made them
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String")]
+pub struct KeyId(String);
+
+impl KeyId {
+    pub fn new<T: Into<String>>(id: T) -> Result<Self, Error> {
+        let id = id.into();
+        if id.is_empty() || id.contains(KEY_ID_SEPARATOR) {
+            return Err(Error::DecodingError(format!("invalid key id: {id}")));
+        }
+        Ok(Self(id))
+    }
+
+    pub fn generate<R>(rng: &mut R) -> Self
+    where
+        R: CryptoRng + RngCore,
+    {
+        let mut id = [0u8; 6];
+        rng.fill_bytes(&mut id);
+        Self(id.iter().map(|byte| format!("{byte:02x}")).collect())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl TryFrom<String> for KeyId {
+    type Error = Error;
+
+    fn try_from(id: String) -> Result<Self, Self::Error> {
+        Self::new(id)
+    }
+}
+
+impl fmt::Display for KeyId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Signs with the active key, while older keys keep verifying what they signed until they're retired
+#[derive(Clone)]
+pub struct Keyring {
+    active: KeyId,
+    keys: HashMap<KeyId, SharedKey>,
+}
+
+impl Keyring {
+    pub fn new(active: KeyId, key: SharedKey) -> Self {
+        Self {
+            keys: HashMap::from([(active.clone(), key)]),
+            active,
+        }
+    }
+
+    /// Adds a key that is only used to verify signatures
+    pub fn insert(&mut self, id: KeyId, key: SharedKey) {
+        self.keys.insert(id, key);
+    }
+
+    pub fn active_algorithm(&self) -> Algorithm {
+        self.keys[&self.active].algorithm()
+    }
+
+    pub fn active_id(&self) -> &KeyId {
+        &self.active
+    }
+
+    pub fn contains(&self, id: &KeyId) -> bool {
+        self.keys.contains_key(id)
+    }
+
+    pub fn sign<R>(&self, rng: &mut R, data: &[u8]) -> KeyedSignature
+    where
+        R: CryptoRng + RngCore,
+    {
+        let key = &self.keys[&self.active];
+        KeyedSignature {
+            key_id: self.active.clone(),
+            signature: key.sign(rng, data),
+        }
+    }
+
+    pub fn verify(&self, data: &[u8], signature: &KeyedSignature) -> Result<(), Error> {
+        let key = self
+            .keys
+            .get(&signature.key_id)
+            .ok_or_else(|| Error::UnknownKey(signature.key_id.clone()))?;
+        key.verify(data, &signature.signature)
+    }
+}
+
+/// A signature along with the key that made it
+// ? encoded as <key id>.<base64 signature>, or only the base64 for signatures older than keyrings
+#[derive(Clone, Debug)]
+pub struct KeyedSignature {
+    pub key_id: KeyId,
+    pub signature: Vec<u8>,
+}
+
+impl KeyedSignature {
+    pub fn encode(&self) -> String {
+        format!(
+            "{}{KEY_ID_SEPARATOR}{}",
+            self.key_id,
+            crate::encode_base64(&self.signature)
+        )
+    }
+
+    pub fn decode(encoded: &str) -> Result<Self, Error> {
+        // ? Base64 has no separator in it, so a signature without one was made by the legacy key
+        let (key_id, signature) = encoded
+            .split_once(KEY_ID_SEPARATOR)
+            .unwrap_or((LEGACY_KEY_ID, encoded));
+        let signature =
+            crate::decode_base64(signature).map_err(|e| Error::DecodingError(e.to_string()))?;
+
+        Ok(Self {
+            key_id: KeyId::new(key_id)?,
+            signature,
+        })
+    }
+}
+
 fn new_signing_key(private_key: RsaPrivateKey) -> Result<BlindedSigningKey<Sha256>, Error> {
     Ok(BlindedSigningKey::new(private_key))
 }
@@ -102,3 +439,69 @@ pub fn decode_private_key<T: AsRef<[u8]>>(key: T) -> Result<RsaPrivateKey, Error
 pub fn signature_from_bytes<T: AsRef<[u8]>>(bytes: T) -> Result<Signature, Error> {
     Ok(Signature::try_from(bytes.as_ref())?)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_algorithm_verifies_its_own_signatures() {
+        let mut rng = crate::new_rng();
+
+        for algorithm in [Algorithm::RsaPss, Algorithm::Ed25519, Algorithm::HmacSha256] {
+            let (encoded, key) = generate_key(algorithm, &mut rng).unwrap();
+            let signature = key.sign(&mut rng, b"session");
+
+            assert_eq!(key.algorithm(), algorithm);
+            assert!(key.verify(b"session", &signature).is_ok());
+            assert!(key.verify(b"other session", &signature).is_err());
+
+            // * keys still verify once loaded back
+            let decoded = decode_key(algorithm, &encoded).unwrap();
+            assert!(decoded.verify(b"session", &signature).is_ok());
+            assert_eq!(
+                algorithm.to_string().parse::<Algorithm>().unwrap(),
+                algorithm
+            );
+        }
+    }
+
+    #[test]
+    fn retired_keys_no_longer_verify() {
+        let mut rng = crate::new_rng();
+        let (_, old_key) = generate_key(Algorithm::RsaPss, &mut rng).unwrap();
+        let (_, new_key) = generate_key(Algorithm::Ed25519, &mut rng).unwrap();
+        let old_id = KeyId::generate(&mut rng);
+        let new_id = KeyId::generate(&mut rng);
+
+        let old_keyring = Keyring::new(old_id.clone(), old_key.clone());
+        let signature = old_keyring.sign(&mut rng, b"session");
+
+        // * rotated to another algorithm, the old key still verifies what it signed
+        let mut keyring = Keyring::new(new_id.clone(), new_key.clone());
+        keyring.insert(old_id, old_key);
+        let decoded = KeyedSignature::decode(&signature.encode()).unwrap();
+        assert!(keyring.verify(b"session", &decoded).is_ok());
+        assert_eq!(keyring.sign(&mut rng, b"session").key_id, new_id);
+        assert_eq!(keyring.active_algorithm(), Algorithm::Ed25519);
+
+        // * retired
+        let keyring = Keyring::new(new_id, new_key);
+        assert!(keyring.verify(b"session", &decoded).is_err());
+    }
+
+    #[test]
+    fn signatures_without_a_key_id_are_checked_with_the_legacy_key() {
+        let mut rng = crate::new_rng();
+        let (_, keys) = Keys::generate(&mut rng).unwrap();
+
+        // * signed the way sessions were before keyrings existed
+        let signature = crate::encode_base64(keys.sign(&mut rng, b"session"));
+
+        let keyring = Keyring::new(KeyId::new(LEGACY_KEY_ID).unwrap(), Arc::new(keys));
+        let decoded = KeyedSignature::decode(&signature).unwrap();
+        assert_eq!(decoded.key_id.as_str(), LEGACY_KEY_ID);
+        assert!(keyring.verify(b"session", &decoded).is_ok());
+        assert!(keyring.verify(b"other session", &decoded).is_err());
+    }
+}
@@ -0,0 +1,147 @@
+use rand_core::{CryptoRng, RngCore};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::sign::{Error, KeyId, KeyedSignature, Keyring};
+
+// NOTE Tokens end up in urls, so they're encoded with the url safe base64 alphabet
+// ? format: <key id>.<payload>.<signature>
+const SEPARATOR: char = '.';
+
+/// Serializes the claims and signs them, the claims can be read by anyone holding the token
+pub fn sign<T, R>(keyring: &Keyring, rng: &mut R, claims: &T) -> Result<String, Error>
+where
+    T: Serialize,
+    R: CryptoRng + RngCore,
+{
+    let payload = rmp_serde::to_vec(claims)?;
+    let KeyedSignature { key_id, signature } = keyring.sign(rng, &payload);
+
+    Ok(format!(
+        "{key_id}{SEPARATOR}{}{SEPARATOR}{}",
+        encode_base64_url(payload),
+        encode_base64_url(signature)
+    ))
+}
+
+/// Checks the token signature and returns its claims, it's up to the caller to validate them
+pub fn verify<T>(keyring: &Keyring, token: &str) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    let malformed = || Error::DecodingError("malformed token".to_owned());
+
+    let mut parts = token.splitn(3, SEPARATOR);
+    let key_id = parts.next().ok_or_else(malformed)?;
+    let payload = parts.next().ok_or_else(malformed)?;
+    let signature = parts.next().ok_or_else(malformed)?;
+
+    let payload = decode_base64_url(payload)?;
+    let signature = KeyedSignature {
+        key_id: KeyId::new(key_id)?,
+        signature: decode_base64_url(signature)?,
+    };
+
+    keyring.verify(&payload, &signature)?;
+
+    rmp_serde::from_slice(&payload).map_err(|e| Error::DecodingError(e.to_string()))
+}
+
+/// A random token that can only be checked against its hash, meant to be stored hashed with `hash_secret`
+pub fn new_secret<R>(rng: &mut R) -> String
+where
+    R: CryptoRng + RngCore,
+{
+    let mut secret = [0u8; 32];
+    rng.fill_bytes(&mut secret);
+    encode_base64_url(secret)
+}
+
+// NOTE The secrets are random, so unlike passwords they don't need a slow salted hash
+pub fn hash_secret(secret: &str) -> String {
+    use rsa::sha2::{Digest, Sha256};
+
+    encode_base64_url(Sha256::digest(secret.as_bytes()))
+}
+
+fn encode_base64_url<T: AsRef<[u8]>>(data: T) -> String {
+    use base64::{engine::general_purpose, Engine as _};
+
+    general_purpose::URL_SAFE_NO_PAD.encode(data)
+}
+
+fn decode_base64_url<T: AsRef<[u8]>>(data: T) -> Result<Vec<u8>, Error> {
+    use base64::{engine::general_purpose, Engine as _};
+
+    general_purpose::URL_SAFE_NO_PAD
+        .decode(data.as_ref())
+        .map_err(|e| Error::DecodingError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::sign::{generate_key, Algorithm};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Claims {
+        user: String,
+        expires: i64,
+    }
+
+    fn claims() -> Claims {
+        Claims {
+            user: "user 1".to_owned(),
+            expires: 100,
+        }
+    }
+
+    fn new_keyring() -> Keyring {
+        let mut rng = crate::new_rng();
+        let (_, key) = generate_key(Algorithm::Ed25519, &mut rng).unwrap();
+        Keyring::new(KeyId::generate(&mut rng), key)
+    }
+
+    #[test]
+    fn signed_claims_are_verified() {
+        let mut rng = crate::new_rng();
+        let keys = new_keyring();
+
+        let token = sign(&keys, &mut rng, &claims()).unwrap();
+        let verified: Claims = verify(&keys, &token).unwrap();
+        assert_eq!(verified, claims());
+    }
+
+    #[test]
+    fn tampered_tokens_fail_verification() {
+        let mut rng = crate::new_rng();
+        let keys = new_keyring();
+
+        let token = sign(&keys, &mut rng, &claims()).unwrap();
+        let (key_id, rest) = token.split_once(SEPARATOR).unwrap();
+        let (_, signature) = rest.split_once(SEPARATOR).unwrap();
+        let forged_payload = rmp_serde::to_vec(&Claims {
+            user: "user 2".to_owned(),
+            expires: 100,
+        })
+        .unwrap();
+        let forged = format!(
+            "{key_id}{SEPARATOR}{}{SEPARATOR}{signature}",
+            encode_base64_url(forged_payload)
+        );
+
+        assert!(verify::<Claims>(&keys, &forged).is_err());
+        assert!(verify::<Claims>(&keys, "not a token").is_err());
+    }
+
+    #[test]
+    fn secrets_are_hashed_consistently() {
+        let mut rng = crate::new_rng();
+        let secret = new_secret(&mut rng);
+
+        assert_ne!(secret, new_secret(&mut rng));
+        assert_eq!(hash_secret(&secret), hash_secret(&secret));
+        assert_ne!(hash_secret(&secret), secret);
+    }
+}
@@ -0,0 +1,206 @@
+use data_encoding::BASE32_NOPAD;
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use rand_core::{CryptoRng, RngCore};
+use sha1::Sha1;
+use subtle::ConstantTimeEq;
+
+// NOTE RFC 6238 defaults, which is what most authenticator apps support
+pub const DIGITS: u32 = 6;
+pub const STEP_SECS: u64 = 30;
+const SECRET_LEN: usize = 20;
+// ? Codes from the previous and next steps are accepted as well, to make up for clock drift
+const ALLOWED_DRIFT_STEPS: u64 = 1;
+
+const RECOVERY_CODE_CHARSET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+const RECOVERY_CODE_LEN: usize = 10;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("invalid secret: {0}")]
+    InvalidSecret(String),
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct TotpSecret(Vec<u8>);
+
+impl TotpSecret {
+    pub fn generate<R>(rng: &mut R) -> Self
+    where
+        R: CryptoRng + RngCore,
+    {
+        let mut secret = vec![0u8; SECRET_LEN];
+        rng.fill_bytes(&mut secret);
+        Self(secret)
+    }
+
+    pub fn from_base32<T: AsRef<str>>(secret: T) -> Result<Self, Error> {
+        BASE32_NOPAD
+            .decode(secret.as_ref().as_bytes())
+            .map(Self)
+            .map_err(|e| Error::InvalidSecret(e.to_string()))
+    }
+
+    /// The encoding authenticator apps expect when the secret is typed in manually
+    pub fn to_base32(&self) -> String {
+        BASE32_NOPAD.encode(&self.0)
+    }
+
+    /// URI that authenticator apps can enroll from, usually shown as a QR code
+    pub fn provisioning_uri(&self, issuer: &str, account: &str) -> String {
+        let issuer = percent_encode(issuer);
+        let account = percent_encode(account);
+        format!(
+            "otpauth://totp/{issuer}:{account}?secret={}&issuer={issuer}&algorithm=SHA1&digits={DIGITS}&period={STEP_SECS}",
+            self.to_base32()
+        )
+    }
+
+    pub fn code_at_step(&self, step: u64) -> String {
+        let mut mac =
+            Hmac::<Sha1>::new_from_slice(&self.0).expect("hmac accepts keys of any length");
+        mac.update(&step.to_be_bytes());
+        let hash = mac.finalize().into_bytes();
+
+        // ? Dynamic truncation, as described in RFC 4226
+        let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+        let binary = u32::from_be_bytes([
+            hash[offset] & 0x7f,
+            hash[offset + 1],
+            hash[offset + 2],
+            hash[offset + 3],
+        ]);
+
+        format!(
+            "{:0width$}",
+            binary % 10u32.pow(DIGITS),
+            width = DIGITS as usize
+        )
+    }
+
+    /// Returns the time step the code belongs to, callers should reject steps that were already used
+    pub fn verify(&self, code: &str, unix_secs: u64) -> Option<u64> {
+        let current = unix_secs / STEP_SECS;
+        let first = current.saturating_sub(ALLOWED_DRIFT_STEPS);
+
+        // ? Every step is compared in constant time, so timing doesn't tell how close a guess was
+        // or which step it matched
+        let mut matched = None;
+        for step in first..=current + ALLOWED_DRIFT_STEPS {
+            let is_match = self.code_at_step(step).as_bytes().ct_eq(code.as_bytes());
+            if bool::from(is_match) && matched.is_none() {
+                matched = Some(step);
+            }
+        }
+        matched
+    }
+}
+
+/// One-time codes that replace a TOTP code when the authenticator is lost, store them hashed
+pub fn generate_recovery_codes<R>(rng: &mut R, count: usize) -> Vec<String>
+where
+    R: CryptoRng + RngCore,
+{
+    (0..count)
+        .map(|_| {
+            (0..RECOVERY_CODE_LEN)
+                .map(|_| {
+                    let idx = rng.gen_range(0..RECOVERY_CODE_CHARSET.len());
+                    RECOVERY_CODE_CHARSET[idx] as char
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Whether `code` has the shape of a TOTP code, so anything else is turned down without checking it
+pub fn is_totp_code(code: &str) -> bool {
+    code.len() == DIGITS as usize && code.bytes().all(|byte| byte.is_ascii_digit())
+}
+
+/// Whether `code` has the shape of a code made by `generate_recovery_codes`
+pub fn is_recovery_code(code: &str) -> bool {
+    code.len() == RECOVERY_CODE_LEN
+        && code
+            .bytes()
+            .all(|byte| RECOVERY_CODE_CHARSET.contains(&byte))
+}
+
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // NOTE Test vectors from RFC 6238, which uses this ascii secret for SHA1
+    fn rfc_secret() -> TotpSecret {
+        TotpSecret(b"12345678901234567890".to_vec())
+    }
+
+    #[test]
+    fn generates_rfc_codes() {
+        let secret = rfc_secret();
+        // ? The RFC lists 8 digit codes, these are their last 6 digits
+        assert_eq!(secret.code_at_step(59 / STEP_SECS), "287082");
+        assert_eq!(secret.code_at_step(1111111109 / STEP_SECS), "081804");
+        assert_eq!(secret.code_at_step(1234567890 / STEP_SECS), "005924");
+    }
+
+    #[test]
+    fn accepts_codes_within_drift() {
+        let secret = rfc_secret();
+        let now = 1111111109;
+        let step = now / STEP_SECS;
+
+        assert_eq!(secret.verify("081804", now), Some(step));
+        assert_eq!(secret.verify("081804", now + STEP_SECS), Some(step));
+        assert_eq!(secret.verify("081804", now + STEP_SECS * 2), None);
+        assert_eq!(secret.verify("000000", now), None);
+    }
+
+    #[test]
+    fn secrets_roundtrip_through_base32() {
+        let mut rng = crate::new_rng();
+        let secret = TotpSecret::generate(&mut rng);
+        let encoded = secret.to_base32();
+
+        assert!(TotpSecret::from_base32(&encoded).unwrap() == secret);
+        assert!(secret
+            .provisioning_uri("uChat", "some user")
+            .starts_with("otpauth://totp/uChat:some%20user?secret="));
+    }
+
+    #[test]
+    fn recovery_codes_are_unique() {
+        let mut rng = crate::new_rng();
+        let codes = generate_recovery_codes(&mut rng, 10);
+
+        assert_eq!(codes.len(), 10);
+        assert!(codes.iter().all(|code| code.len() == RECOVERY_CODE_LEN));
+        assert!(codes.iter().skip(1).all(|code| code != &codes[0]));
+    }
+
+    #[test]
+    fn codes_are_told_apart_by_their_shape() {
+        let mut rng = crate::new_rng();
+        let recovery_code = &generate_recovery_codes(&mut rng, 1)[0];
+
+        assert!(is_totp_code("081804"));
+        assert!(!is_totp_code("08180"));
+        assert!(!is_totp_code("08180a"));
+        assert!(is_recovery_code(recovery_code));
+        assert!(!is_recovery_code(&recovery_code.to_uppercase()));
+        assert!(!is_recovery_code("081804"));
+        assert!(!is_recovery_code(&"a".repeat(1000)));
+    }
+}
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS public.trending_posts_rank_index CASCADE;
+DROP TABLE IF EXISTS public.trending_posts CASCADE;
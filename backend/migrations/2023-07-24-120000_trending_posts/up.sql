@@ -0,0 +1,14 @@
+-- NOTE Refreshed periodically by the server, so trending posts aren't scored on every request
+CREATE TABLE public.trending_posts (
+  post_id uuid NOT NULL,
+  rank bigint NOT NULL,
+  score double precision NOT NULL,
+  refreshed_at timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  CONSTRAINT trending_posts_pk PRIMARY KEY (post_id)
+);
+
+ALTER TABLE public.trending_posts ADD CONSTRAINT post_id_fk FOREIGN KEY (post_id)
+REFERENCES public.posts (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+
+CREATE UNIQUE INDEX trending_posts_rank_index ON public.trending_posts (rank);
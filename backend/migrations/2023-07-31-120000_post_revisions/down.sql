@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS public.post_revisions_post_id_index CASCADE;
+DROP TABLE IF EXISTS public.post_revisions CASCADE;
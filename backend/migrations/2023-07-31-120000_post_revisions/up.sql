@@ -0,0 +1,15 @@
+-- NOTE Holds the content a post had before each edit, the current content stays in `posts`
+CREATE TABLE public.post_revisions (
+  id uuid NOT NULL,
+  post_id uuid NOT NULL,
+  content jsonb NOT NULL,
+  created_at timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  CONSTRAINT post_revisions_pk PRIMARY KEY (id)
+);
+COMMENT ON COLUMN public.post_revisions.created_at IS E'when this content was replaced by an edit';
+
+ALTER TABLE public.post_revisions ADD CONSTRAINT post_id_fk FOREIGN KEY (post_id)
+REFERENCES public.posts (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+
+CREATE INDEX post_revisions_post_id_index ON public.post_revisions (post_id, created_at);
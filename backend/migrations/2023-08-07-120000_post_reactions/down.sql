@@ -0,0 +1,3 @@
+ALTER TABLE public.reactions ADD COLUMN reaction jsonb;
+DROP INDEX IF EXISTS public.post_reactions_post_id_index CASCADE;
+DROP TABLE IF EXISTS public.post_reactions CASCADE;
@@ -0,0 +1,20 @@
+-- NOTE Replaces the unused `reactions.reaction` JSONB column, `reactions` keeps the like status
+CREATE TABLE public.post_reactions (
+  user_id uuid NOT NULL,
+  post_id uuid NOT NULL,
+  emoji text NOT NULL,
+  created_at timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  CONSTRAINT post_reactions_pk PRIMARY KEY (user_id,post_id,emoji)
+);
+
+ALTER TABLE public.post_reactions ADD CONSTRAINT post_id_fk FOREIGN KEY (post_id)
+REFERENCES public.posts (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+
+ALTER TABLE public.post_reactions ADD CONSTRAINT user_id_fk FOREIGN KEY (user_id)
+REFERENCES public.users (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+
+CREATE INDEX post_reactions_post_id_index ON public.post_reactions (post_id, emoji);
+
+ALTER TABLE public.reactions DROP COLUMN IF EXISTS reaction;
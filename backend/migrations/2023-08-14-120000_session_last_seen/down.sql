@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS public.web_user_id_index CASCADE;
+ALTER TABLE public.web DROP COLUMN IF EXISTS last_seen_at;
@@ -0,0 +1,4 @@
+-- NOTE Bumped by the server as the session is used, so users can tell their active devices apart
+ALTER TABLE public.web ADD COLUMN last_seen_at timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP;
+
+CREATE INDEX web_user_id_index ON public.web (user_id);
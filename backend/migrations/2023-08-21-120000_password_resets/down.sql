@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS public.password_resets_user_id_index CASCADE;
+DROP TABLE IF EXISTS public.password_resets CASCADE;
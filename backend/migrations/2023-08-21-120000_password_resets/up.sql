@@ -0,0 +1,14 @@
+-- NOTE Only a hash of the token is stored, the token itself is only sent to the user by email
+CREATE TABLE public.password_resets (
+  token_hash text NOT NULL,
+  user_id uuid NOT NULL,
+  expires_at timestamptz NOT NULL,
+  created_at timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  CONSTRAINT password_resets_pk PRIMARY KEY (token_hash)
+);
+
+ALTER TABLE public.password_resets ADD CONSTRAINT user_id_fk FOREIGN KEY (user_id)
+REFERENCES public.users (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+
+CREATE INDEX password_resets_user_id_index ON public.password_resets (user_id);
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS public.recovery_codes_user_id_index CASCADE;
+DROP TABLE IF EXISTS public.recovery_codes CASCADE;
+DROP TABLE IF EXISTS public.totp CASCADE;
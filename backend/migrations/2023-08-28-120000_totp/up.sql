@@ -0,0 +1,28 @@
+-- NOTE The secret is pending until the user confirms it with a code from their authenticator
+CREATE TABLE public.totp (
+  user_id uuid NOT NULL,
+  secret text NOT NULL,
+  confirmed_at timestamptz,
+  last_used_step bigint,
+  created_at timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  CONSTRAINT totp_pk PRIMARY KEY (user_id)
+);
+
+ALTER TABLE public.totp ADD CONSTRAINT user_id_fk FOREIGN KEY (user_id)
+REFERENCES public.users (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+
+-- NOTE Recovery codes are stored as argon2 hashes and deleted once used
+CREATE TABLE public.recovery_codes (
+  id uuid NOT NULL,
+  user_id uuid NOT NULL,
+  code_hash text NOT NULL,
+  created_at timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  CONSTRAINT recovery_codes_pk PRIMARY KEY (id)
+);
+
+ALTER TABLE public.recovery_codes ADD CONSTRAINT user_id_fk FOREIGN KEY (user_id)
+REFERENCES public.users (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+
+CREATE INDEX recovery_codes_user_id_index ON public.recovery_codes (user_id);
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS public.access_tokens_user_id_index CASCADE;
+DROP TABLE IF EXISTS public.access_tokens CASCADE;
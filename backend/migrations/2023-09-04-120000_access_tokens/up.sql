@@ -0,0 +1,18 @@
+-- NOTE Only a hash of the token is stored, the token itself is shown to the user once when it's created
+CREATE TABLE public.access_tokens (
+  id uuid NOT NULL,
+  user_id uuid NOT NULL,
+  name text NOT NULL,
+  token_hash text NOT NULL,
+  scopes text[] NOT NULL,
+  last_used_at timestamptz,
+  created_at timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  CONSTRAINT access_tokens_pk PRIMARY KEY (id),
+  CONSTRAINT access_tokens_token_hash_unique UNIQUE (token_hash)
+);
+
+ALTER TABLE public.access_tokens ADD CONSTRAINT user_id_fk FOREIGN KEY (user_id)
+REFERENCES public.users (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+
+CREATE INDEX access_tokens_user_id_index ON public.access_tokens (user_id);
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS public.images_user_id_index CASCADE;
+DROP TABLE IF EXISTS public.images CASCADE;
@@ -0,0 +1,17 @@
+-- NOTE The image bytes are kept in storage, this only records what was validated when they were uploaded
+CREATE TABLE public.images (
+  id uuid NOT NULL,
+  user_id uuid NOT NULL,
+  content_type text NOT NULL,
+  byte_size integer NOT NULL,
+  width integer NOT NULL,
+  height integer NOT NULL,
+  created_at timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  CONSTRAINT images_pk PRIMARY KEY (id)
+);
+
+ALTER TABLE public.images ADD CONSTRAINT user_id_fk FOREIGN KEY (user_id)
+REFERENCES public.users (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+
+CREATE INDEX images_user_id_index ON public.images (user_id);
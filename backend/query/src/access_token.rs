@@ -0,0 +1,155 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use uchat_domain::{
+    access_token::Scope,
+    ids::{AccessTokenId, UserId},
+};
+
+use crate::util::DeleteStatus;
+use crate::DieselError;
+
+#[derive(Clone, Debug, Queryable)]
+pub struct AccessToken {
+    pub id: AccessTokenId,
+    pub user_id: UserId,
+    pub name: String,
+    pub token_hash: String,
+    pub scopes: Vec<String>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl AccessToken {
+    // ? Scopes that are no longer known are dropped, so they can't grant anything
+    pub fn scopes(&self) -> Vec<Scope> {
+        self.scopes
+            .iter()
+            .filter_map(|scope| scope.parse().ok())
+            .collect()
+    }
+}
+
+pub fn new(
+    conn: &mut PgConnection,
+    user_id: UserId,
+    name: &str,
+    token_hash: &str,
+    scopes: &[Scope],
+) -> Result<AccessToken, DieselError> {
+    use crate::schema::access_tokens;
+
+    let scopes: Vec<String> = scopes.iter().map(Scope::to_string).collect();
+
+    diesel::insert_into(access_tokens::table)
+        .values((
+            access_tokens::id.eq(AccessTokenId::new()),
+            access_tokens::user_id.eq(user_id),
+            access_tokens::name.eq(name),
+            access_tokens::token_hash.eq(token_hash),
+            access_tokens::scopes.eq(scopes),
+        ))
+        .get_result(conn)
+}
+
+pub fn find(conn: &mut PgConnection, token_hash: &str) -> Result<Option<AccessToken>, DieselError> {
+    use crate::schema::access_tokens;
+
+    access_tokens::table
+        .filter(access_tokens::token_hash.eq(token_hash))
+        .get_result(conn)
+        .optional()
+}
+
+/// Lists the tokens of the user, newest first
+pub fn get_all(conn: &mut PgConnection, user_id: UserId) -> Result<Vec<AccessToken>, DieselError> {
+    use crate::schema::access_tokens;
+
+    access_tokens::table
+        .filter(access_tokens::user_id.eq(user_id))
+        .order(access_tokens::created_at.desc())
+        .get_results(conn)
+}
+
+pub fn touch(conn: &mut PgConnection, id: AccessTokenId) -> Result<(), DieselError> {
+    use crate::schema::access_tokens;
+
+    diesel::update(access_tokens::table)
+        .filter(access_tokens::id.eq(id))
+        .set(access_tokens::last_used_at.eq(Utc::now()))
+        .execute(conn)
+        .map(|_| ())
+}
+
+/// Revokes a token of the user, tokens of other users are left untouched
+pub fn revoke(
+    conn: &mut PgConnection,
+    user_id: UserId,
+    id: AccessTokenId,
+) -> Result<DeleteStatus, DieselError> {
+    use crate::schema::access_tokens;
+
+    diesel::delete(access_tokens::table)
+        .filter(access_tokens::id.eq(id))
+        .filter(access_tokens::user_id.eq(user_id))
+        .execute(conn)
+        .map(DeleteStatus::new)
+}
+
+/// Revokes every token of the user, returning how many were revoked
+pub fn delete_all(conn: &mut PgConnection, user_id: UserId) -> Result<usize, DieselError> {
+    use crate::schema::access_tokens;
+
+    diesel::delete(access_tokens::table)
+        .filter(access_tokens::user_id.eq(user_id))
+        .execute(conn)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use uchat_domain::access_token::Scope;
+
+    use crate::test_db::{self, Result};
+    use crate::user::tests::util as test_user;
+    use crate::util::DeleteStatus;
+
+    #[test]
+    fn tokens_are_found_by_hash_and_revoked_per_user() -> Result<()> {
+        let mut conn = test_db::new_connection();
+        let user1 = test_user::new_user(&mut conn, "user 1");
+        let user2 = test_user::new_user(&mut conn, "user 2");
+
+        let token = super::new(
+            &mut conn,
+            user1.id,
+            "bot",
+            "hash",
+            &[Scope::Read, Scope::Post],
+        )?;
+        assert_eq!(token.scopes(), vec![Scope::Read, Scope::Post]);
+
+        let found = super::find(&mut conn, "hash")?.expect("token not found");
+        assert_eq!(found.id, token.id);
+        assert!(super::find(&mut conn, "other hash")?.is_none());
+
+        super::touch(&mut conn, token.id)?;
+        let listed = super::get_all(&mut conn, user1.id)?;
+        assert_eq!(listed.len(), 1);
+        assert!(listed[0].last_used_at.is_some());
+
+        assert_eq!(
+            super::revoke(&mut conn, user2.id, token.id)?,
+            DeleteStatus::NotFound
+        );
+        assert_eq!(
+            super::revoke(&mut conn, user1.id, token.id)?,
+            DeleteStatus::Deleted
+        );
+        assert!(super::find(&mut conn, "hash")?.is_none());
+
+        super::new(&mut conn, user1.id, "bot", "hash", &[Scope::Read])?;
+        super::new(&mut conn, user2.id, "bot", "other hash", &[Scope::Read])?;
+        assert_eq!(super::delete_all(&mut conn, user1.id)?, 1);
+        assert!(super::find(&mut conn, "other hash")?.is_some());
+        Ok(())
+    }
+}
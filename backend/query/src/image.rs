@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use uchat_domain::ids::{ImageId, UserId};
+
+use crate::util::DeleteStatus;
+use crate::{schema, DieselError};
+
+/// What was checked about an image when it was uploaded, the bytes themselves are kept in storage
+#[derive(Clone, Debug, PartialEq, Queryable, Insertable)]
+#[diesel(table_name = schema::images)]
+pub struct Image {
+    pub id: ImageId,
+    pub user_id: UserId,
+    pub content_type: String,
+    pub byte_size: i32,
+    pub width: i32,
+    pub height: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+pub fn new(conn: &mut PgConnection, image: &Image) -> Result<(), DieselError> {
+    use crate::schema::images;
+
+    diesel::insert_into(images::table)
+        .values(image)
+        .execute(conn)
+        .map(|_| ())
+}
+
+pub fn get(conn: &mut PgConnection, id: ImageId) -> Result<Option<Image>, DieselError> {
+    use crate::schema::images;
+
+    images::table
+        .filter(images::id.eq(id))
+        .get_result(conn)
+        .optional()
+}
+
+pub fn get_many(
+    conn: &mut PgConnection,
+    ids: &[ImageId],
+) -> Result<HashMap<ImageId, Image>, DieselError> {
+    use crate::schema::images;
+
+    if ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    Ok(images::table
+        .filter(images::id.eq_any(ids))
+        .load::<Image>(conn)?
+        .into_iter()
+        .map(|image| (image.id, image))
+        .collect())
+}
+
+/// Deletes an image record, as long as `user_id` uploaded it
+pub fn delete(
+    conn: &mut PgConnection,
+    user_id: UserId,
+    id: ImageId,
+) -> Result<DeleteStatus, DieselError> {
+    use crate::schema::images;
+
+    diesel::delete(images::table)
+        .filter(images::id.eq(id))
+        .filter(images::user_id.eq(user_id))
+        .execute(conn)
+        .map(DeleteStatus::new)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use chrono::Utc;
+    use uchat_domain::ids::ImageId;
+
+    use super::Image;
+    use crate::test_db::{self, Result};
+    use crate::user::tests::util as test_user;
+    use crate::util::DeleteStatus;
+
+    #[test]
+    fn images_are_recorded_and_deleted() -> Result<()> {
+        let mut conn = test_db::new_connection();
+        let user = test_user::new_user(&mut conn, "user 1");
+
+        let image = Image {
+            id: ImageId::new(),
+            user_id: user.id,
+            content_type: "image/png".to_owned(),
+            byte_size: 1024,
+            width: 16,
+            height: 16,
+            created_at: Utc::now(),
+        };
+        super::new(&mut conn, &image)?;
+
+        let saved = super::get(&mut conn, image.id)?.expect("image not found");
+        assert_eq!(saved.content_type, "image/png");
+        assert_eq!((saved.width, saved.height), (16, 16));
+        assert!(super::get_many(&mut conn, &[image.id])?.contains_key(&image.id));
+
+        let other = test_user::new_user(&mut conn, "user 2");
+        assert_eq!(
+            super::delete(&mut conn, other.id, image.id)?,
+            DeleteStatus::NotFound
+        );
+        assert!(super::get(&mut conn, image.id)?.is_some());
+
+        assert_eq!(
+            super::delete(&mut conn, user.id, image.id)?,
+            DeleteStatus::Deleted
+        );
+        assert!(super::get(&mut conn, image.id)?.is_none());
+        assert_eq!(
+            super::delete(&mut conn, user.id, image.id)?,
+            DeleteStatus::NotFound
+        );
+        Ok(())
+    }
+}
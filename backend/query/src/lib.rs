@@ -13,6 +13,11 @@ pub use error::QueryError;
 pub mod util;
 pub use util::{AsyncConnection, AsyncConnectionPool, OwnedAsyncConnection};
 
+pub mod access_token;
+pub mod image;
+pub mod message;
+pub mod password_reset;
 pub mod post;
 pub mod session;
+pub mod totp;
 pub mod user;
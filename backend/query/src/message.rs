@@ -0,0 +1,107 @@
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::{PgConnection, RunQueryDsl};
+use uchat_domain::ids::UserId;
+
+use crate::{post::Post, DieselError};
+
+/// Gets the latest messages exchanged between two users, newest first
+///
+/// NOTE Only messages sent from one of the users directly to the other are returned
+pub fn get_conversation(
+    conn: &mut PgConnection,
+    user_id: UserId,
+    with_user: UserId,
+    limit: i64,
+) -> Result<Vec<Post>, DieselError> {
+    use crate::schema::posts;
+
+    let sent = posts::user_id
+        .eq(user_id)
+        .and(posts::direct_message_to.eq(with_user));
+    let received = posts::user_id
+        .eq(with_user)
+        .and(posts::direct_message_to.eq(user_id));
+
+    posts::table
+        .filter(sent.or(received))
+        .filter(posts::time_posted.lt(Utc::now()))
+        .order(posts::time_posted.desc())
+        .limit(limit)
+        .get_results(conn)
+}
+
+/// Gets the latest message of every conversation `user_id` is part of, the most recent first
+pub fn get_latest_messages(
+    conn: &mut PgConnection,
+    user_id: UserId,
+) -> Result<Vec<Post>, DieselError> {
+    use diesel::sql_types::{Timestamptz, Uuid};
+
+    // ? A conversation is identified by the other user, whoever sent the message
+    diesel::sql_query(
+        r#"
+        SELECT id, user_id, content, time_posted, direct_message_to, reply_to, created_at
+        FROM (
+            SELECT DISTINCT ON (partner_id) *
+            FROM (
+                SELECT posts.*,
+                    CASE WHEN user_id = $1 THEN direct_message_to ELSE user_id END AS partner_id
+                FROM posts
+                WHERE ((user_id = $1 AND direct_message_to IS NOT NULL) OR direct_message_to = $1)
+                    AND time_posted < $2
+            ) AS messages
+            ORDER BY partner_id, time_posted DESC, id DESC
+        ) AS latest
+        ORDER BY time_posted DESC, id DESC
+        "#,
+    )
+    .bind::<Uuid, _>(user_id)
+    .bind::<Timestamptz, _>(Utc::now())
+    .load(conn)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::test_db::{self, Result};
+    use crate::user::tests::util as test_user;
+    use uchat_endpoint::post::types::NewPostOptions;
+
+    use crate::post::{self, tests::util as test_post, Post};
+    use diesel::PgConnection;
+    use uchat_domain::ids::UserId;
+
+    fn send(conn: &mut PgConnection, from: UserId, to: UserId, msg: &str) {
+        let options = NewPostOptions {
+            direct_message_to: Some(to),
+            ..Default::default()
+        };
+        let post = Post::new(from, test_post::new_chat(msg), options).unwrap();
+        post::new(conn, post).unwrap();
+    }
+
+    #[test]
+    fn conversation_only_includes_participants() -> Result<()> {
+        let mut conn = test_db::new_connection();
+        let user1 = test_user::new_user(&mut conn, "user 1");
+        let user2 = test_user::new_user(&mut conn, "user 2");
+        let user3 = test_user::new_user(&mut conn, "user 3");
+
+        send(&mut conn, user1.id, user2.id, "hi");
+        send(&mut conn, user2.id, user1.id, "hello");
+        send(&mut conn, user3.id, user2.id, "not for user 1");
+
+        let messages = super::get_conversation(&mut conn, user1.id, user2.id, 10)?;
+        assert_eq!(messages.len(), 2);
+
+        let latest = super::get_latest_messages(&mut conn, user2.id)?;
+        assert_eq!(latest.len(), 2);
+        assert_eq!(latest[0].user_id, user3.id);
+
+        let latest = super::get_latest_messages(&mut conn, user1.id)?;
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest[0].user_id, user2.id);
+
+        Ok(())
+    }
+}
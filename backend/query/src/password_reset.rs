@@ -0,0 +1,157 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use password_hash::PasswordHashString;
+use uchat_domain::ids::UserId;
+
+use crate::DieselError;
+
+pub fn new(
+    conn: &mut PgConnection,
+    user_id: UserId,
+    token_hash: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<(), DieselError> {
+    use crate::schema::password_resets;
+
+    diesel::insert_into(password_resets::table)
+        .values((
+            password_resets::token_hash.eq(token_hash),
+            password_resets::user_id.eq(user_id),
+            password_resets::expires_at.eq(expires_at),
+        ))
+        .execute(conn)
+        .map(|_| ())
+}
+
+/// Finds the user a reset belongs to if it hasn't expired, without using it up
+pub fn find(conn: &mut PgConnection, token_hash: &str) -> Result<Option<UserId>, DieselError> {
+    use crate::schema::password_resets;
+
+    password_resets::table
+        .filter(password_resets::token_hash.eq(token_hash))
+        .filter(password_resets::expires_at.gt(Utc::now()))
+        .select(password_resets::user_id)
+        .get_result(conn)
+        .optional()
+}
+
+/// Deletes the reset so it can't be used again, returning the user it belongs to if it hasn't expired
+pub fn consume(conn: &mut PgConnection, token_hash: &str) -> Result<Option<UserId>, DieselError> {
+    use crate::schema::password_resets;
+
+    diesel::delete(password_resets::table)
+        .filter(password_resets::token_hash.eq(token_hash))
+        .filter(password_resets::expires_at.gt(Utc::now()))
+        .returning(password_resets::user_id)
+        .get_result(conn)
+        .optional()
+}
+
+pub fn delete_all(conn: &mut PgConnection, user_id: UserId) -> Result<usize, DieselError> {
+    use crate::schema::password_resets;
+
+    diesel::delete(password_resets::table)
+        .filter(password_resets::user_id.eq(user_id))
+        .execute(conn)
+}
+
+/// Consumes the reset and sets the new password, then revokes every session and token of the user
+pub fn reset_password(
+    conn: &mut PgConnection,
+    token_hash: &str,
+    password_hash: PasswordHashString,
+) -> Result<Option<UserId>, DieselError> {
+    use crate::schema::users;
+
+    conn.transaction(|conn| {
+        let Some(user_id) = consume(conn, token_hash)? else {
+            return Ok(None);
+        };
+
+        diesel::update(users::table)
+            .filter(users::id.eq(user_id))
+            .set(users::password_hash.eq(password_hash.to_string()))
+            .execute(conn)?;
+
+        delete_all(conn, user_id)?;
+        crate::session::revoke_all(conn, user_id)?;
+
+        Ok(Some(user_id))
+    })
+}
+
+pub fn delete_expired(conn: &mut PgConnection) -> Result<usize, DieselError> {
+    use crate::schema::password_resets;
+
+    diesel::delete(password_resets::table)
+        .filter(password_resets::expires_at.le(Utc::now()))
+        .execute(conn)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use chrono::{Duration, Utc};
+    use uchat_domain::access_token::Scope;
+
+    use crate::test_db::{self, Result};
+    use crate::user::tests::util as test_user;
+
+    #[test]
+    fn resets_can_only_be_used_once_before_expiring() -> Result<()> {
+        let mut conn = test_db::new_connection();
+        let user = test_user::new_user(&mut conn, "user 1");
+
+        super::new(&mut conn, user.id, "valid", Utc::now() + Duration::hours(1))?;
+        super::new(
+            &mut conn,
+            user.id,
+            "expired",
+            Utc::now() - Duration::hours(1),
+        )?;
+
+        assert_eq!(super::find(&mut conn, "expired")?, None);
+        assert_eq!(super::find(&mut conn, "valid")?, Some(user.id));
+
+        assert_eq!(super::consume(&mut conn, "expired")?, None);
+        assert_eq!(super::consume(&mut conn, "valid")?, Some(user.id));
+        assert_eq!(super::consume(&mut conn, "valid")?, None);
+
+        assert_eq!(super::delete_expired(&mut conn)?, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn resetting_password_revokes_sessions_and_tokens() -> Result<()> {
+        let mut conn = test_db::new_connection();
+        let user = test_user::new_user(&mut conn, "user 1");
+
+        let session = crate::session::new(
+            &mut conn,
+            user.id,
+            Duration::weeks(1),
+            serde_json::json!({}).into(),
+        )?;
+        crate::access_token::new(&mut conn, user.id, "bot", "token", &[Scope::Read])?;
+        super::new(&mut conn, user.id, "first", Utc::now() + Duration::hours(1))?;
+        super::new(
+            &mut conn,
+            user.id,
+            "second",
+            Utc::now() + Duration::hours(1),
+        )?;
+
+        let hash = uchat_crypto::hash_password("new password")?;
+        assert_eq!(
+            super::reset_password(&mut conn, "first", hash.clone())?,
+            Some(user.id)
+        );
+
+        let user = crate::user::get(&mut conn, user.id)?;
+        assert_eq!(user.password_hash, hash.to_string());
+        assert!(crate::session::get(&mut conn, session.id)?.is_none());
+        assert!(crate::access_token::find(&mut conn, "token")?.is_none());
+        // ? Other pending resets are invalidated as well
+        assert_eq!(super::reset_password(&mut conn, "second", hash)?, None);
+        Ok(())
+    }
+}
@@ -2,17 +2,41 @@ use chrono::{DateTime, Utc};
 use diesel::prelude::*;
 use diesel::{PgConnection, RunQueryDsl};
 
+use std::collections::{HashMap, HashSet};
+
 use serde::{Deserialize, Serialize};
-use uchat_domain::ids::{PollChoiceId, PostId, UserId};
+use uchat_domain::ids::{ImageId, PollChoiceId, PostId, UserId};
 use uchat_endpoint::post::types::VoteCast;
 
+use crate::image::Image;
+use crate::user::User;
 use crate::util::{is_one, DeleteStatus};
 use crate::{schema, DieselError};
 
 #[derive(Clone, Debug, DieselNewType, Serialize, Deserialize)]
 pub struct Content(pub serde_json::Value);
 
-#[derive(Debug, Queryable, Selectable, Insertable)]
+impl Content {
+    /// Ids of the uploaded images in the content, read without deserializing all of it
+    pub fn image_ids(&self) -> Vec<ImageId> {
+        let gallery = self
+            .0
+            .pointer("/Gallery/images")
+            .and_then(|images| images.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|image| image.pointer("/kind/Id"));
+
+        self.0
+            .pointer("/Image/kind/Id")
+            .into_iter()
+            .chain(gallery)
+            .filter_map(|id| serde_json::from_value(id.clone()).ok())
+            .collect()
+    }
+}
+
+#[derive(Debug, Queryable, QueryableByName, Selectable, Insertable)]
 #[diesel(table_name = schema::posts)]
 pub struct Post {
     pub id: PostId,
@@ -76,69 +100,426 @@ pub fn get(conn: &mut PgConnection, post_id: PostId) -> Result<Post, DieselError
     posts.filter(id.eq(post_id.as_uuid())).get_result(conn)
 }
 
+/// Keyset pagination position, only posts older than it will be returned
+///
+/// ? Posts are ordered by `time_posted` and then `id`, so ties are resolved deterministically
+pub type PostCursor = (DateTime<Utc>, PostId);
+
+pub const POSTS_PER_PAGE: i64 = 30;
+
+/// NOTE Starting from the current time with a nil id means no posts are skipped besides scheduled ones
+fn cursor_or_now(cursor: Option<PostCursor>) -> PostCursor {
+    cursor.unwrap_or_else(|| (Utc::now(), PostId::from(uuid::Uuid::nil())))
+}
+
 pub fn get_public_posts(
     conn: &mut PgConnection,
     user_id: UserId,
+    cursor: Option<PostCursor>,
 ) -> Result<Vec<Post>, DieselError> {
     use crate::schema::posts;
+    let (before_time, before_id) = cursor_or_now(cursor);
+
+    // NOTE Filtering by user_id and time_posted allows the query to use `post_pagination_index`
     posts::table
         .filter(posts::user_id.eq(user_id))
         .filter(posts::time_posted.lt(Utc::now()))
         .filter(posts::direct_message_to.is_null())
-        .order(posts::time_posted.desc())
-        .limit(30)
+        .filter(
+            posts::time_posted.lt(before_time).or(posts::time_posted
+                .eq(before_time)
+                .and(posts::id.lt(before_id))),
+        )
+        .order((posts::time_posted.desc(), posts::id.desc()))
+        .limit(POSTS_PER_PAGE)
         .get_results(conn)
 }
 
-pub fn get_trending(conn: &mut PgConnection) -> Result<Vec<Post>, DieselError> {
+/// Posts the user scheduled for a future time, the ones going out soonest first
+pub fn get_scheduled_posts(
+    conn: &mut PgConnection,
+    user_id: UserId,
+) -> Result<Vec<Post>, DieselError> {
     use crate::schema::posts;
+
     posts::table
-        .filter(posts::time_posted.lt(Utc::now()))
+        .filter(posts::user_id.eq(user_id))
+        .filter(posts::time_posted.gt(Utc::now()))
         .filter(posts::direct_message_to.is_null())
-        .order(posts::time_posted.desc())
-        .limit(30)
+        .order((posts::time_posted.asc(), posts::id.asc()))
         .get_results(conn)
 }
 
-pub fn bookmark(
+/// Moves a pending post to another time, returns `false` if the user has no such scheduled post
+///
+/// NOTE Posts that were already published can't be rescheduled
+pub fn reschedule(
     conn: &mut PgConnection,
     user_id: UserId,
     post_id: PostId,
-) -> Result<(), DieselError> {
-    let uid = user_id;
-    let pid = post_id;
+    time_posted: DateTime<Utc>,
+) -> Result<bool, DieselError> {
+    use crate::schema::posts;
 
-    {
-        use crate::schema::bookmarks::dsl::*;
+    diesel::update(posts::table)
+        .filter(posts::id.eq(post_id))
+        .filter(posts::user_id.eq(user_id))
+        .filter(posts::time_posted.gt(Utc::now()))
+        .set(posts::time_posted.eq(time_posted))
+        .execute(conn)
+        .map(|count| count > 0)
+}
 
-        diesel::insert_into(bookmarks)
-            .values((user_id.eq(uid), post_id.eq(pid)))
-            .on_conflict((user_id, post_id))
-            .do_nothing()
-            .execute(conn)
-            .map(|_| ())
+/// Deletes a post that has not been published yet, returning its content and that of its revisions
+pub fn cancel_scheduled(
+    conn: &mut PgConnection,
+    user_id: UserId,
+    post_id: PostId,
+) -> Result<Option<Vec<Content>>, DieselError> {
+    use crate::schema::{post_revisions, posts};
+
+    conn.transaction::<Option<Vec<Content>>, DieselError, _>(|conn| {
+        let revisions = post_revisions::table
+            .filter(post_revisions::post_id.eq(post_id))
+            .select(post_revisions::content)
+            .load::<Content>(conn)?;
+
+        let content = diesel::delete(posts::table)
+            .filter(posts::id.eq(post_id))
+            .filter(posts::user_id.eq(user_id))
+            .filter(posts::time_posted.gt(Utc::now()))
+            .returning(posts::content)
+            .get_result::<Content>(conn)
+            .optional()?;
+
+        Ok(content.map(|content| {
+            let mut deleted = revisions;
+            deleted.push(content);
+            deleted
+        }))
+    })
+}
+
+/// Weights used to score trending posts
+#[derive(Clone, Debug)]
+pub struct TrendingParams {
+    /// only posts made within this many hours are considered
+    pub window_hours: i32,
+    pub like_weight: f64,
+    pub dislike_weight: f64,
+    pub boost_weight: f64,
+    pub reply_weight: f64,
+    /// how fast the score decays as the post gets older
+    pub gravity: f64,
+    /// maximum amount of posts kept in `trending_posts`
+    pub max_posts: i64,
+}
+
+/// Recomputes the `trending_posts` table, returning the amount of trending posts
+///
+/// ? score = (likes - dislikes + boosts + replies, each weighted) / (age in hours + 2) ^ gravity
+///
+/// NOTE The current time is sent as a parameter since `now()` is frozen for the whole transaction
+pub fn refresh_trending(
+    conn: &mut PgConnection,
+    params: &TrendingParams,
+) -> Result<usize, DieselError> {
+    use diesel::sql_types::{BigInt, Double, Integer, Timestamptz};
+
+    conn.transaction::<usize, DieselError, _>(|conn| {
+        diesel::delete(schema::trending_posts::table).execute(conn)?;
+
+        diesel::sql_query(
+            r#"
+            INSERT INTO trending_posts (post_id, rank, score, refreshed_at)
+            SELECT scored.id, row_number() OVER (ORDER BY scored.score DESC, scored.id DESC), scored.score, $8
+            FROM (
+                SELECT p.id, (
+                    $1 * (SELECT count(*) FROM reactions r WHERE r.post_id = p.id AND r.like_status = 1)
+                    - $2 * (SELECT count(*) FROM reactions r WHERE r.post_id = p.id AND r.like_status = -1)
+                    + $3 * (SELECT count(*) FROM boosts b WHERE b.post_id = p.id)
+                    + $4 * (
+                        SELECT count(*) FROM posts reply
+                        WHERE reply.reply_to = p.id
+                            AND reply.direct_message_to IS NULL
+                            AND reply.time_posted < $8
+                    )
+                ) / power(extract(epoch FROM $8 - p.time_posted)::double precision / 3600.0 + 2.0, $5) AS score
+                FROM posts p
+                WHERE p.direct_message_to IS NULL
+                    AND p.time_posted < $8
+                    AND p.time_posted > $8 - make_interval(hours => $6)
+            ) AS scored
+            ORDER BY scored.score DESC, scored.id DESC
+            LIMIT $7
+            "#,
+        )
+        .bind::<Double, _>(params.like_weight)
+        .bind::<Double, _>(params.dislike_weight)
+        .bind::<Double, _>(params.boost_weight)
+        .bind::<Double, _>(params.reply_weight)
+        .bind::<Double, _>(params.gravity)
+        .bind::<Integer, _>(params.window_hours)
+        .bind::<BigInt, _>(params.max_posts)
+        .bind::<Timestamptz, _>(Utc::now())
+        .execute(conn)
+    })
+}
+
+/// Keyset pagination position in the trending posts, only posts ranked lower will be returned
+///
+/// ? Ranks are renumbered on every refresh, so the score and id of the last post are used instead
+pub type TrendingCursor = (f64, PostId);
+
+/// Gets trending posts ranked after `cursor`, along with their score
+///
+/// NOTE Scores are only updated by `refresh_trending`
+pub fn get_trending(
+    conn: &mut PgConnection,
+    cursor: Option<TrendingCursor>,
+) -> Result<Vec<(Post, f64)>, DieselError> {
+    use crate::schema::{posts, trending_posts};
+
+    let mut query = trending_posts::table
+        .inner_join(posts::table)
+        .select((Post::as_select(), trending_posts::score))
+        .order((trending_posts::score.desc(), trending_posts::post_id.desc()))
+        .limit(POSTS_PER_PAGE)
+        .into_boxed();
+
+    if let Some((score, post_id)) = cursor {
+        query = query.filter(
+            trending_posts::score.lt(score).or(trending_posts::score
+                .eq(score)
+                .and(trending_posts::post_id.lt(post_id))),
+        );
     }
+
+    query.get_results(conn)
+}
+
+// ? Guards against runaway recursion in the thread queries
+const MAX_THREAD_DEPTH: i32 = 1000;
+
+/// Gets the chain of posts that `post_id` is replying to, starting from the root of the thread
+///
+/// NOTE The chain stops at the first post `viewer` can't see, which is a direct message or
+/// another user's scheduled post
+pub fn get_ancestors(
+    conn: &mut PgConnection,
+    post_id: PostId,
+    viewer: UserId,
+) -> Result<Vec<Post>, DieselError> {
+    use diesel::sql_types::{Integer, Timestamptz, Uuid};
+
+    diesel::sql_query(
+        r#"
+        WITH RECURSIVE ancestors AS (
+            SELECT parent.*, 1 AS depth
+            FROM posts child
+            INNER JOIN posts parent ON parent.id = child.reply_to
+            WHERE child.id = $1
+                AND parent.direct_message_to IS NULL
+                AND (parent.time_posted < $3 OR parent.user_id = $4)
+            UNION ALL
+            SELECT parent.*, a.depth + 1
+            FROM ancestors a
+            INNER JOIN posts parent ON parent.id = a.reply_to
+            WHERE a.depth < $2
+                AND parent.direct_message_to IS NULL
+                AND (parent.time_posted < $3 OR parent.user_id = $4)
+        )
+        SELECT id, user_id, content, time_posted, direct_message_to, reply_to, created_at
+        FROM ancestors
+        ORDER BY depth DESC
+        "#,
+    )
+    .bind::<Uuid, _>(post_id)
+    .bind::<Integer, _>(MAX_THREAD_DEPTH)
+    .bind::<Timestamptz, _>(Utc::now())
+    .bind::<Uuid, _>(viewer)
+    .load(conn)
 }
 
-pub fn get_bookmark(
+/// Gets the replies to `post_id` at any depth, ordered from oldest to newest
+///
+/// NOTE Unlike the feeds, the cursor here is the last post already seen, so newer posts are returned
+///
+/// ? Replies to a post `viewer` can't see are hidden along with it, like the ancestors
+pub fn get_descendants(
+    conn: &mut PgConnection,
+    post_id: PostId,
+    viewer: UserId,
+    cursor: Option<PostCursor>,
+) -> Result<Vec<Post>, DieselError> {
+    use diesel::sql_types::{BigInt, Integer, Nullable, Timestamptz, Uuid};
+
+    let (after_time, after_id) = match cursor {
+        Some((time, id)) => (Some(time), Some(id)),
+        None => (None, None),
+    };
+
+    diesel::sql_query(
+        r#"
+        WITH RECURSIVE descendants AS (
+            SELECT reply.*, 1 AS depth
+            FROM posts reply
+            WHERE reply.reply_to = $1
+                AND reply.direct_message_to IS NULL
+                AND (reply.time_posted < $3 OR reply.user_id = $7)
+            UNION ALL
+            SELECT reply.*, d.depth + 1
+            FROM descendants d
+            INNER JOIN posts reply ON reply.reply_to = d.id
+            WHERE d.depth < $2
+                AND reply.direct_message_to IS NULL
+                AND (reply.time_posted < $3 OR reply.user_id = $7)
+        )
+        SELECT id, user_id, content, time_posted, direct_message_to, reply_to, created_at
+        FROM descendants
+        WHERE $4 IS NULL OR time_posted > $4 OR (time_posted = $4 AND id > $5)
+        ORDER BY time_posted ASC, id ASC
+        LIMIT $6
+        "#,
+    )
+    .bind::<Uuid, _>(post_id)
+    .bind::<Integer, _>(MAX_THREAD_DEPTH)
+    .bind::<Timestamptz, _>(Utc::now())
+    .bind::<Nullable<Timestamptz>, _>(after_time)
+    .bind::<Nullable<Uuid>, _>(after_id)
+    .bind::<BigInt, _>(POSTS_PER_PAGE)
+    .bind::<Uuid, _>(viewer)
+    .load(conn)
+}
+
+#[derive(Debug, Queryable, Insertable)]
+#[diesel(table_name = schema::post_revisions)]
+pub struct PostRevision {
+    pub id: uuid::Uuid,
+    pub post_id: PostId,
+    /// the content the post had before it was edited
+    pub content: Content,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Replaces the content of a post, keeping the previous content as a revision
+///
+/// Returns `false` if the user has no such post
+pub fn edit(
     conn: &mut PgConnection,
     user_id: UserId,
     post_id: PostId,
+    content: Content,
 ) -> Result<bool, DieselError> {
+    use crate::schema::{post_revisions, posts};
+
+    conn.transaction::<bool, DieselError, _>(|conn| {
+        let previous = posts::table
+            .filter(posts::id.eq(post_id))
+            .filter(posts::user_id.eq(user_id))
+            .select(posts::content)
+            .for_update()
+            .get_result::<Content>(conn)
+            .optional()?;
+
+        let Some(previous) = previous else {
+            return Ok(false);
+        };
+
+        diesel::insert_into(post_revisions::table)
+            .values(PostRevision {
+                id: uuid::Uuid::new_v4(),
+                post_id,
+                content: previous,
+                created_at: Utc::now(),
+            })
+            .execute(conn)?;
+
+        diesel::update(posts::table)
+            .filter(posts::id.eq(post_id))
+            .set(posts::content.eq(content))
+            .execute(conn)?;
+
+        Ok(true)
+    })
+}
+
+/// Past contents of a post, the most recently replaced first
+pub fn get_revisions(
+    conn: &mut PgConnection,
+    post_id: PostId,
+) -> Result<Vec<PostRevision>, DieselError> {
+    use crate::schema::post_revisions;
+
+    post_revisions::table
+        .filter(post_revisions::post_id.eq(post_id))
+        .order(post_revisions::created_at.desc())
+        .get_results(conn)
+}
+
+/// Deletes a post, leaving its replies in place as posts that don't reply to anything
+///
+/// NOTE Replies are detached first, since the `comment_fk` cascade would take down posts by other
+/// users, along with whole threads below them
+///
+/// Returns the content of the post and of its past revisions, along with who wrote it, so files
+/// referenced by them can be removed, or `None` if the user has no such post
+pub fn delete(
+    conn: &mut PgConnection,
+    user_id: UserId,
+    post_id: PostId,
+) -> Result<Option<Vec<(UserId, Content)>>, DieselError> {
+    use crate::schema::{post_revisions, posts};
+
+    conn.transaction::<Option<Vec<(UserId, Content)>>, DieselError, _>(|conn| {
+        let content = posts::table
+            .filter(posts::id.eq(post_id))
+            .filter(posts::user_id.eq(user_id))
+            .select(posts::content)
+            .get_result::<Content>(conn)
+            .optional()?;
+        let Some(content) = content else {
+            return Ok(None);
+        };
+        let revisions = post_revisions::table
+            .filter(post_revisions::post_id.eq(post_id))
+            .select(post_revisions::content)
+            .load::<Content>(conn)?;
+
+        diesel::update(posts::table)
+            .filter(posts::reply_to.eq(post_id))
+            .set(posts::reply_to.eq(None::<PostId>))
+            .execute(conn)?;
+        diesel::delete(posts::table)
+            .filter(posts::id.eq(post_id))
+            .execute(conn)?;
+
+        Ok(Some(
+            std::iter::once(content)
+                .chain(revisions)
+                .map(|content| (user_id, content))
+                .collect(),
+        ))
+    })
+}
+
+pub fn bookmark(
+    conn: &mut PgConnection,
+    user_id: UserId,
+    post_id: PostId,
+) -> Result<(), DieselError> {
     let uid = user_id;
     let pid = post_id;
 
     {
         use crate::schema::bookmarks::dsl::*;
-        use diesel::dsl::count;
 
-        bookmarks
-            .filter(post_id.eq(pid))
-            .filter(user_id.eq(uid))
-            .select(count(post_id))
-            .get_result(conn)
-            .optional()
-            .map(is_one)
+        diesel::insert_into(bookmarks)
+            .values((user_id.eq(uid), post_id.eq(pid)))
+            .on_conflict((user_id, post_id))
+            .do_nothing()
+            .execute(conn)
+            .map(|_| ())
     }
 }
 
@@ -160,9 +541,6 @@ pub fn delete_bookmark(
     }
 }
 
-#[derive(Clone, Debug, DieselNewType, Serialize, Deserialize)]
-pub struct ReactionData(serde_json::Value);
-
 #[derive(Clone, Debug, Queryable, Insertable, Deserialize, Serialize)]
 #[diesel(table_name = schema::reactions)]
 pub struct Reaction {
@@ -170,8 +548,6 @@ pub struct Reaction {
     pub post_id: PostId,
     pub created_at: DateTime<Utc>,
     pub like_status: i16,
-    // ! Storing the reaction as a JSONB value is not scalable, should be a separate table
-    pub reaction: Option<ReactionData>,
 }
 
 pub fn react(conn: &mut PgConnection, reaction: Reaction) -> Result<(), DieselError> {
@@ -181,10 +557,7 @@ pub fn react(conn: &mut PgConnection, reaction: Reaction) -> Result<(), DieselEr
         .values(&reaction)
         .on_conflict((reactions::user_id, reactions::post_id))
         .do_update()
-        .set((
-            reactions::like_status.eq(&reaction.like_status),
-            reactions::reaction.eq(&reaction.reaction),
-        ))
+        .set(reactions::like_status.eq(&reaction.like_status))
         .execute(conn)
         .map(|_| ())
 }
@@ -207,6 +580,84 @@ pub fn get_reaction(
     }
 }
 
+pub fn add_emoji_reaction(
+    conn: &mut PgConnection,
+    user_id: UserId,
+    post_id: PostId,
+    emoji: &str,
+) -> Result<(), DieselError> {
+    use crate::schema::post_reactions;
+
+    diesel::insert_into(post_reactions::table)
+        .values((
+            post_reactions::user_id.eq(user_id),
+            post_reactions::post_id.eq(post_id),
+            post_reactions::emoji.eq(emoji),
+        ))
+        .on_conflict_do_nothing()
+        .execute(conn)
+        .map(|_| ())
+}
+
+pub fn remove_emoji_reaction(
+    conn: &mut PgConnection,
+    user_id: UserId,
+    post_id: PostId,
+    emoji: &str,
+) -> Result<DeleteStatus, DieselError> {
+    use crate::schema::post_reactions;
+
+    diesel::delete(post_reactions::table)
+        .filter(post_reactions::user_id.eq(user_id))
+        .filter(post_reactions::post_id.eq(post_id))
+        .filter(post_reactions::emoji.eq(emoji))
+        .execute(conn)
+        .map(DeleteStatus::new)
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct EmojiReactionCount {
+    pub emoji: String,
+    pub count: i64,
+    /// whether the user the reactions were aggregated for used this emoji
+    pub reacted: bool,
+}
+
+/// Counts the emoji reactions on a post, the most used emoji first
+pub fn aggregate_emoji_reactions(
+    conn: &mut PgConnection,
+    post_id: PostId,
+    user_id: Option<UserId>,
+) -> Result<Vec<EmojiReactionCount>, DieselError> {
+    use crate::schema::post_reactions;
+    use diesel::dsl::count_star;
+
+    let counts = post_reactions::table
+        .filter(post_reactions::post_id.eq(post_id))
+        .group_by(post_reactions::emoji)
+        .select((post_reactions::emoji, count_star()))
+        .order((count_star().desc(), post_reactions::emoji.asc()))
+        .load::<(String, i64)>(conn)?;
+
+    let reacted = match user_id {
+        Some(user_id) => post_reactions::table
+            .filter(post_reactions::post_id.eq(post_id))
+            .filter(post_reactions::user_id.eq(user_id))
+            .select(post_reactions::emoji)
+            .load::<String>(conn)?,
+        None => vec![],
+    };
+
+    Ok(counts
+        .into_iter()
+        .map(|(emoji, count)| EmojiReactionCount {
+            reacted: reacted.contains(&emoji),
+            emoji,
+            count,
+        })
+        .collect())
+}
+
 #[derive(Clone, Debug, Serialize)]
 pub struct AggregatePostInfo {
     pub post_id: PostId,
@@ -272,28 +723,6 @@ pub fn boost(
     }
 }
 
-pub fn get_boost(
-    conn: &mut PgConnection,
-    user_id: UserId,
-    post_id: PostId,
-) -> Result<bool, DieselError> {
-    let uid = user_id;
-    let pid = post_id;
-
-    {
-        use crate::schema::boosts::dsl::*;
-        use diesel::dsl::count;
-
-        boosts
-            .filter(post_id.eq(pid))
-            .filter(user_id.eq(uid))
-            .select(count(post_id))
-            .get_result(conn)
-            .optional()
-            .map(is_one)
-    }
-}
-
 pub fn delete_boost(
     conn: &mut PgConnection,
     user_id: UserId,
@@ -340,116 +769,321 @@ pub fn vote(
     }
 }
 
-pub fn did_vote(
+pub fn get_home_posts(
     conn: &mut PgConnection,
     user_id: UserId,
-    post_id: PostId,
-) -> Result<Option<PollChoiceId>, DieselError> {
-    let uid = user_id;
-    let pid = post_id;
-
-    {
-        use crate::schema::poll_votes::dsl::*;
-
-        poll_votes
-            .filter(post_id.eq(pid))
-            .filter(user_id.eq(uid))
-            .select(choice_id)
-            .get_result(conn)
-            .optional()
-    }
-}
-
-pub struct PollResults {
-    pub post_id: PostId,
-    pub results: Vec<(PollChoiceId, i64)>,
-}
-
-pub fn get_poll_results(
-    conn: &mut PgConnection,
-    post_id: PostId,
-) -> Result<PollResults, DieselError> {
-    let pid = post_id;
-
-    {
-        use crate::schema::poll_votes::dsl::*;
-
-        use diesel::dsl::count;
-
-        let results = poll_votes
-            .filter(post_id.eq(pid))
-            .group_by(choice_id)
-            .select((choice_id, count(choice_id)))
-            .load::<(PollChoiceId, i64)>(conn)?;
-
-        Ok(PollResults {
-            post_id: pid,
-            results,
-        })
-    }
-}
-
-pub fn get_home_posts(conn: &mut PgConnection, user_id: UserId) -> Result<Vec<Post>, DieselError> {
+    cursor: Option<PostCursor>,
+) -> Result<Vec<Post>, DieselError> {
     use crate::schema::{boosts, followers, posts};
     let uid = user_id;
+    let (before_time, before_id) = cursor_or_now(cursor);
     let on_schedule = posts::time_posted.lt(Utc::now());
     let public_only = posts::direct_message_to.is_null();
-    let by_time_posted = posts::time_posted.desc();
-    let limit = 30;
+    let before_cursor = posts::time_posted.lt(before_time).or(posts::time_posted
+        .eq(before_time)
+        .and(posts::id.lt(before_id)));
+    let by_time_posted = (posts::time_posted.desc(), posts::id.desc());
 
-    followers::table
+    let mut home_posts = followers::table
         .filter(followers::user_id.eq(uid))
         .inner_join(posts::table.on(followers::follows.eq(posts::user_id)))
         .filter(on_schedule)
         .filter(public_only)
+        .filter(before_cursor)
         .select(Post::as_select())
         .order(by_time_posted)
-        .limit(limit)
-        .union(
-            followers::table
-                .filter(followers::user_id.eq(uid))
-                .inner_join(boosts::table.on(boosts::user_id.eq(followers::follows)))
-                .inner_join(posts::table.on(posts::user_id.eq(boosts::post_id)))
-                .filter(on_schedule)
-                .filter(public_only)
-                .select(Post::as_select())
-                .order(by_time_posted)
-                .limit(limit),
-        )
-        .get_results(conn)
+        .limit(POSTS_PER_PAGE)
+        .get_results(conn)?;
+
+    let boosted_posts = followers::table
+        .filter(followers::user_id.eq(uid))
+        .inner_join(boosts::table.on(boosts::user_id.eq(followers::follows)))
+        .inner_join(posts::table.on(posts::id.eq(boosts::post_id)))
+        .filter(on_schedule)
+        .filter(public_only)
+        .filter(before_cursor)
+        .select(Post::as_select())
+        .order(by_time_posted)
+        .limit(POSTS_PER_PAGE)
+        .get_results(conn)?;
+
+    // NOTE Diesel can't order the results of a union, so both pages are merged here instead
+    home_posts.extend(boosted_posts);
+    home_posts.sort_by(|a, b| (b.time_posted, b.id).cmp(&(a.time_posted, a.id)));
+    home_posts.dedup_by_key(|post| post.id);
+    home_posts.truncate(POSTS_PER_PAGE as usize);
+
+    Ok(home_posts)
 }
 
-pub fn get_liked_posts(conn: &mut PgConnection, user_id: UserId) -> Result<Vec<Post>, DieselError> {
+pub fn get_liked_posts(
+    conn: &mut PgConnection,
+    user_id: UserId,
+    cursor: Option<PostCursor>,
+) -> Result<Vec<Post>, DieselError> {
     use crate::schema::{posts, reactions};
+    let (before_time, before_id) = cursor_or_now(cursor);
+    let on_schedule = posts::time_posted.lt(Utc::now());
+
     reactions::table
         .inner_join(posts::table)
         .filter(reactions::user_id.eq(user_id))
         .filter(reactions::like_status.eq(1))
+        .filter(on_schedule)
         .filter(posts::direct_message_to.is_null())
+        .filter(
+            posts::time_posted.lt(before_time).or(posts::time_posted
+                .eq(before_time)
+                .and(posts::id.lt(before_id))),
+        )
         .select(Post::as_select())
-        .limit(30)
+        .order((posts::time_posted.desc(), posts::id.desc()))
+        .limit(POSTS_PER_PAGE)
         .get_results(conn)
 }
 
 pub fn get_bookmarked_posts(
     conn: &mut PgConnection,
     user_id: UserId,
+    cursor: Option<PostCursor>,
 ) -> Result<Vec<Post>, DieselError> {
     use crate::schema::{bookmarks, posts};
+    let (before_time, before_id) = cursor_or_now(cursor);
+    let on_schedule = posts::time_posted.lt(Utc::now());
+
     bookmarks::table
         .inner_join(posts::table)
         .filter(bookmarks::user_id.eq(user_id))
+        .filter(on_schedule)
         .filter(posts::direct_message_to.is_null())
+        .filter(
+            posts::time_posted.lt(before_time).or(posts::time_posted
+                .eq(before_time)
+                .and(posts::id.lt(before_id))),
+        )
         .select(Post::as_select())
-        .limit(30)
+        .order((posts::time_posted.desc(), posts::id.desc()))
+        .limit(POSTS_PER_PAGE)
         .get_results(conn)
 }
 
+/// Everything needed to present a batch of posts, see `get_details`
+///
+/// NOTE Data that depends on the viewer is left empty when there isn't one
+#[derive(Debug, Default)]
+pub struct PostDetails {
+    /// the posts being replied to, which may not be part of the batch
+    ///
+    /// NOTE Parents `viewer` can't see are left out, with the same rules as `get_ancestors`
+    pub parents: HashMap<PostId, Post>,
+    /// authors of both the posts and their parents
+    pub users: HashMap<UserId, User>,
+    /// what was recorded about the images in the posts when they were uploaded
+    pub images: HashMap<ImageId, Image>,
+    pub aggregates: HashMap<PostId, AggregatePostInfo>,
+    pub poll_results: HashMap<PostId, Vec<(PollChoiceId, i64)>>,
+    pub last_edits: HashMap<PostId, DateTime<Utc>>,
+    pub emoji_reactions: HashMap<PostId, Vec<EmojiReactionCount>>,
+    pub like_statuses: HashMap<PostId, i16>,
+    pub bookmarked: HashSet<PostId>,
+    pub boosted: HashSet<PostId>,
+    pub votes: HashMap<PostId, PollChoiceId>,
+}
+
+/// Loads the authors, reactions and poll results of already loaded posts in a fixed number of
+/// queries, regardless of how many posts there are
+pub fn get_details(
+    conn: &mut PgConnection,
+    posts: &[Post],
+    viewer: Option<UserId>,
+) -> Result<PostDetails, DieselError> {
+    use crate::schema::{
+        bookmarks, boosts, poll_votes, post_reactions, post_revisions, posts, reactions, users,
+    };
+    use diesel::dsl::{count_star, max};
+
+    let mut details = PostDetails::default();
+    if posts.is_empty() {
+        return Ok(details);
+    }
+    let ids = posts.iter().map(|post| post.id).collect::<Vec<_>>();
+
+    let parent_ids = posts
+        .iter()
+        .filter_map(|post| post.reply_to)
+        .collect::<Vec<_>>();
+    details.parents = posts::table
+        .filter(posts::id.eq_any(&parent_ids))
+        .filter(posts::direct_message_to.is_null())
+        .filter(
+            posts::time_posted
+                .lt(Utc::now())
+                .or(posts::user_id.nullable().eq(viewer)),
+        )
+        .load::<Post>(conn)?
+        .into_iter()
+        .map(|post| (post.id, post))
+        .collect();
+
+    let user_ids = posts
+        .iter()
+        .chain(details.parents.values())
+        .map(|post| post.user_id)
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    details.users = users::table
+        .filter(users::id.eq_any(&user_ids))
+        .load::<User>(conn)?
+        .into_iter()
+        .map(|user| (user.id, user))
+        .collect();
+
+    let image_ids = posts
+        .iter()
+        .flat_map(|post| post.content.image_ids())
+        .collect::<Vec<_>>();
+    details.images = crate::image::get_many(conn, &image_ids)?;
+
+    for &post_id in ids.iter() {
+        details.aggregates.insert(
+            post_id,
+            AggregatePostInfo {
+                post_id,
+                likes: 0,
+                dislikes: 0,
+                boosts: 0,
+            },
+        );
+    }
+    let like_counts = reactions::table
+        .filter(reactions::post_id.eq_any(&ids))
+        .filter(reactions::like_status.ne(0))
+        .group_by((reactions::post_id, reactions::like_status))
+        .select((reactions::post_id, reactions::like_status, count_star()))
+        .load::<(PostId, i16, i64)>(conn)?;
+    for (post_id, like_status, count) in like_counts {
+        if let Some(aggregate) = details.aggregates.get_mut(&post_id) {
+            match like_status {
+                1 => aggregate.likes = count,
+                -1 => aggregate.dislikes = count,
+                _ => (),
+            }
+        }
+    }
+    let boost_counts = boosts::table
+        .filter(boosts::post_id.eq_any(&ids))
+        .group_by(boosts::post_id)
+        .select((boosts::post_id, count_star()))
+        .load::<(PostId, i64)>(conn)?;
+    for (post_id, count) in boost_counts {
+        if let Some(aggregate) = details.aggregates.get_mut(&post_id) {
+            aggregate.boosts = count;
+        }
+    }
+
+    let poll_counts = poll_votes::table
+        .filter(poll_votes::post_id.eq_any(&ids))
+        .group_by((poll_votes::post_id, poll_votes::choice_id))
+        .select((poll_votes::post_id, poll_votes::choice_id, count_star()))
+        .load::<(PostId, PollChoiceId, i64)>(conn)?;
+    for (post_id, choice_id, count) in poll_counts {
+        details
+            .poll_results
+            .entry(post_id)
+            .or_default()
+            .push((choice_id, count));
+    }
+
+    details.last_edits = post_revisions::table
+        .filter(post_revisions::post_id.eq_any(&ids))
+        .group_by(post_revisions::post_id)
+        .select((post_revisions::post_id, max(post_revisions::created_at)))
+        .load::<(PostId, Option<DateTime<Utc>>)>(conn)?
+        .into_iter()
+        .filter_map(|(post_id, edited_at)| Some((post_id, edited_at?)))
+        .collect();
+
+    let emoji_counts = post_reactions::table
+        .filter(post_reactions::post_id.eq_any(&ids))
+        .group_by((post_reactions::post_id, post_reactions::emoji))
+        .select((post_reactions::post_id, post_reactions::emoji, count_star()))
+        .order((
+            post_reactions::post_id,
+            count_star().desc(),
+            post_reactions::emoji.asc(),
+        ))
+        .load::<(PostId, String, i64)>(conn)?;
+
+    let viewer_emoji = match viewer {
+        Some(viewer) => post_reactions::table
+            .filter(post_reactions::post_id.eq_any(&ids))
+            .filter(post_reactions::user_id.eq(viewer))
+            .select((post_reactions::post_id, post_reactions::emoji))
+            .load::<(PostId, String)>(conn)?
+            .into_iter()
+            .collect::<HashSet<_>>(),
+        None => HashSet::new(),
+    };
+    for (post_id, emoji, count) in emoji_counts {
+        let reacted = viewer_emoji.contains(&(post_id, emoji.clone()));
+        details
+            .emoji_reactions
+            .entry(post_id)
+            .or_default()
+            .push(EmojiReactionCount {
+                emoji,
+                count,
+                reacted,
+            });
+    }
+
+    let Some(viewer) = viewer else {
+        return Ok(details);
+    };
+
+    details.like_statuses = reactions::table
+        .filter(reactions::post_id.eq_any(&ids))
+        .filter(reactions::user_id.eq(viewer))
+        .select((reactions::post_id, reactions::like_status))
+        .load::<(PostId, i16)>(conn)?
+        .into_iter()
+        .collect();
+
+    details.bookmarked = bookmarks::table
+        .filter(bookmarks::post_id.eq_any(&ids))
+        .filter(bookmarks::user_id.eq(viewer))
+        .select(bookmarks::post_id)
+        .load::<PostId>(conn)?
+        .into_iter()
+        .collect();
+
+    details.boosted = boosts::table
+        .filter(boosts::post_id.eq_any(&ids))
+        .filter(boosts::user_id.eq(viewer))
+        .select(boosts::post_id)
+        .load::<PostId>(conn)?
+        .into_iter()
+        .collect();
+
+    details.votes = poll_votes::table
+        .filter(poll_votes::post_id.eq_any(&ids))
+        .filter(poll_votes::user_id.eq(viewer))
+        .select((poll_votes::post_id, poll_votes::choice_id))
+        .load::<(PostId, PollChoiceId)>(conn)?
+        .into_iter()
+        .collect();
+
+    Ok(details)
+}
+
 #[cfg(test)]
 pub mod tests {
     use crate::test_db::{self, Result};
     use crate::user::tests::util as test_user;
 
+    use chrono::{Duration, Utc};
+
     use uchat_endpoint::post::types::NewPostOptions;
     use util as test_post;
 
@@ -480,4 +1114,401 @@ pub mod tests {
         assert_eq!(post_id, post.id);
         Ok(())
     }
+
+    #[test]
+    fn image_ids_are_read_from_images_and_galleries() {
+        use uchat_domain::ids::ImageId;
+        use uchat_endpoint::post::types::{Content, Gallery, GalleryImage, Image, ImageKind};
+
+        let content = |content: Content| super::Content(serde_json::to_value(content).unwrap());
+        let (first, second) = (ImageId::new(), ImageId::new());
+
+        let image = content(
+            Image {
+                kind: ImageKind::Id(first),
+                caption: None,
+            }
+            .into(),
+        );
+        assert_eq!(image.image_ids(), vec![first]);
+
+        let gallery = content(
+            Gallery {
+                images: vec![
+                    GalleryImage {
+                        kind: ImageKind::Id(first),
+                        alt: None,
+                    },
+                    GalleryImage {
+                        kind: ImageKind::DataUrl("data:".to_owned()),
+                        alt: None,
+                    },
+                    GalleryImage {
+                        kind: ImageKind::Id(second),
+                        alt: None,
+                    },
+                ],
+            }
+            .into(),
+        );
+        assert_eq!(gallery.image_ids(), vec![first, second]);
+
+        assert!(content(test_post::new_chat("test")).image_ids().is_empty());
+    }
+
+    #[test]
+    fn thread_includes_ancestors_and_descendants() -> Result<()> {
+        let mut conn = test_db::new_connection();
+        let user1 = test_user::new_user(&mut conn, "user 1");
+
+        let reply = |conn: &mut diesel::PgConnection, reply_to| {
+            let options = NewPostOptions {
+                reply_to,
+                ..Default::default()
+            };
+            let post = Post::new(user1.id, test_post::new_chat("test"), options)
+                .expect("failed to create new post struct");
+            super::new(conn, post).expect("failed to create post")
+        };
+
+        let root = reply(&mut conn, None);
+        let child = reply(&mut conn, Some(root));
+        let grandchild = reply(&mut conn, Some(child));
+        let other_child = reply(&mut conn, Some(root));
+
+        let ancestors = super::get_ancestors(&mut conn, grandchild, user1.id)?;
+        let ancestors = ancestors.iter().map(|p| p.id).collect::<Vec<_>>();
+        assert_eq!(ancestors, vec![root, child]);
+
+        let descendants = super::get_descendants(&mut conn, root, user1.id, None)?;
+        let descendants = descendants.iter().map(|p| p.id).collect::<Vec<_>>();
+        assert_eq!(descendants.len(), 3);
+        assert!(descendants.contains(&grandchild));
+        assert!(descendants.contains(&other_child));
+
+        assert!(super::get_descendants(&mut conn, grandchild, user1.id, None)?.is_empty());
+
+        // * a scheduled reply is only part of the thread for its author, along with its replies
+        let user2 = test_user::new_user(&mut conn, "user 2");
+        let scheduled = {
+            let options = NewPostOptions {
+                reply_to: Some(root),
+                time_posted: Utc::now() + Duration::hours(1),
+                ..Default::default()
+            };
+            let post = Post::new(user1.id, test_post::new_chat("later"), options)
+                .expect("failed to create new post struct");
+            super::new(&mut conn, post).expect("failed to create post")
+        };
+        let reply_to_scheduled = reply(&mut conn, Some(scheduled));
+
+        let descendants = super::get_descendants(&mut conn, root, user2.id, None)?;
+        assert_eq!(descendants.len(), 3);
+        let descendants = super::get_descendants(&mut conn, root, user1.id, None)?;
+        assert_eq!(descendants.len(), 5);
+
+        assert!(super::get_ancestors(&mut conn, reply_to_scheduled, user2.id)?.is_empty());
+        let ancestors = super::get_ancestors(&mut conn, reply_to_scheduled, user1.id)?;
+        let ancestors = ancestors.iter().map(|p| p.id).collect::<Vec<_>>();
+        assert_eq!(ancestors, vec![root, scheduled]);
+        Ok(())
+    }
+
+    #[test]
+    fn trending_posts_are_ranked_by_engagement() -> Result<()> {
+        use super::{Reaction, TrendingParams};
+
+        let mut conn = test_db::new_connection();
+        let user1 = test_user::new_user(&mut conn, "user 1");
+        let user2 = test_user::new_user(&mut conn, "user 2");
+
+        let new_post = |conn: &mut diesel::PgConnection| {
+            let post = Post::new(
+                user1.id,
+                test_post::new_chat("test"),
+                NewPostOptions::default(),
+            )
+            .expect("failed to create new post struct");
+            super::new(conn, post).expect("failed to create post")
+        };
+        let quiet_post = new_post(&mut conn);
+        let liked_post = new_post(&mut conn);
+
+        super::react(
+            &mut conn,
+            Reaction {
+                user_id: user2.id,
+                post_id: liked_post,
+                created_at: Utc::now(),
+                like_status: 1,
+            },
+        )?;
+
+        let params = TrendingParams {
+            window_hours: 24,
+            like_weight: 1.0,
+            dislike_weight: 1.0,
+            boost_weight: 2.0,
+            reply_weight: 1.5,
+            gravity: 1.5,
+            max_posts: 100,
+        };
+        let total = super::refresh_trending(&mut conn, &params)?;
+        assert_eq!(total, 2);
+
+        let trending = super::get_trending(&mut conn, None)?;
+        assert_eq!(trending[0].0.id, liked_post);
+        assert_eq!(trending[1].0.id, quiet_post);
+        assert!(trending[0].1 > trending[1].1);
+
+        let after_first = super::get_trending(&mut conn, Some((trending[0].1, liked_post)))?;
+        assert_eq!(after_first.len(), 1);
+        assert_eq!(after_first[0].0.id, quiet_post);
+        Ok(())
+    }
+
+    #[test]
+    fn public_posts_are_paginated() -> Result<()> {
+        let mut conn = test_db::new_connection();
+        let user1 = test_user::new_user(&mut conn, "user 1");
+
+        let total = super::POSTS_PER_PAGE + 5;
+        for i in 0..total {
+            let options = NewPostOptions {
+                time_posted: Utc::now() - Duration::minutes(i),
+                ..Default::default()
+            };
+            let post = Post::new(user1.id, test_post::new_chat("test message"), options)
+                .expect("failed to create new post struct");
+            super::new(&mut conn, post).expect("failed to create post");
+        }
+
+        let first_page = super::get_public_posts(&mut conn, user1.id, None)?;
+        assert_eq!(first_page.len() as i64, super::POSTS_PER_PAGE);
+
+        let last = first_page.last().unwrap();
+        let cursor = Some((last.time_posted, last.id));
+        let second_page = super::get_public_posts(&mut conn, user1.id, cursor)?;
+        assert_eq!(second_page.len() as i64, total - super::POSTS_PER_PAGE);
+
+        assert!(second_page
+            .iter()
+            .all(|post| first_page.iter().all(|p| p.id != post.id)));
+        Ok(())
+    }
+
+    #[test]
+    fn scheduled_posts_can_be_rescheduled_and_cancelled() -> Result<()> {
+        let mut conn = test_db::new_connection();
+        let user1 = test_user::new_user(&mut conn, "user 1");
+        let user2 = test_user::new_user(&mut conn, "user 2");
+
+        let new_post = |conn: &mut diesel::PgConnection, time_posted| {
+            let options = NewPostOptions {
+                time_posted,
+                ..Default::default()
+            };
+            let post = Post::new(user1.id, test_post::new_chat("test"), options)
+                .expect("failed to create new post struct");
+            super::new(conn, post).expect("failed to create post")
+        };
+        let later = new_post(&mut conn, Utc::now() + Duration::hours(2));
+        let sooner = new_post(&mut conn, Utc::now() + Duration::hours(1));
+        let published = new_post(&mut conn, Utc::now() - Duration::hours(1));
+
+        let scheduled = super::get_scheduled_posts(&mut conn, user1.id)?;
+        let scheduled = scheduled.iter().map(|p| p.id).collect::<Vec<_>>();
+        assert_eq!(scheduled, vec![sooner, later]);
+        assert!(super::get_scheduled_posts(&mut conn, user2.id)?.is_empty());
+
+        // * bookmarking a post doesn't show it before it's published
+        super::bookmark(&mut conn, user2.id, later)?;
+        super::bookmark(&mut conn, user2.id, published)?;
+        let bookmarked = super::get_bookmarked_posts(&mut conn, user2.id, None)?;
+        let bookmarked = bookmarked.iter().map(|p| p.id).collect::<Vec<_>>();
+        assert_eq!(bookmarked, vec![published]);
+
+        let new_time = Utc::now() + Duration::hours(3);
+        assert!(super::reschedule(&mut conn, user1.id, sooner, new_time)?);
+        assert!(!super::reschedule(&mut conn, user2.id, later, new_time)?);
+        assert!(!super::reschedule(
+            &mut conn, user1.id, published, new_time
+        )?);
+
+        let scheduled = super::get_scheduled_posts(&mut conn, user1.id)?;
+        assert_eq!(scheduled.last().unwrap().id, sooner);
+
+        assert!(super::cancel_scheduled(&mut conn, user1.id, published)?.is_none());
+        assert!(super::cancel_scheduled(&mut conn, user1.id, later)?.is_some());
+        assert_eq!(super::get_scheduled_posts(&mut conn, user1.id)?.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn edits_keep_revisions_and_deletes_detach_replies() -> Result<()> {
+        let mut conn = test_db::new_connection();
+        let user1 = test_user::new_user(&mut conn, "user 1");
+        let user2 = test_user::new_user(&mut conn, "user 2");
+
+        let post = Post::new(
+            user1.id,
+            test_post::new_chat("original"),
+            NewPostOptions::default(),
+        )
+        .expect("failed to create new post struct");
+        let post_id = super::new(&mut conn, post).expect("failed to create post");
+        assert!(super::get_revisions(&mut conn, post_id)?.is_empty());
+
+        let edited = super::Content(serde_json::to_value(test_post::new_chat("edited")).unwrap());
+        assert!(!super::edit(&mut conn, user2.id, post_id, edited.clone())?);
+        assert!(super::edit(&mut conn, user1.id, post_id, edited)?);
+
+        let revisions = super::get_revisions(&mut conn, post_id)?;
+        assert_eq!(revisions.len(), 1);
+        assert_eq!(
+            revisions[0].content.0,
+            serde_json::to_value(test_post::new_chat("original")).unwrap()
+        );
+        let posts = [super::get(&mut conn, post_id)?];
+        let details = super::get_details(&mut conn, &posts, None)?;
+        assert!(details.last_edits.contains_key(&post_id));
+
+        let reply = |conn: &mut diesel::PgConnection, reply_to| {
+            let options = NewPostOptions {
+                reply_to: Some(reply_to),
+                ..Default::default()
+            };
+            let post = Post::new(user2.id, test_post::new_chat("reply"), options)
+                .expect("failed to create new post struct");
+            super::new(conn, post).expect("failed to create post")
+        };
+        let reply_id = reply(&mut conn, post_id);
+        let nested_reply_id = reply(&mut conn, reply_id);
+
+        assert!(super::delete(&mut conn, user2.id, post_id)?.is_none());
+
+        let deleted = super::delete(&mut conn, user1.id, post_id)?.unwrap();
+        // ? the post and its revision, but not the reply by another user
+        assert_eq!(deleted.len(), 2);
+        assert!(deleted.iter().all(|(user_id, _)| *user_id == user1.id));
+        assert!(super::get(&mut conn, post_id).is_err());
+
+        let reply = super::get(&mut conn, reply_id)?;
+        assert_eq!(reply.reply_to, None);
+        let nested_reply = super::get(&mut conn, nested_reply_id)?;
+        assert_eq!(nested_reply.reply_to, Some(reply_id));
+        Ok(())
+    }
+
+    #[test]
+    fn emoji_reactions_are_counted_per_emoji() -> Result<()> {
+        let mut conn = test_db::new_connection();
+        let user1 = test_user::new_user(&mut conn, "user 1");
+        let user2 = test_user::new_user(&mut conn, "user 2");
+
+        let post = Post::new(
+            user1.id,
+            test_post::new_chat("test"),
+            NewPostOptions::default(),
+        )
+        .expect("failed to create new post struct");
+        let post_id = super::new(&mut conn, post).expect("failed to create post");
+
+        super::add_emoji_reaction(&mut conn, user1.id, post_id, "🎉")?;
+        super::add_emoji_reaction(&mut conn, user2.id, post_id, "🎉")?;
+        super::add_emoji_reaction(&mut conn, user2.id, post_id, "😂")?;
+        // ? reacting twice with the same emoji has no effect
+        super::add_emoji_reaction(&mut conn, user2.id, post_id, "😂")?;
+
+        let counts = super::aggregate_emoji_reactions(&mut conn, post_id, Some(user1.id))?;
+        let counts = counts
+            .iter()
+            .map(|c| (c.emoji.as_str(), c.count, c.reacted))
+            .collect::<Vec<_>>();
+        assert_eq!(counts, vec![("🎉", 2, true), ("😂", 1, false)]);
+
+        super::remove_emoji_reaction(&mut conn, user2.id, post_id, "😂")?;
+        let counts = super::aggregate_emoji_reactions(&mut conn, post_id, None)?;
+        assert_eq!(counts.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn details_are_loaded_for_every_post() -> Result<()> {
+        use super::Reaction;
+
+        let mut conn = test_db::new_connection();
+        let user1 = test_user::new_user(&mut conn, "user 1");
+        let user2 = test_user::new_user(&mut conn, "user 2");
+
+        let new_post = |conn: &mut diesel::PgConnection, user_id, reply_to| {
+            let options = NewPostOptions {
+                reply_to,
+                ..Default::default()
+            };
+            let post = Post::new(user_id, test_post::new_chat("test"), options)
+                .expect("failed to create new post struct");
+            super::new(conn, post).expect("failed to create post")
+        };
+        let parent = new_post(&mut conn, user1.id, None);
+        let reply = new_post(&mut conn, user2.id, Some(parent));
+
+        super::react(
+            &mut conn,
+            Reaction {
+                user_id: user2.id,
+                post_id: parent,
+                created_at: Utc::now(),
+                like_status: 1,
+            },
+        )?;
+        super::bookmark(&mut conn, user2.id, parent)?;
+        super::boost(&mut conn, user1.id, reply, Utc::now())?;
+        super::add_emoji_reaction(&mut conn, user2.id, parent, "🎉")?;
+
+        // NOTE The parent is only loaded as the post being replied to
+        let posts = [super::get(&mut conn, reply)?];
+        let details = super::get_details(&mut conn, &posts, Some(user2.id))?;
+        assert!(details.parents.contains_key(&parent));
+        assert!(details.users.contains_key(&user1.id));
+        assert_eq!(details.aggregates[&reply].boosts, 1);
+
+        let posts = [
+            super::get(&mut conn, parent)?,
+            super::get(&mut conn, reply)?,
+        ];
+        let details = super::get_details(&mut conn, &posts, Some(user2.id))?;
+        assert_eq!(details.aggregates.len(), 2);
+        assert_eq!(details.aggregates[&parent].likes, 1);
+        assert_eq!(details.like_statuses[&parent], 1);
+        assert!(details.bookmarked.contains(&parent));
+        assert!(!details.boosted.contains(&reply));
+        assert!(details.emoji_reactions[&parent][0].reacted);
+
+        let posts = [super::get(&mut conn, parent)?];
+        let details = super::get_details(&mut conn, &posts, None)?;
+        assert!(details.like_statuses.is_empty());
+        assert!(!details.emoji_reactions[&parent][0].reacted);
+
+        // * a scheduled parent is only loaded for its author
+        let scheduled = {
+            let options = NewPostOptions {
+                time_posted: Utc::now() + Duration::hours(1),
+                ..Default::default()
+            };
+            let post = Post::new(user1.id, test_post::new_chat("later"), options)
+                .expect("failed to create new post struct");
+            super::new(&mut conn, post).expect("failed to create post")
+        };
+        let reply_to_scheduled = new_post(&mut conn, user1.id, Some(scheduled));
+
+        let posts = [super::get(&mut conn, reply_to_scheduled)?];
+        let details = super::get_details(&mut conn, &posts, Some(user2.id))?;
+        assert!(details.parents.is_empty());
+        let details = super::get_details(&mut conn, &posts, None)?;
+        assert!(details.parents.is_empty());
+        let details = super::get_details(&mut conn, &posts, Some(user1.id))?;
+        assert!(details.parents.contains_key(&scheduled));
+        Ok(())
+    }
 }
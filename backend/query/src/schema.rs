@@ -1,5 +1,17 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    access_tokens (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        name -> Text,
+        token_hash -> Text,
+        scopes -> Array<Text>,
+        last_used_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     bookmarks (user_id, post_id) {
         user_id -> Uuid,
@@ -24,6 +36,27 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    images (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        content_type -> Text,
+        byte_size -> Int4,
+        width -> Int4,
+        height -> Int4,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    password_resets (token_hash) {
+        token_hash -> Text,
+        user_id -> Uuid,
+        expires_at -> Timestamptz,
+        created_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     poll_choices (id) {
         id -> Uuid,
@@ -41,6 +74,24 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    post_reactions (user_id, post_id, emoji) {
+        user_id -> Uuid,
+        post_id -> Uuid,
+        emoji -> Text,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    post_revisions (id) {
+        id -> Uuid,
+        post_id -> Uuid,
+        content -> Jsonb,
+        created_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     posts (id) {
         id -> Uuid,
@@ -59,7 +110,34 @@ diesel::table! {
         post_id -> Uuid,
         created_at -> Timestamptz,
         like_status -> Int2,
-        reaction -> Nullable<Jsonb>,
+    }
+}
+
+diesel::table! {
+    recovery_codes (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        code_hash -> Text,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    totp (user_id) {
+        user_id -> Uuid,
+        secret -> Text,
+        confirmed_at -> Nullable<Timestamptz>,
+        last_used_step -> Nullable<Int8>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    trending_posts (post_id) {
+        post_id -> Uuid,
+        rank -> Int8,
+        score -> Float8,
+        refreshed_at -> Timestamptz,
     }
 }
 
@@ -83,23 +161,40 @@ diesel::table! {
         expires_at -> Timestamptz,
         created_at -> Timestamptz,
         fingerprint -> Jsonb,
+        last_seen_at -> Timestamptz,
     }
 }
 
 diesel::joinable!(followers -> users (follows));
+diesel::joinable!(access_tokens -> users (user_id));
+diesel::joinable!(images -> users (user_id));
+diesel::joinable!(password_resets -> users (user_id));
 diesel::joinable!(poll_votes -> poll_choices (choice_id));
+diesel::joinable!(post_reactions -> posts (post_id));
+diesel::joinable!(post_revisions -> posts (post_id));
 diesel::joinable!(posts -> users (direct_message_to));
 diesel::joinable!(bookmarks -> posts (post_id));
 diesel::joinable!(reactions -> posts (post_id));
+diesel::joinable!(recovery_codes -> users (user_id));
+diesel::joinable!(totp -> users (user_id));
+diesel::joinable!(trending_posts -> posts (post_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    access_tokens,
     bookmarks,
     boosts,
     followers,
+    images,
+    password_resets,
     poll_choices,
     poll_votes,
+    post_reactions,
+    post_revisions,
     posts,
     reactions,
+    recovery_codes,
+    totp,
+    trending_posts,
     users,
     web,
 );
@@ -3,15 +3,32 @@ use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 use uchat_domain::ids::{SessionId, UserId};
 
+use crate::util::DeleteStatus;
 use crate::DieselError;
 
 use crate::schema;
 
-// NOTE The `Fingerprint` can be used to identify a logged user's device and location, which can enable more security features
-// ! however it won't be used since it'd need MFA to be implemented first
+// NOTE The `Fingerprint` identifies a logged user's device and location, a user gets one session per fingerprint
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, DieselNewType)]
 pub struct Fingerprint(serde_json::Value);
 
+impl Fingerprint {
+    pub fn new(user_agent: Option<String>, ip_address: Option<String>) -> Self {
+        Self(serde_json::json!({
+            "user_agent": user_agent,
+            "ip_address": ip_address,
+        }))
+    }
+
+    pub fn user_agent(&self) -> Option<&str> {
+        self.0.get("user_agent").and_then(|value| value.as_str())
+    }
+
+    pub fn ip_address(&self) -> Option<&str> {
+        self.0.get("ip_address").and_then(|value| value.as_str())
+    }
+}
+
 impl From<serde_json::Value> for Fingerprint {
     fn from(value: serde_json::Value) -> Self {
         Self(value)
@@ -29,6 +46,7 @@ pub struct Session {
     pub expires_at: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
     pub fingerprint: Fingerprint,
+    pub last_seen_at: DateTime<Utc>,
 }
 
 pub fn new(
@@ -44,6 +62,7 @@ pub fn new(
         expires_at: Utc::now() + duration,
         created_at: Utc::now(),
         fingerprint,
+        last_seen_at: Utc::now(),
     };
     {
         use crate::schema::web;
@@ -52,7 +71,10 @@ pub fn new(
             .on_conflict((web::user_id, web::fingerprint))
             .do_update()
             // ? Updates the expiration date if an already logged in user logs in again
-            .set(web::expires_at.eq(new_session.expires_at))
+            .set((
+                web::expires_at.eq(new_session.expires_at),
+                web::last_seen_at.eq(new_session.last_seen_at),
+            ))
             .get_result(conn)
     }
 }
@@ -76,3 +98,137 @@ pub fn find(
         .filter(web::fingerprint.eq(fingerprint))
         .get_result(conn)
 }
+
+/// Lists the sessions of the user that haven't expired, most recently used first
+pub fn get_active(conn: &mut PgConnection, user_id: UserId) -> Result<Vec<Session>, DieselError> {
+    use crate::schema::web;
+    web::table
+        .filter(web::user_id.eq(user_id))
+        .filter(web::expires_at.gt(Utc::now()))
+        .order(web::last_seen_at.desc())
+        .get_results(conn)
+}
+
+pub fn touch(conn: &mut PgConnection, session_id: SessionId) -> Result<(), DieselError> {
+    use crate::schema::web;
+    diesel::update(web::table)
+        .filter(web::id.eq(session_id))
+        .set(web::last_seen_at.eq(Utc::now()))
+        .execute(conn)
+        .map(|_| ())
+}
+
+/// Revokes a session of the user, sessions of other users are left untouched
+pub fn revoke(
+    conn: &mut PgConnection,
+    user_id: UserId,
+    session_id: SessionId,
+) -> Result<DeleteStatus, DieselError> {
+    use crate::schema::web;
+    diesel::delete(web::table)
+        .filter(web::id.eq(session_id))
+        .filter(web::user_id.eq(user_id))
+        .execute(conn)
+        .map(DeleteStatus::new)
+}
+
+/// Revokes a single session, such as when the user logs out
+pub fn delete(conn: &mut PgConnection, session_id: SessionId) -> Result<DeleteStatus, DieselError> {
+    use crate::schema::web;
+    diesel::delete(web::table)
+        .filter(web::id.eq(session_id))
+        .execute(conn)
+        .map(DeleteStatus::new)
+}
+
+/// Revokes every session of the user, returning how many were revoked
+pub fn delete_all(conn: &mut PgConnection, user_id: UserId) -> Result<usize, DieselError> {
+    use crate::schema::web;
+    diesel::delete(web::table)
+        .filter(web::user_id.eq(user_id))
+        .execute(conn)
+}
+
+/// Revokes every session and access token of the user, returning how many sessions were revoked
+///
+/// NOTE Access tokens outlive sessions, so signing out everywhere has to reach them as well
+pub fn revoke_all(conn: &mut PgConnection, user_id: UserId) -> Result<usize, DieselError> {
+    conn.transaction(|conn| {
+        crate::access_token::delete_all(conn, user_id)?;
+        delete_all(conn, user_id)
+    })
+}
+
+/// Removes the sessions that already expired, returning how many were removed
+pub fn delete_expired(conn: &mut PgConnection) -> Result<usize, DieselError> {
+    use crate::schema::web;
+    diesel::delete(web::table)
+        .filter(web::expires_at.le(Utc::now()))
+        .execute(conn)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use chrono::Duration;
+
+    use super::Fingerprint;
+    use crate::test_db::{self, Result};
+    use crate::user::tests::util as test_user;
+    use crate::util::DeleteStatus;
+
+    #[test]
+    fn sessions_can_be_revoked() -> Result<()> {
+        let mut conn = test_db::new_connection();
+        let user1 = test_user::new_user(&mut conn, "user 1");
+
+        let fingerprint = |device: &str| Fingerprint::new(Some(device.to_owned()), None);
+        let first = super::new(&mut conn, user1.id, Duration::weeks(1), fingerprint("a"))?;
+        let second = super::new(&mut conn, user1.id, Duration::weeks(1), fingerprint("b"))?;
+        let expired = super::new(&mut conn, user1.id, Duration::weeks(-1), fingerprint("c"))?;
+
+        assert_eq!(super::delete_expired(&mut conn)?, 1);
+        assert!(super::get(&mut conn, expired.id)?.is_none());
+
+        assert_eq!(super::delete(&mut conn, first.id)?, DeleteStatus::Deleted);
+        assert!(super::get(&mut conn, first.id)?.is_none());
+        assert!(super::get(&mut conn, second.id)?.is_some());
+
+        assert_eq!(super::delete_all(&mut conn, user1.id)?, 1);
+        assert!(super::get(&mut conn, second.id)?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn sessions_are_listed_and_revoked_per_device() -> Result<()> {
+        let mut conn = test_db::new_connection();
+        let user1 = test_user::new_user(&mut conn, "user 1");
+        let user2 = test_user::new_user(&mut conn, "user 2");
+
+        let phone = Fingerprint::new(Some("phone".to_owned()), Some("10.0.0.1".to_owned()));
+        let laptop = Fingerprint::new(Some("laptop".to_owned()), Some("10.0.0.2".to_owned()));
+        let first = super::new(&mut conn, user1.id, Duration::weeks(1), phone.clone())?;
+        // ? Logging in again from the same device reuses its session
+        let again = super::new(&mut conn, user1.id, Duration::weeks(1), phone)?;
+        assert_eq!(first.id, again.id);
+
+        let second = super::new(&mut conn, user1.id, Duration::weeks(1), laptop)?;
+        super::touch(&mut conn, second.id)?;
+
+        let active = super::get_active(&mut conn, user1.id)?;
+        assert_eq!(active.len(), 2);
+        assert_eq!(active[0].id, second.id);
+        assert_eq!(active[0].fingerprint.user_agent(), Some("laptop"));
+        assert_eq!(active[0].fingerprint.ip_address(), Some("10.0.0.2"));
+
+        assert_eq!(
+            super::revoke(&mut conn, user2.id, first.id)?,
+            DeleteStatus::NotFound
+        );
+        assert_eq!(
+            super::revoke(&mut conn, user1.id, first.id)?,
+            DeleteStatus::Deleted
+        );
+        assert_eq!(super::get_active(&mut conn, user1.id)?.len(), 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,215 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use uchat_domain::ids::UserId;
+use uuid::Uuid;
+
+use crate::{schema, DieselError};
+
+#[derive(Clone, Debug, Queryable)]
+pub struct Totp {
+    pub user_id: UserId,
+    /// base32 encoded secret
+    pub secret: String,
+    /// `None` while the user hasn't confirmed the secret with a code
+    pub confirmed_at: Option<DateTime<Utc>>,
+    /// the time step of the last accepted code, so codes can't be reused
+    pub last_used_step: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Totp {
+    pub fn is_enabled(&self) -> bool {
+        self.confirmed_at.is_some()
+    }
+}
+
+#[derive(Clone, Debug, Queryable, Selectable)]
+#[diesel(table_name = schema::recovery_codes)]
+pub struct RecoveryCode {
+    pub id: Uuid,
+    pub code_hash: String,
+}
+
+pub fn get(conn: &mut PgConnection, user_id: UserId) -> Result<Option<Totp>, DieselError> {
+    use crate::schema::totp;
+
+    totp::table
+        .filter(totp::user_id.eq(user_id))
+        .get_result(conn)
+        .optional()
+}
+
+pub fn is_enabled(conn: &mut PgConnection, user_id: UserId) -> Result<bool, DieselError> {
+    get(conn, user_id).map(|totp| totp.map_or(false, |totp| totp.is_enabled()))
+}
+
+/// Saves a secret that still has to be confirmed, returns false if the user already has TOTP enabled
+pub fn set_pending(
+    conn: &mut PgConnection,
+    user_id: UserId,
+    secret: &str,
+) -> Result<bool, DieselError> {
+    use crate::schema::totp;
+
+    conn.transaction(|conn| {
+        if is_enabled(conn, user_id)? {
+            return Ok(false);
+        }
+
+        diesel::insert_into(totp::table)
+            .values((totp::user_id.eq(user_id), totp::secret.eq(secret)))
+            .on_conflict(totp::user_id)
+            .do_update()
+            .set((totp::secret.eq(secret), totp::created_at.eq(Utc::now())))
+            .execute(conn)?;
+        Ok(true)
+    })
+}
+
+/// Enables TOTP for the user, replacing any previous recovery codes
+pub fn confirm(
+    conn: &mut PgConnection,
+    user_id: UserId,
+    step: i64,
+    recovery_code_hashes: &[String],
+) -> Result<bool, DieselError> {
+    use crate::schema::{recovery_codes, totp};
+
+    conn.transaction(|conn| {
+        let confirmed = diesel::update(totp::table)
+            .filter(totp::user_id.eq(user_id))
+            .filter(totp::confirmed_at.is_null())
+            .set((
+                totp::confirmed_at.eq(Utc::now()),
+                totp::last_used_step.eq(step),
+            ))
+            .execute(conn)?;
+        if confirmed == 0 {
+            return Ok(false);
+        }
+
+        diesel::delete(recovery_codes::table)
+            .filter(recovery_codes::user_id.eq(user_id))
+            .execute(conn)?;
+
+        let codes = recovery_code_hashes
+            .iter()
+            .map(|hash| {
+                (
+                    recovery_codes::id.eq(Uuid::new_v4()),
+                    recovery_codes::user_id.eq(user_id),
+                    recovery_codes::code_hash.eq(hash),
+                )
+            })
+            .collect::<Vec<_>>();
+        diesel::insert_into(recovery_codes::table)
+            .values(&codes)
+            .execute(conn)?;
+
+        Ok(true)
+    })
+}
+
+/// Marks the time step as used, returns false if a code from this step or a later one was already used
+pub fn use_step(conn: &mut PgConnection, user_id: UserId, step: i64) -> Result<bool, DieselError> {
+    use crate::schema::totp;
+
+    diesel::update(totp::table)
+        .filter(totp::user_id.eq(user_id))
+        .filter(totp::confirmed_at.is_not_null())
+        .filter(
+            totp::last_used_step
+                .is_null()
+                .or(totp::last_used_step.lt(step)),
+        )
+        .set(totp::last_used_step.eq(step))
+        .execute(conn)
+        .map(|updated| updated > 0)
+}
+
+pub fn get_recovery_codes(
+    conn: &mut PgConnection,
+    user_id: UserId,
+) -> Result<Vec<RecoveryCode>, DieselError> {
+    use crate::schema::recovery_codes;
+
+    recovery_codes::table
+        .filter(recovery_codes::user_id.eq(user_id))
+        .select(RecoveryCode::as_select())
+        .get_results(conn)
+}
+
+/// Deletes the recovery code, returns false if it was already used
+pub fn use_recovery_code(conn: &mut PgConnection, id: Uuid) -> Result<bool, DieselError> {
+    use crate::schema::recovery_codes;
+
+    diesel::delete(recovery_codes::table)
+        .filter(recovery_codes::id.eq(id))
+        .execute(conn)
+        .map(|deleted| deleted > 0)
+}
+
+/// Disables TOTP for the user, along with their recovery codes
+pub fn delete(conn: &mut PgConnection, user_id: UserId) -> Result<(), DieselError> {
+    use crate::schema::{recovery_codes, totp};
+
+    conn.transaction(|conn| {
+        diesel::delete(recovery_codes::table)
+            .filter(recovery_codes::user_id.eq(user_id))
+            .execute(conn)?;
+        diesel::delete(totp::table)
+            .filter(totp::user_id.eq(user_id))
+            .execute(conn)?;
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::test_db::{self, Result};
+    use crate::user::tests::util as test_user;
+
+    #[test]
+    fn totp_is_enabled_once_confirmed() -> Result<()> {
+        let mut conn = test_db::new_connection();
+        let user = test_user::new_user(&mut conn, "user 1");
+
+        assert!(super::set_pending(&mut conn, user.id, "FIRST")?);
+        assert!(super::set_pending(&mut conn, user.id, "SECOND")?);
+        assert!(!super::is_enabled(&mut conn, user.id)?);
+
+        let hashes = vec!["hash 1".to_owned(), "hash 2".to_owned()];
+        assert!(super::confirm(&mut conn, user.id, 10, &hashes)?);
+        assert!(!super::confirm(&mut conn, user.id, 11, &hashes)?);
+
+        let totp = super::get(&mut conn, user.id)?.unwrap();
+        assert_eq!(totp.secret, "SECOND");
+        assert!(totp.is_enabled());
+        // ? An enabled secret can't be replaced without disabling TOTP first
+        assert!(!super::set_pending(&mut conn, user.id, "THIRD")?);
+
+        super::delete(&mut conn, user.id)?;
+        assert!(super::get(&mut conn, user.id)?.is_none());
+        assert!(super::get_recovery_codes(&mut conn, user.id)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn codes_can_only_be_used_once() -> Result<()> {
+        let mut conn = test_db::new_connection();
+        let user = test_user::new_user(&mut conn, "user 1");
+
+        super::set_pending(&mut conn, user.id, "SECRET")?;
+        super::confirm(&mut conn, user.id, 10, &["hash".to_owned()])?;
+
+        assert!(!super::use_step(&mut conn, user.id, 10)?);
+        assert!(super::use_step(&mut conn, user.id, 11)?);
+        assert!(!super::use_step(&mut conn, user.id, 11)?);
+
+        let codes = super::get_recovery_codes(&mut conn, user.id)?;
+        assert_eq!(codes.len(), 1);
+        assert!(super::use_recovery_code(&mut conn, codes[0].id)?);
+        assert!(!super::use_recovery_code(&mut conn, codes[0].id)?);
+        Ok(())
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
 use diesel::prelude::*;
 use diesel::{PgConnection, RunQueryDsl};
@@ -41,7 +43,7 @@ pub fn get_password_hash(
         .get_result(conn)?)
 }
 
-#[derive(Debug, Queryable)]
+#[derive(Clone, Debug, Queryable)]
 pub struct User {
     pub id: UserId,
     pub email: Option<String>,
@@ -59,6 +61,24 @@ pub fn get(conn: &mut PgConnection, user_id: UserId) -> Result<User, DieselError
     users.filter(id.eq(user_id)).get_result(conn)
 }
 
+pub fn get_many(
+    conn: &mut PgConnection,
+    user_ids: &[UserId],
+) -> Result<HashMap<UserId, User>, DieselError> {
+    use crate::schema::users;
+
+    if user_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    Ok(users::table
+        .filter(users::id.eq_any(user_ids))
+        .load::<User>(conn)?
+        .into_iter()
+        .map(|user| (user.id, user))
+        .collect())
+}
+
 #[derive(Debug, Queryable, Selectable)]
 #[diesel(table_name = schema::users)]
 pub struct Profile {
@@ -152,6 +172,7 @@ struct UpdateProfileInternal {
     // ? Some(Some(_)) => the field is updated
     pub display_name: Option<Option<String>>,
     pub email: Option<Option<String>>,
+    pub email_confirmed: Option<Option<DateTime<Utc>>>,
     pub password_hash: Option<String>,
     pub profile_image: Option<Option<String>>,
 }
@@ -162,9 +183,16 @@ pub fn update_profile(
 ) -> Result<(), DieselError> {
     use crate::schema::users;
 
+    // ? A new email address has to be confirmed again
+    let email_confirmed = match query_params.email {
+        Update::NoChange => None,
+        Update::Change(_) | Update::SetNull => Some(None),
+    };
+
     let update = UpdateProfileInternal {
         display_name: query_params.display_name.into_nullable(),
         email: query_params.email.into_nullable(),
+        email_confirmed,
         password_hash: query_params
             .password_hash
             .into_option()
@@ -180,8 +208,44 @@ pub fn update_profile(
         .map(|_| ())
 }
 
+// NOTE Emails aren't unique, more than one account may have confirmed the same email
+pub fn find_by_confirmed_email(
+    conn: &mut PgConnection,
+    email: &str,
+) -> Result<Vec<User>, DieselError> {
+    use crate::schema::users;
+
+    users::table
+        .filter(users::email.eq(email))
+        .filter(users::email_confirmed.is_not_null())
+        .get_results(conn)
+}
+
+/// Marks the email as confirmed, as long as it's still the user's current email
+pub fn confirm_email(
+    conn: &mut PgConnection,
+    user_id: UserId,
+    email: &str,
+) -> Result<bool, DieselError> {
+    use crate::schema::users;
+
+    diesel::update(users::table)
+        .filter(users::id.eq(user_id))
+        .filter(users::email.eq(email))
+        .set(users::email_confirmed.eq(Utc::now()))
+        .execute(conn)
+        .map(|updated| updated > 0)
+}
+
 #[cfg(test)]
 pub mod tests {
+    use diesel::PgConnection;
+    use uchat_domain::ids::UserId;
+    use uchat_endpoint::Update;
+
+    use super::UpdateProfileParams;
+    use crate::test_db::{self, Result};
+
     pub mod util {
         use diesel::PgConnection;
 
@@ -196,4 +260,40 @@ pub mod tests {
             user_query::get(conn, id).unwrap()
         }
     }
+
+    fn set_email(conn: &mut PgConnection, user_id: UserId, email: &str) {
+        let params = UpdateProfileParams {
+            id: user_id,
+            display_name: Update::NoChange,
+            email: Update::Change(email.to_owned()),
+            password_hash: Update::NoChange,
+            profile_image: Update::NoChange,
+        };
+        super::update_profile(conn, params).unwrap();
+    }
+
+    #[test]
+    fn changing_email_requires_confirming_it_again() -> Result<()> {
+        let mut conn = test_db::new_connection();
+        let user = util::new_user(&mut conn, "user 1");
+
+        set_email(&mut conn, user.id, "old@example.com");
+        assert!(!super::confirm_email(
+            &mut conn,
+            user.id,
+            "other@example.com"
+        )?);
+        assert!(super::confirm_email(&mut conn, user.id, "old@example.com")?);
+        assert!(super::get(&mut conn, user.id)?.email_confirmed.is_some());
+
+        set_email(&mut conn, user.id, "new@example.com");
+        assert!(super::get(&mut conn, user.id)?.email_confirmed.is_none());
+        // ? Tokens sent to the previous address can't confirm the new one
+        assert!(!super::confirm_email(
+            &mut conn,
+            user.id,
+            "old@example.com"
+        )?);
+        Ok(())
+    }
 }
@@ -1,7 +1,8 @@
 use clap::{command, Parser, Subcommand};
 use color_eyre::{eyre::Context, Help, Result};
-use std::net::SocketAddr;
+use std::{net::SocketAddr, path::PathBuf};
 use tracing::{debug, error, info};
+use uchat_crypto::sign::Algorithm;
 
 #[derive(Debug, Parser)]
 #[command(author, version, about, long_about = None)]
@@ -17,17 +18,58 @@ struct Cli {
     #[clap(short, long, default_value = "127.0.0.1:8070", env = "API_BIND")]
     bind: SocketAddr,
 
+    /// signing keys, the key in API_PRIVATE_KEY is used alone when this file doesn't exist
+    #[clap(long, default_value = "keyring.json", env = "API_KEYRING")]
+    keyring: PathBuf,
+
     #[clap(flatten)]
     verbosity: uchat_server::logging::Verbosity,
 
+    /// how often expired sessions are purged, in seconds
+    #[clap(long, default_value_t = 3600, env = "API_SESSION_PURGE_SECS")]
+    session_purge_secs: u64,
+
+    #[clap(flatten)]
+    trending: uchat_server::trending::TrendingConfig,
+
+    #[clap(flatten)]
+    mail: uchat_server::mail::MailConfig,
+
+    #[clap(flatten)]
+    rate_limit: uchat_server::rate_limit::RateLimitConfig,
+
+    #[clap(flatten)]
+    blob: uchat_server::blob::BlobConfig,
+
+    #[clap(flatten)]
+    proxy: uchat_server::extractor::ProxyConfig,
+
     #[command(subcommand)]
     command: Option<Command>,
 }
 
 #[derive(Debug, Subcommand)]
 enum Command {
-    /// generate a session signing key
-    GenKey,
+    /// generate a session signing key, or manage the keyring with a subcommand
+    GenKey {
+        #[command(subcommand)]
+        action: Option<KeyCommand>,
+    },
+}
+
+// NOTE The server reads the keyring on startup, so changes take effect once it's restarted
+#[derive(Debug, Subcommand)]
+enum KeyCommand {
+    /// generate a key and make it the one that signs, older keys keep verifying
+    Add {
+        /// one of ed25519, hmac-sha256 or rsa-pss
+        #[clap(long, default_value_t = Algorithm::Ed25519, env = "API_SIGNING_ALGORITHM")]
+        algorithm: Algorithm,
+    },
+    /// list the keys in the keyring
+    List,
+    /// remove a key, the sessions and tokens it signed stop working
+    Retire { id: String },
 }
 
 const CLI_TARGET: &str = "uchat_server";
@@ -49,7 +91,7 @@ async fn run() -> Result<()> {
 
     if let Some(command) = args.command {
         match command {
-            Command::GenKey => {
+            Command::GenKey { action: None } => {
                 let mut rng = uchat_crypto::new_rng();
                 info!(target: CLI_TARGET, "generating private key...");
                 let (key, _) = uchat_server::cli::gen_keys(&mut rng)?;
@@ -62,12 +104,21 @@ async fn run() -> Result<()> {
 
                 return Ok(());
             }
+            Command::GenKey {
+                action: Some(action),
+            } => return run_key_command(action, &args.keyring),
         }
     }
 
     debug!(target: CLI_TARGET, "loading signing keys");
 
-    let signing_keys = uchat_server::cli::load_keys()?;
+    let signing_keys = uchat_server::keyring::load(&args.keyring)?;
+    info!(
+        target: CLI_TARGET,
+        active_key = %signing_keys.active_id(),
+        algorithm = %signing_keys.active_algorithm(),
+        "signing keys loaded"
+    );
 
     info!(
         target: CLI_TARGET,
@@ -80,12 +131,30 @@ async fn run() -> Result<()> {
         .with_suggestion(|| "ensure correct db access rights")
         .with_suggestion(|| "make sure db exists")?;
 
+    let blobs = uchat_server::blob::from_config(&args.blob)
+        .with_suggestion(|| "check the blob storage args")?;
+    info!(target: CLI_TARGET, backend = ?args.blob.backend, "blob storage configured");
+
     let state = uchat_server::AppState {
         db_pool,
         signing_keys,
         rng: uchat_crypto::new_rng(),
+        trending: args.trending,
+        mailer: uchat_server::mail::Mailer::from_config(&args.mail),
+        rate_limiter: uchat_server::rate_limit::RateLimiter::new(args.rate_limit),
+        blobs,
+        proxy: args.proxy,
     };
 
+    debug!(target: CLI_TARGET, "starting background tasks");
+
+    tokio::spawn(uchat_server::trending::refresh_periodically(state.clone()));
+    tokio::spawn(uchat_server::session::purge_expired_periodically(
+        state.clone(),
+        std::time::Duration::from_secs(args.session_purge_secs),
+    ));
+    tokio::spawn(uchat_server::rate_limit::prune_periodically(state.clone()));
+
     info!(target: CLI_TARGET, bind_addr = %args.bind);
 
     let router = uchat_server::router::new_router(state);
@@ -95,7 +164,7 @@ async fn run() -> Result<()> {
         .with_suggestion(|| "check bind address")
         .with_suggestion(|| "check if other services are using the same port")?;
 
-    let server = server.serve(router.into_make_service());
+    let server = server.serve(router.into_make_service_with_connect_info::<SocketAddr>());
 
     info!(target: CLI_TARGET, "listening");
 
@@ -106,6 +175,48 @@ async fn run() -> Result<()> {
     Ok(())
 }
 
+fn run_key_command(action: KeyCommand, path: &std::path::Path) -> Result<()> {
+    use uchat_crypto::sign::KeyId;
+    use uchat_server::keyring::{self, KeyringFile};
+
+    match action {
+        KeyCommand::Add { algorithm } => {
+            let mut rng = uchat_crypto::new_rng();
+            let id = keyring::add_key(path, &mut rng, algorithm)?;
+            info!(target: CLI_TARGET, key_id = %id, %algorithm, path = %path.display(), "signing key added");
+        }
+        KeyCommand::List => {
+            let Some(keyring) = KeyringFile::load(path)? else {
+                info!(target: CLI_TARGET, path = %path.display(), "there is no keyring, API_PRIVATE_KEY is used instead");
+                return Ok(());
+            };
+            for key in keyring.keys {
+                let active = if key.id == keyring.active {
+                    " (active)"
+                } else {
+                    ""
+                };
+                println!(
+                    "{}\t{}\t{}{active}",
+                    key.id,
+                    key.algorithm,
+                    key.created_at.to_rfc3339()
+                );
+            }
+        }
+        KeyCommand::Retire { id } => {
+            let id = KeyId::new(id)?;
+            let mut keyring = KeyringFile::load(path)?
+                .ok_or_else(|| color_eyre::eyre::eyre!("there is no keyring at {}", path.display()))
+                .suggestion("add a key first")?;
+            keyring.retire(&id)?;
+            keyring.save(path)?;
+            info!(target: CLI_TARGET, key_id = %id, "signing key retired");
+        }
+    }
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     run().await
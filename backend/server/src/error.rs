@@ -1,12 +1,16 @@
 use crate::prelude::*;
+use std::time::Duration;
+
 use axum::{
     response::{IntoResponse, Response},
     Json,
 };
-use uchat_endpoint::RequestFailed;
+use hyper::header;
+use uchat_endpoint::{ErrorCode, RequestFailed};
 
 pub type ApiResult<T> = std::result::Result<T, ApiErr>;
 
+#[derive(Debug)]
 pub struct ApiErr {
     pub code: Option<StatusCode>,
     pub err: color_eyre::Report,
@@ -14,24 +18,40 @@ pub struct ApiErr {
 
 #[derive(Debug, thiserror::Error)]
 pub enum ServerErr {
-    #[error("Login failed")]
-    Login((StatusCode, String)),
-    #[error("Registration failed")]
-    Registration((StatusCode, String)),
+    // NOTE Unknown users and wrong passwords share an error, so handles can't be probed through login
+    #[error("Invalid username or password")]
+    InvalidCredentials,
+    #[error("Account already exists")]
+    AccountExists,
+    #[error("Too many attempts, try again in {} seconds", retry_after_secs(.0))]
+    TooManyRequests(Duration),
+    #[error("This access token isn't allowed to use this endpoint")]
+    InsufficientScope,
 }
 
 impl ServerErr {
-    pub fn missing_login() -> Self {
-        Self::Login((StatusCode::NOT_FOUND, "Missing login".into()))
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::InvalidCredentials => StatusCode::UNAUTHORIZED,
+            Self::AccountExists => StatusCode::CONFLICT,
+            Self::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
+            Self::InsufficientScope => StatusCode::FORBIDDEN,
+        }
     }
 
-    pub fn wrong_password() -> Self {
-        Self::Login((StatusCode::BAD_REQUEST, "Invalid password".into()))
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::InvalidCredentials => ErrorCode::InvalidCredentials,
+            Self::AccountExists => ErrorCode::AccountExists,
+            Self::TooManyRequests(_) => ErrorCode::TooManyRequests,
+            Self::InsufficientScope => ErrorCode::InsufficientScope,
+        }
     }
+}
 
-    pub fn account_exists() -> Self {
-        Self::Login((StatusCode::CONFLICT, "Account already exists".to_string()))
-    }
+// ? Rounded up, so clients never retry while still locked out
+fn retry_after_secs(retry_after: &Duration) -> u64 {
+    retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0)
 }
 
 impl ApiErr {
@@ -39,7 +59,7 @@ impl ApiErr {
         let msg: String = err.into();
         Self {
             code: Some(code),
-            err: color_eyre::Report::new(RequestFailed { msg }),
+            err: color_eyre::Report::new(RequestFailed::new(msg)),
         }
     }
 
@@ -49,11 +69,7 @@ impl ApiErr {
 }
 
 pub fn err_response<T: Into<String>>(code: StatusCode, msg: T) -> Response {
-    (
-        code,
-        Json(uchat_endpoint::RequestFailed { msg: msg.into() }),
-    )
-        .into_response()
+    (code, Json(RequestFailed::new(msg))).into_response()
 }
 
 impl IntoResponse for ApiErr {
@@ -64,10 +80,15 @@ impl IntoResponse for ApiErr {
 
         // NOTE downcast_ref tries to convert the error into a reference to the specified type
         if let Some(server_err) = self.err.downcast_ref::<ServerErr>() {
-            return match server_err {
-                ServerErr::Login((code, msg)) => err_response(*code, msg),
-                ServerErr::Registration((code, msg)) => err_response(*code, msg),
-            };
+            let failed = RequestFailed::with_code(server_err.code(), server_err.to_string());
+            let mut response = (server_err.status(), Json(failed)).into_response();
+
+            if let ServerErr::TooManyRequests(retry_after) = server_err {
+                response
+                    .headers_mut()
+                    .insert(header::RETRY_AFTER, retry_after_secs(retry_after).into());
+            }
+            return response;
         };
 
         tracing::error!("{}", self.err);
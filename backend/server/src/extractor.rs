@@ -1,11 +1,23 @@
-use std::str::FromStr;
+use std::{
+    convert::Infallible,
+    net::{IpAddr, SocketAddr},
+    str::FromStr,
+};
 
-use axum::{async_trait, extract::FromRequestParts, Extension, Json, RequestPartsExt};
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRequestParts},
+    Extension, Json, RequestPartsExt,
+};
+use chrono::{Duration, Utc};
 use hyper::{header, http::request::Parts, StatusCode};
-use tracing::info;
-use uchat_domain::ids::{SessionId, UserId};
-use uchat_endpoint::RequestFailed;
-use uchat_query::OwnedAsyncConnection;
+use tracing::{error, info};
+use uchat_domain::{
+    access_token::Scope,
+    ids::{AccessTokenId, SessionId, UserId},
+};
+use uchat_endpoint::{ErrorCode, RequestFailed};
+use uchat_query::{session::Fingerprint, OwnedAsyncConnection};
 
 use crate::AppState;
 
@@ -43,10 +55,120 @@ where
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(clap::Args, Clone, Debug, Default)]
+pub struct ProxyConfig {
+    /// addresses of reverse proxies whose X-Forwarded-For header is trusted, comma separated
+    #[clap(
+        long = "trusted-proxy",
+        env = "API_TRUSTED_PROXIES",
+        value_delimiter = ','
+    )]
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+impl ProxyConfig {
+    /// Walks X-Forwarded-For back from the peer for as long as each hop was added by a trusted proxy
+    ///
+    /// ! Anything before the first untrusted hop was written by the client, so it's never used
+    pub fn client_ip(&self, peer: IpAddr, forwarded_for: Option<&str>) -> IpAddr {
+        let mut client = peer;
+        if let Some(forwarded_for) = forwarded_for {
+            for hop in forwarded_for.rsplit(',') {
+                if !self.trusted_proxies.contains(&client) {
+                    break;
+                }
+                match hop.trim().parse::<IpAddr>() {
+                    Ok(ip) => client = ip,
+                    Err(_) => break,
+                }
+            }
+        }
+        client
+    }
+}
+
+/// The device making the request, used to fingerprint sessions
+#[derive(Clone, Debug, Default)]
+pub struct ClientInfo {
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+impl ClientInfo {
+    pub fn fingerprint(&self) -> Fingerprint {
+        Fingerprint::new(self.user_agent.clone(), self.ip_address.clone())
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ClientInfo
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        let user_agent = parts
+            .headers
+            .get(header::USER_AGENT)
+            .and_then(|header| header.to_str().ok())
+            .map(str::to_owned);
+
+        let state = extract_state!(parts);
+
+        let forwarded_for = parts
+            .headers
+            .get("x-forwarded-for")
+            .and_then(|header| header.to_str().ok());
+        let ip_address = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| state.proxy.client_ip(addr.ip(), forwarded_for).to_string());
+
+        Ok(Self {
+            user_agent,
+            ip_address,
+        })
+    }
+}
+
+// NOTE Sessions are only marked as seen once in a while, so every request doesn't write to the db
+const LAST_SEEN_INTERVAL_MINUTES: i64 = 5;
+
+#[derive(Clone, Debug)]
+pub enum Credentials {
+    /// a browser session, authenticated by cookies
+    Session(SessionId),
+    /// a personal access token sent as `Authorization: Bearer`, limited to its scopes
+    AccessToken {
+        id: AccessTokenId,
+        scopes: Vec<Scope>,
+    },
+}
+
+#[derive(Clone, Debug)]
 pub struct UserSession {
     pub user_id: UserId,
-    pub session_id: SessionId,
+    pub credentials: Credentials,
+}
+
+impl UserSession {
+    /// The browser session making the request, `None` for access tokens
+    pub fn session_id(&self) -> Option<SessionId> {
+        match self.credentials {
+            Credentials::Session(session_id) => Some(session_id),
+            Credentials::AccessToken { .. } => None,
+        }
+    }
+
+    /// Browser sessions can do everything, access tokens only reach endpoints that declare one of their scopes
+    pub fn allows(&self, scope: Option<Scope>) -> bool {
+        match (&self.credentials, scope) {
+            (Credentials::Session(_), _) => true,
+            (Credentials::AccessToken { scopes, .. }, Some(scope)) => scopes.contains(&scope),
+            (Credentials::AccessToken { .. }, None) => false,
+        }
+    }
 }
 
 #[async_trait]
@@ -63,12 +185,50 @@ where
         let unauthorized = || {
             (
                 StatusCode::UNAUTHORIZED,
-                Json(RequestFailed {
-                    msg: "unauthorized".into(),
-                }),
+                Json(RequestFailed::with_code(
+                    ErrorCode::Unauthorized,
+                    "unauthorized",
+                )),
             )
         };
 
+        // ? A bearer token is the only credential looked at, cookies sent along with it are ignored
+        if let Some(authorization) = parts.headers.get(header::AUTHORIZATION) {
+            let token = authorization
+                .to_str()
+                .ok()
+                .and_then(|value| value.strip_prefix("Bearer "))
+                .ok_or_else(unauthorized)?;
+            let token_hash = uchat_crypto::token::hash_secret(token.trim());
+
+            let access_token = uchat_query::access_token::find(&mut conn, &token_hash)
+                .ok()
+                .flatten()
+                .ok_or_else(unauthorized)?;
+
+            let stale = access_token.last_used_at.map_or(true, |last_used_at| {
+                Utc::now() - last_used_at > Duration::minutes(LAST_SEEN_INTERVAL_MINUTES)
+            });
+            if stale {
+                if let Err(e) = uchat_query::access_token::touch(&mut conn, access_token.id) {
+                    error!(err = %e, "failed to update access token last used time");
+                }
+            }
+
+            info!(
+                user_id = access_token.user_id.into_inner().to_string(),
+                "user authenticated with an access token"
+            );
+
+            return Ok(Self {
+                user_id: access_token.user_id,
+                credentials: Credentials::AccessToken {
+                    id: access_token.id,
+                    scopes: access_token.scopes(),
+                },
+            });
+        }
+
         let cookies = parts
             .headers
             .get(header::COOKIE)
@@ -81,13 +241,13 @@ where
 
         let session_signature =
             uchat_cookie::get_from_str(cookies, uchat_cookie::SESSION_SIGNATURE)
-                .and_then(|sig| uchat_crypto::decode_base64(sig).ok())
-                .and_then(|sig| uchat_crypto::sign::signature_from_bytes(sig).ok())
+                .and_then(|sig| uchat_crypto::sign::KeyedSignature::decode(sig).ok())
                 .ok_or_else(unauthorized)?;
 
+        // ? Signatures made by a retired key fail here, which ends the session
         state
             .signing_keys
-            .verify(session_id.as_uuid().as_bytes(), session_signature)
+            .verify(session_id.as_uuid().as_bytes(), &session_signature)
             .map_err(|_| unauthorized())?;
 
         let session = uchat_query::session::get(&mut conn, session_id)
@@ -95,6 +255,17 @@ where
             .flatten()
             .ok_or_else(unauthorized)?;
 
+        // ? Expired sessions are purged periodically, until then they must not be usable
+        if session.expires_at <= Utc::now() {
+            return Err(unauthorized());
+        }
+
+        if Utc::now() - session.last_seen_at > Duration::minutes(LAST_SEEN_INTERVAL_MINUTES) {
+            if let Err(e) = uchat_query::session::touch(&mut conn, session.id) {
+                error!(err = %e, "failed to update session last seen time");
+            }
+        }
+
         info!(
             user_id = session.user_id.into_inner().to_string(),
             "user logged in"
@@ -102,7 +273,89 @@ where
 
         Ok(Self {
             user_id: session.user_id,
-            session_id: session.id,
+            credentials: Credentials::Session(session.id),
         })
     }
 }
+
+/// Rejects requests without a valid CSRF token for the session, should run alongside `UserSession`
+pub struct CsrfToken;
+
+#[async_trait]
+impl<S> FromRequestParts<S> for CsrfToken
+where
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, Json<RequestFailed>);
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        // ? Browsers never attach a bearer token on their own, so requests using one can't be forged by other sites
+        if parts.headers.contains_key(header::AUTHORIZATION) {
+            return Ok(Self);
+        }
+
+        let state = extract_state!(parts);
+
+        let forbidden = || {
+            (
+                StatusCode::FORBIDDEN,
+                Json(RequestFailed::with_code(
+                    ErrorCode::InvalidCsrfToken,
+                    "invalid csrf token",
+                )),
+            )
+        };
+
+        let session_id = parts
+            .headers
+            .get(header::COOKIE)
+            .and_then(|header| header.to_str().ok())
+            .and_then(|cookies| uchat_cookie::get_from_str(cookies, uchat_cookie::SESSION_ID))
+            .and_then(|id| SessionId::from_str(id).ok())
+            .ok_or_else(forbidden)?;
+
+        let token = parts
+            .headers
+            .get(uchat_endpoint::CSRF_HEADER)
+            .and_then(|header| header.to_str().ok())
+            .ok_or_else(forbidden)?;
+
+        if !crate::session::verify_csrf_token(&state, session_id, token) {
+            return Err(forbidden());
+        }
+
+        Ok(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(addr: &str) -> IpAddr {
+        addr.parse().unwrap()
+    }
+
+    #[test]
+    fn forwarded_for_is_ignored_from_untrusted_peers() {
+        let config = ProxyConfig::default();
+        let client = config.client_ip(ip("203.0.113.7"), Some("198.51.100.1"));
+        assert_eq!(client, ip("203.0.113.7"));
+    }
+
+    #[test]
+    fn forwarded_for_is_followed_through_trusted_proxies_only() {
+        let config = ProxyConfig {
+            trusted_proxies: vec![ip("10.0.0.1"), ip("10.0.0.2")],
+        };
+
+        let client = config.client_ip(ip("10.0.0.1"), Some("198.51.100.1, 203.0.113.7, 10.0.0.2"));
+        assert_eq!(client, ip("203.0.113.7"));
+
+        let client = config.client_ip(ip("10.0.0.1"), Some("not an ip"));
+        assert_eq!(client, ip("10.0.0.1"));
+
+        let client = config.client_ip(ip("10.0.0.1"), None);
+        assert_eq!(client, ip("10.0.0.1"));
+    }
+}
@@ -1,59 +1,73 @@
-use std::path::PathBuf;
+use std::sync::Arc;
 
 use axum::{
     async_trait,
-    body::Full,
-    extract::{Path, State},
-    response::{IntoResponse, Response},
+    extract::{Path, Query, State},
+    response::{IntoResponse, Redirect, Response},
     Json,
 };
-use hyper::{header, StatusCode};
+use chrono::Utc;
+use hyper::{
+    header::{self, HeaderValue},
+    HeaderMap, StatusCode,
+};
 use serde::Deserialize;
-use uchat_domain::ids::ImageId;
+use uchat_domain::{
+    access_token::Scope,
+    ids::{ImageId, UserId},
+};
+use uchat_endpoint::app_url::user_content::ImageSize;
+use uchat_query::AsyncConnection;
 use uuid::Uuid;
 
 use crate::{
-    error::{ApiErr, ApiResult},
-    extractor::{DbConnection, UserSession},
+    blob::BlobStore,
+    error::{ApiErr, ApiResult, ServerErr},
+    extractor::{ClientInfo, CsrfToken, DbConnection, UserSession},
+    http_cache::{self, ByteRange},
+    image::{decode_data_url, storage_key, variant_dimensions, ImageType, ValidImage},
     AppState,
 };
 
+pub mod message;
 pub mod post;
 pub mod user;
 
-const USER_CONTENT_DIR: &str = "usercontent";
-
 #[async_trait]
 pub trait PublicApiRequest {
     type Response: IntoResponse;
     async fn process_request(
         self,
         conn: DbConnection,
+        client: ClientInfo,
         state: AppState,
     ) -> ApiResult<Self::Response>;
 }
 
 /// This handler is used for public endpoints
 ///
-/// It will extract the request payload, the db connection and the app state
+/// It will extract the request payload, the db connection, the client info and the app state
 ///
 /// NOTE The `Json` extractor will deserialize the request body into the specified type
 ///
 /// NOTE The `State` extractor will extract the app state from the request
 pub async fn with_public_handler<'a, Req>(
     conn: DbConnection,
+    client: ClientInfo,
     State(state): State<AppState>,
     Json(payload): Json<Req>,
 ) -> ApiResult<Req::Response>
 where
     Req: PublicApiRequest + Deserialize<'a>,
 {
-    payload.process_request(conn, state).await
+    payload.process_request(conn, client, state).await
 }
 
 #[async_trait]
 pub trait AuthorizedApiRequest {
     type Response: IntoResponse;
+    /// The scope an access token needs to use the endpoint, `None` keeps it to browser sessions
+    const SCOPE: Option<Scope> = None;
     async fn process_request(
         self,
         conn: DbConnection,
@@ -62,68 +76,229 @@ pub trait AuthorizedApiRequest {
     ) -> ApiResult<Self::Response>;
 }
 
+/// This handler is used for endpoints that need a logged in user
+///
+/// NOTE Every authorized endpoint is authenticated by cookies, so the `CsrfToken` extractor guards all of them
 pub async fn with_handler<'a, Req>(
     conn: DbConnection,
     session: UserSession,
+    _csrf: CsrfToken,
     State(state): State<AppState>,
     Json(payload): Json<Req>,
 ) -> ApiResult<Req::Response>
 where
     Req: AuthorizedApiRequest + Deserialize<'a>,
 {
+    if !session.allows(Req::SCOPE) {
+        return Err(ServerErr::InsufficientScope.into());
+    }
     payload.process_request(conn, session, state).await
 }
 
-pub async fn save_image<T>(id: ImageId, data: T) -> Result<(), ApiErr>
-where
-    T: AsRef<[u8]>,
-{
-    // NOTE tokio::fs enables async file operations
-    use tokio::fs;
+/// Validates an image sent as a data url, then stores it in every size along with its metadata
+pub async fn save_image(
+    conn: &mut AsyncConnection,
+    blobs: &dyn BlobStore,
+    user_id: UserId,
+    id: ImageId,
+    data_url: &str,
+) -> Result<(), ApiErr> {
+    let image = ValidImage::from_data_url(data_url)?;
+    // NOTE Decoding and resizing is CPU bound, so it's kept off the async runtime's threads
+    let encoded = tokio::task::spawn_blocking(move || image.encode_sizes()).await??;
+
+    let full = encoded
+        .iter()
+        .find(|encoded| encoded.size == ImageSize::Full)
+        .ok_or_else(|| ApiErr::from_msg("the full size image wasn't encoded"))?;
+    let record = uchat_query::image::Image {
+        id,
+        user_id,
+        content_type: full.image_type.mime_type().to_owned(),
+        byte_size: full.data.len() as i32,
+        width: full.width as i32,
+        height: full.height as i32,
+        created_at: Utc::now(),
+    };
 
-    let mut path = PathBuf::from(USER_CONTENT_DIR);
-    fs::create_dir_all(&path).await?;
-    path.push(id.to_string());
-    fs::write(&path, data).await?;
+    // ? The record goes first, so the sizes written before a failure can be found and removed
+    uchat_query::image::new(conn, &record)?;
+    for encoded in encoded {
+        let key = storage_key(id, encoded.size);
+        let put = blobs
+            .put(&key, encoded.data, encoded.image_type.mime_type())
+            .await;
+        if let Err(e) = put {
+            if let Err(cleanup) = delete_image(conn, blobs, user_id, id).await {
+                tracing::error!(err = %cleanup.err, image_id = ?id, "failed to delete image");
+            }
+            return Err(e);
+        }
+    }
 
     Ok(())
 }
 
-const PARSE_ERR: &str =
-    "Failed to parse image data url, it must be in a 'data:text/plain;base64' format";
+/// Removes an image that `user_id` saved with `save_image`
+///
+/// An image that was already removed, or that another user saved, is left alone and is not an error
+///
+/// NOTE The record is only removed once every size is, so a failed removal can be tried again
+pub async fn delete_image(
+    conn: &mut AsyncConnection,
+    blobs: &dyn BlobStore,
+    user_id: UserId,
+    id: ImageId,
+) -> Result<(), ApiErr> {
+    match uchat_query::image::get(conn, id)? {
+        Some(image) if image.user_id == user_id => (),
+        _ => return Ok(()),
+    }
 
-fn parse_img_data_url(raw: &str) -> Result<(&str, &str), ApiErr> {
-    // NOTE Using a closure makes it easier to refactor this to have more specific error handling if necessary
-    let parse = move || {
-        let (header, image_data) = raw.split_once(',')?;
-        // ? header: data:text/plain;base64
-        let mime_type = header.split_once("data:")?.1.split_once(";base64")?.0;
+    let mut errors = vec![];
+    for size in ImageSize::VARIANTS.into_iter().chain([ImageSize::Full]) {
+        if let Err(e) = blobs.delete(&storage_key(id, size)).await {
+            errors.push(format!("{}: {}", size.as_str(), e.err));
+        }
+    }
+    if !errors.is_empty() {
+        return Err(ApiErr::from_msg(format!(
+            "failed to delete image sizes: {}",
+            errors.join(", ")
+        )));
+    }
 
-        Some((image_data, mime_type))
-    };
+    uchat_query::image::delete(conn, user_id, id)?;
+    Ok(())
+}
+
+async fn get_image_blob(blobs: &dyn BlobStore, key: &str) -> Result<Vec<u8>, ApiErr> {
+    blobs
+        .get(key)
+        .await?
+        .ok_or_else(|| ApiErr::new(StatusCode::NOT_FOUND, "image not found"))
+}
 
-    parse().ok_or_else(|| ApiErr::from_msg(PARSE_ERR))
+#[derive(Deserialize)]
+pub struct ImageParams {
+    // ? Named after `user_content::SIZE_PARAM`
+    #[serde(default)]
+    size: ImageSize,
 }
 
 pub async fn load_image(
+    DbConnection(mut conn): DbConnection,
+    State(blobs): State<Arc<dyn BlobStore>>,
     Path(img_id): Path<Uuid>,
-) -> Result<Response<Full<axum::body::Bytes>>, ApiErr> {
-    use tokio::fs;
+    Query(params): Query<ImageParams>,
+    headers: HeaderMap,
+) -> Result<Response, ApiErr> {
+    let id = ImageId::from(img_id);
+    let image = uchat_query::image::get(&mut conn, id)?;
+
+    // NOTE Images smaller than the requested size weren't downscaled, so they're served in full
+    let size = match image {
+        Some(ref image) => {
+            let (width, height) = (image.width as u32, image.height as u32);
+            match variant_dimensions(params.size, width, height) {
+                Some(_) => params.size,
+                None => ImageSize::Full,
+            }
+        }
+        None => ImageSize::Full,
+    };
+    let key = storage_key(id, size);
+
+    let etag = http_cache::etag(&key);
+    let last_modified = image.as_ref().map(|image| image.created_at);
+    let mut response_headers = HeaderMap::new();
+    response_headers.insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static(http_cache::IMMUTABLE),
+    );
+    response_headers.insert(header::ETAG, HeaderValue::from_str(&etag)?);
+    if let Some(last_modified) = last_modified {
+        response_headers.insert(
+            header::LAST_MODIFIED,
+            HeaderValue::from_str(&http_cache::http_date(last_modified))?,
+        );
+    }
 
-    let mut path = PathBuf::from(USER_CONTENT_DIR);
-    path.push(img_id.to_string());
+    // ? Images saved before they were validated only exist as blobs, so the blob is checked first
+    let legacy_data = match image {
+        Some(_) => None,
+        None => Some(get_image_blob(blobs.as_ref(), &key).await?),
+    };
+
+    if http_cache::is_fresh(&headers, &etag, last_modified) {
+        return Ok((StatusCode::NOT_MODIFIED, response_headers).into_response());
+    }
+
+    // NOTE Backends that can serve the image themselves take the download off the server
+    if image.is_some() {
+        if let Some(presigned) = blobs.presigned_url(&key)? {
+            // ? The url only changes once per window, so the redirect is cached while it works
+            let cache_control = format!("public, max-age={}", presigned.valid_for.as_secs());
+            let mut response = Redirect::temporary(presigned.url.as_str()).into_response();
+            response.headers_mut().insert(
+                header::CACHE_CONTROL,
+                HeaderValue::from_str(&cache_control)?,
+            );
+            return Ok(response);
+        }
+    }
+
+    let data = match legacy_data {
+        Some(data) => data,
+        None => get_image_blob(blobs.as_ref(), &key).await?,
+    };
 
-    let raw = fs::read_to_string(path).await?;
+    let (data, content_type) = match image {
+        Some(image) if size == ImageSize::Full => (data, image.content_type),
+        Some(image) => {
+            let image_type = ImageType::from_mime_type(&image.content_type)
+                .ok_or_else(|| ApiErr::from_msg("unknown image content type"))?;
+            (data, image_type.variant_type().mime_type().to_owned())
+        }
+        // ? Images saved before they were validated are still stored as data urls
+        None => {
+            let data_url = String::from_utf8(data)
+                .map_err(|_| ApiErr::new(StatusCode::NOT_FOUND, "image not found"))?;
+            let data = decode_data_url(&data_url)?;
+            let image_type = ImageType::sniff(&data).ok_or_else(|| {
+                ApiErr::new(StatusCode::UNSUPPORTED_MEDIA_TYPE, "unsupported image type")
+            })?;
+            (data, image_type.mime_type().to_owned())
+        }
+    };
 
-    let (image_data, mime_type) = parse_img_data_url(&raw)?;
-    {
-        use base64::{engine::general_purpose, Engine as _};
-        let image_data = general_purpose::STANDARD.decode(image_data).unwrap();
+    response_headers.insert(header::CONTENT_TYPE, HeaderValue::from_str(&content_type)?);
+    // ? Browsers must not second guess the type, which was checked when the image was saved
+    response_headers.insert(
+        header::X_CONTENT_TYPE_OPTIONS,
+        HeaderValue::from_static("nosniff"),
+    );
+    response_headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
 
-        Ok(Response::builder()
-            .status(StatusCode::OK)
-            .header(header::CONTENT_TYPE, mime_type)
-            .body(Full::from(image_data))
-            .unwrap())
+    let len = data.len();
+    match http_cache::byte_range(&headers, &etag, len) {
+        ByteRange::Full => Ok((StatusCode::OK, response_headers, data).into_response()),
+        ByteRange::Partial(range) => {
+            let content_range = format!("bytes {}-{}/{len}", range.start, range.end - 1);
+            response_headers.insert(
+                header::CONTENT_RANGE,
+                HeaderValue::from_str(&content_range)?,
+            );
+            let data = data[range].to_vec();
+            Ok((StatusCode::PARTIAL_CONTENT, response_headers, data).into_response())
+        }
+        ByteRange::Unsatisfiable => {
+            let content_range = format!("bytes */{len}");
+            response_headers.insert(
+                header::CONTENT_RANGE,
+                HeaderValue::from_str(&content_range)?,
+            );
+            Ok((StatusCode::RANGE_NOT_SATISFIABLE, response_headers).into_response())
+        }
     }
 }
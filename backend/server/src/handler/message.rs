@@ -0,0 +1,193 @@
+use crate::{error::ApiErr, prelude::*};
+use axum::{async_trait, Json};
+
+use uchat_domain::access_token::Scope;
+use uchat_endpoint::{
+    message::{
+        endpoint::{
+            Conversations, ConversationsOk, SendDirectMessage, SendDirectMessageOk,
+            ViewConversation, ViewConversationOk,
+        },
+        types::{Conversation, DirectMessage},
+    },
+    post::types::{Chat, Content, NewPostOptions},
+};
+use uchat_query::{post::Post, DieselError};
+
+use crate::{
+    error::ApiResult,
+    extractor::{DbConnection, UserSession},
+    AppState,
+};
+
+use super::AuthorizedApiRequest;
+
+const MESSAGES_PER_CONVERSATION: i64 = 100;
+
+pub fn to_direct_message(post: Post) -> ApiResult<DirectMessage> {
+    let Some(to) = post.direct_message_to else {
+        return Err(ApiErr::from_msg("post is not a direct message"));
+    };
+
+    match serde_json::from_value(post.content.0) {
+        Ok(Content::Chat(chat)) => Ok(DirectMessage {
+            id: post.id,
+            from: post.user_id,
+            to,
+            message: chat.message,
+            time_sent: post.time_posted,
+        }),
+        _ => Err(ApiErr::from_msg("invalid direct message data")),
+    }
+}
+
+fn many_to_direct_messages(posts: Vec<Post>) -> Vec<DirectMessage> {
+    posts
+        .into_iter()
+        .filter_map(|p| {
+            let post_id = p.id;
+            match to_direct_message(p) {
+                Ok(res) => Some(res),
+                Err(e) => {
+                    tracing::error!(err = %e.err, post_id = ?post_id, "message contains invalid data");
+                    None
+                }
+            }
+        })
+        .collect::<Vec<DirectMessage>>()
+}
+
+#[async_trait]
+impl AuthorizedApiRequest for SendDirectMessage {
+    type Response = (StatusCode, Json<SendDirectMessageOk>);
+    const SCOPE: Option<Scope> = Some(Scope::Post);
+
+    async fn process_request(
+        self,
+        DbConnection(mut conn): DbConnection,
+        session: UserSession,
+        _state: AppState,
+    ) -> ApiResult<Self::Response> {
+        if self.to == session.user_id {
+            return Err(ApiErr::new(
+                StatusCode::BAD_REQUEST,
+                "cannot send a message to self",
+            ));
+        }
+
+        match uchat_query::user::get(&mut conn, self.to) {
+            Ok(_) => (),
+            Err(DieselError::NotFound) => {
+                return Err(ApiErr::new(StatusCode::NOT_FOUND, "recipient not found"))
+            }
+            Err(e) => return Err(e.into()),
+        }
+
+        let content = Content::Chat(Chat {
+            headline: None,
+            message: self.message.clone(),
+        });
+        let options = NewPostOptions {
+            direct_message_to: Some(self.to),
+            ..Default::default()
+        };
+        let post = Post::new(session.user_id, content, options)?;
+        let time_sent = post.time_posted;
+
+        let id = uchat_query::post::new(&mut conn, post)?;
+
+        Ok((
+            StatusCode::OK,
+            Json(SendDirectMessageOk {
+                message: DirectMessage {
+                    id,
+                    from: session.user_id,
+                    to: self.to,
+                    message: self.message,
+                    time_sent,
+                },
+            }),
+        ))
+    }
+}
+
+#[async_trait]
+impl AuthorizedApiRequest for Conversations {
+    type Response = (StatusCode, Json<ConversationsOk>);
+    const SCOPE: Option<Scope> = Some(Scope::Read);
+
+    async fn process_request(
+        self,
+        DbConnection(mut conn): DbConnection,
+        session: UserSession,
+        _state: AppState,
+    ) -> ApiResult<Self::Response> {
+        let latest = uchat_query::message::get_latest_messages(&mut conn, session.user_id)?;
+        let latest = many_to_direct_messages(latest);
+
+        let partner_of = |message: &DirectMessage| {
+            if message.from == session.user_id {
+                message.to
+            } else {
+                message.from
+            }
+        };
+        let partner_ids = latest.iter().map(partner_of).collect::<Vec<_>>();
+        let mut partners = uchat_query::user::get_many(&mut conn, &partner_ids)?;
+
+        let mut conversations = Vec::with_capacity(latest.len());
+        for last_message in latest {
+            let Some(user) = partners.remove(&partner_of(&last_message)) else {
+                continue;
+            };
+            conversations.push(Conversation {
+                with_user: super::user::to_public(user)?,
+                last_message,
+            });
+        }
+
+        Ok((StatusCode::OK, Json(ConversationsOk { conversations })))
+    }
+}
+
+#[async_trait]
+impl AuthorizedApiRequest for ViewConversation {
+    type Response = (StatusCode, Json<ViewConversationOk>);
+    const SCOPE: Option<Scope> = Some(Scope::Read);
+
+    async fn process_request(
+        self,
+        DbConnection(mut conn): DbConnection,
+        session: UserSession,
+        _state: AppState,
+    ) -> ApiResult<Self::Response> {
+        let with_user = match uchat_query::user::get(&mut conn, self.with_user) {
+            Ok(user) => super::user::to_public(user)?,
+            Err(DieselError::NotFound) => {
+                return Err(ApiErr::new(StatusCode::NOT_FOUND, "user not found"))
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let messages = {
+            let posts = uchat_query::message::get_conversation(
+                &mut conn,
+                session.user_id,
+                self.with_user,
+                MESSAGES_PER_CONVERSATION,
+            )?;
+            let mut messages = many_to_direct_messages(posts);
+            // ? Messages are displayed from oldest to newest
+            messages.reverse();
+            messages
+        };
+
+        Ok((
+            StatusCode::OK,
+            Json(ViewConversationOk {
+                with_user,
+                messages,
+            }),
+        ))
+    }
+}
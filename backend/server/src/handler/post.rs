@@ -1,133 +1,75 @@
 use crate::{error::ApiErr, prelude::*};
 use axum::{async_trait, Json};
 use chrono::Utc;
+use std::collections::HashSet;
 
-use uchat_domain::{ids::ImageId, Username};
+use uchat_domain::{
+    access_token::Scope,
+    ids::{ImageId, PostId, UserId},
+    post::Emoji,
+    UserFacingError, Username,
+};
 use uchat_endpoint::{
-    app_url::{self, user_content},
+    app_url::{
+        self,
+        user_content::{self, ImageSize},
+    },
     post::{
         endpoint::{
-            Bookmark, BookmarkOk, BookmarkedPosts, BookmarkedPostsOk, Boost, BoostOk, HomePosts,
-            HomePostsOk, LikedPosts, LikedPostsOk, NewPost, NewPostOk, React, ReactOk,
-            TrendingPosts, TrendingPostsOk, Vote, VoteOk,
+            Bookmark, BookmarkOk, BookmarkedPosts, BookmarkedPostsOk, Boost, BoostOk,
+            CancelScheduledPost, CancelScheduledPostOk, DeletePost, DeletePostOk, EditPost,
+            EditPostOk, HomePosts, HomePostsOk, LikedPosts, LikedPostsOk, NewPost, NewPostOk,
+            React, ReactOk, ReschedulePost, ReschedulePostOk, ScheduledPosts, ScheduledPostsOk,
+            TrendingPosts, TrendingPostsOk, ViewPost, ViewPostOk, Vote, VoteOk,
+        },
+        types::{
+            BookmarkAction, BoostAction, Content, EmojiReaction, ImageKind, ImageSrcSet,
+            ImageVariant, LikeStatus, PostCursor, PublicPost, ReactAction,
         },
-        types::{BookmarkAction, BoostAction, ImageKind, LikeStatus, PublicPost},
     },
 };
 use uchat_query::{
-    post::{AggregatePostInfo, Post},
-    AsyncConnection,
+    post::{AggregatePostInfo, EmojiReactionCount, Post, PostDetails, POSTS_PER_PAGE},
+    AsyncConnection, DieselError,
 };
 
 use crate::{
+    blob::BlobStore,
     error::ApiResult,
     extractor::{DbConnection, UserSession},
+    image::variant_dimensions,
     AppState,
 };
 
-use super::{save_image, AuthorizedApiRequest};
+use super::{delete_image, save_image, AuthorizedApiRequest};
 
 pub fn to_public(
     conn: &mut AsyncConnection,
     post: Post,
     session: Option<&UserSession>,
 ) -> ApiResult<PublicPost> {
-    use uchat_query::post as query_post;
-    use uchat_query::user as query_user;
+    let viewer = session.map(|session| session.user_id);
+    let details = uchat_query::post::get_details(conn, std::slice::from_ref(&post), viewer)?;
 
-    if let Ok(mut content) = serde_json::from_value(post.content.0) {
-        use uchat_endpoint::post::types::Content;
-        match content {
-            Content::Image(ref mut image) => {
-                if let ImageKind::Id(id) = image.kind {
-                    let url = app_url::domain_and(user_content::ROOT)
-                        .join(user_content::IMAGES)
-                        .unwrap()
-                        .join(&id.to_string())
-                        .unwrap();
-                    image.kind = ImageKind::Url(url);
-                }
-            }
-            Content::Poll(ref mut poll) => {
-                for (id, result) in query_post::get_poll_results(conn, post.id)?.results {
-                    for choice in poll.choices.iter_mut().filter(|c| c.id == id).take(1) {
-                        choice.num_votes = result;
-                    }
-                }
-
-                if let Some(session) = session {
-                    poll.voted = query_post::did_vote(conn, session.user_id, post.id)?;
-                }
-            }
-            _ => (),
-        }
-        let aggregate_reactions = query_post::aggregate_reactions(conn, post.id)?;
-
-        Ok(PublicPost {
-            id: post.id,
-            by_user: {
-                let profile = query_user::get(conn, post.user_id)?;
-                super::user::to_public(profile)?
-            },
-            content,
-            time_posted: post.time_posted,
-            reply_to: {
-                match post.reply_to {
-                    Some(other_post_id) => {
-                        let original_post = query_post::get(conn, other_post_id)?;
-                        let original_user = query_user::get(conn, original_post.user_id)?;
-                        Some((
-                            Username::new(original_user.handle).unwrap(),
-                            original_user.id,
-                            other_post_id,
-                        ))
-                    }
-                    None => None,
-                }
-            },
-            like_status: {
-                match session {
-                    Some(session) => {
-                        match query_post::get_reaction(conn, post.id, session.user_id)? {
-                            Some(reaction) if reaction.like_status == -1 => LikeStatus::Dislike,
-                            Some(reaction) if reaction.like_status == 1 => LikeStatus::Like,
-                            _ => LikeStatus::NoReaction,
-                        }
-                    }
-                    None => LikeStatus::NoReaction,
-                }
-            },
-            bookmarked: {
-                match session {
-                    Some(session) => query_post::get_bookmark(conn, session.user_id, post.id)?,
-                    None => false,
-                }
-            },
-            boosted: {
-                match session {
-                    Some(session) => query_post::get_boost(conn, session.user_id, post.id)?,
-                    None => false,
-                }
-            },
-            likes: aggregate_reactions.likes,
-            dislikes: aggregate_reactions.dislikes,
-            boosts: aggregate_reactions.boosts,
-        })
-    } else {
-        Err(ApiErr::from_msg("invalid post data"))
-    }
+    to_public_with_details(post, &details)
 }
 
+/// Converts posts loaded together, skipping (and logging) the ones that contain invalid data
+///
+/// NOTE The details for every post are loaded in a fixed number of queries
 pub fn many_to_public(
     conn: &mut AsyncConnection,
     posts: Vec<Post>,
     session: Option<&UserSession>,
-) -> Vec<PublicPost> {
-    posts
+) -> ApiResult<Vec<PublicPost>> {
+    let viewer = session.map(|session| session.user_id);
+    let details = uchat_query::post::get_details(conn, &posts, viewer)?;
+
+    let posts = posts
         .into_iter()
         .filter_map(|p| {
             let post_id = p.id;
-            match to_public(conn, p, session) {
+            match to_public_with_details(p, &details) {
                 Ok(res) => Some(res),
                 Err(e) => {
                     tracing::error!(err = %e.err, post_id = ?post_id, "post contains invalid data");
@@ -135,28 +77,291 @@ pub fn many_to_public(
                 }
             }
         })
-        .collect::<Vec<PublicPost>>()
+        .collect::<Vec<PublicPost>>();
+    Ok(posts)
+}
+
+/// Builds the urls of an uploaded image in each size it was saved in
+fn image_src_set(id: ImageId, image: Option<&uchat_query::image::Image>) -> ImageSrcSet {
+    let src = app_url::domain_and(user_content::ROOT)
+        .join(user_content::IMAGES)
+        .unwrap()
+        .join(&id.to_string())
+        .unwrap();
+
+    let variants = match image {
+        Some(image) => {
+            let (width, height) = (image.width as u32, image.height as u32);
+            ImageSize::VARIANTS
+                .into_iter()
+                .filter_map(|size| {
+                    let (variant_width, _) = variant_dimensions(size, width, height)?;
+                    Some(ImageVariant {
+                        url: user_content::sized(&src, size),
+                        width: variant_width,
+                    })
+                })
+                .chain(std::iter::once(ImageVariant {
+                    url: src.clone(),
+                    width,
+                }))
+                .collect()
+        }
+        // ? Images saved before their metadata was recorded only have the full size
+        None => vec![],
+    };
+
+    ImageSrcSet { src, variants }
+}
+
+fn to_public_with_details(post: Post, details: &PostDetails) -> ApiResult<PublicPost> {
+    let Ok(mut content) = serde_json::from_value(post.content.0) else {
+        return Err(ApiErr::from_msg("invalid post data"));
+    };
+
+    match content {
+        Content::Image(ref mut image) => {
+            if let ImageKind::Id(id) = image.kind {
+                image.kind = ImageKind::Url(image_src_set(id, details.images.get(&id)));
+            }
+        }
+        Content::Gallery(ref mut gallery) => {
+            for image in gallery.images.iter_mut() {
+                if let ImageKind::Id(id) = image.kind {
+                    image.kind = ImageKind::Url(image_src_set(id, details.images.get(&id)));
+                }
+            }
+        }
+        Content::Poll(ref mut poll) => {
+            let results = details.poll_results.get(&post.id).into_iter().flatten();
+            for &(id, result) in results {
+                for choice in poll.choices.iter_mut().filter(|c| c.id == id).take(1) {
+                    choice.num_votes = result;
+                }
+            }
+
+            poll.voted = details.votes.get(&post.id).copied();
+        }
+        _ => (),
+    }
+
+    let missing_user = || ApiErr::from_msg("post author not found");
+    let aggregate = details.aggregates.get(&post.id);
+
+    Ok(PublicPost {
+        id: post.id,
+        by_user: {
+            let profile = details.users.get(&post.user_id).ok_or_else(missing_user)?;
+            super::user::to_public(profile.clone())?
+        },
+        content,
+        time_posted: post.time_posted,
+        edited_at: details.last_edits.get(&post.id).copied(),
+        reply_to: {
+            // NOTE Parents the viewer can't see aren't loaded, so the reply shows as a new post
+            match post.reply_to.and_then(|id| details.parents.get(&id)) {
+                Some(original_post) => {
+                    let original_user = details
+                        .users
+                        .get(&original_post.user_id)
+                        .ok_or_else(missing_user)?;
+                    Some((
+                        Username::new(original_user.handle.clone()).unwrap(),
+                        original_user.id,
+                        original_post.id,
+                    ))
+                }
+                None => None,
+            }
+        },
+        like_status: match details.like_statuses.get(&post.id) {
+            Some(-1) => LikeStatus::Dislike,
+            Some(1) => LikeStatus::Like,
+            _ => LikeStatus::NoReaction,
+        },
+        bookmarked: details.bookmarked.contains(&post.id),
+        boosted: details.boosted.contains(&post.id),
+        likes: aggregate.map_or(0, |a| a.likes),
+        dislikes: aggregate.map_or(0, |a| a.dislikes),
+        boosts: aggregate.map_or(0, |a| a.boosts),
+        emoji_reactions: {
+            let counts = details.emoji_reactions.get(&post.id).cloned();
+            to_emoji_reactions(counts.unwrap_or_default())
+        },
+    })
+}
+
+fn get_like_status(
+    conn: &mut AsyncConnection,
+    post_id: PostId,
+    user_id: UserId,
+) -> ApiResult<LikeStatus> {
+    let like_status = match uchat_query::post::get_reaction(conn, post_id, user_id)? {
+        Some(reaction) if reaction.like_status == -1 => LikeStatus::Dislike,
+        Some(reaction) if reaction.like_status == 1 => LikeStatus::Like,
+        _ => LikeStatus::NoReaction,
+    };
+    Ok(like_status)
+}
+
+fn to_emoji_reactions(counts: Vec<EmojiReactionCount>) -> Vec<EmojiReaction> {
+    counts
+        .into_iter()
+        .filter_map(|count| {
+            // NOTE Emoji that are no longer allowed are left out instead of failing the whole post
+            let emoji = Emoji::new(count.emoji).ok()?;
+            Some(EmojiReaction {
+                emoji,
+                count: count.count,
+                reacted: count.reacted,
+            })
+        })
+        .collect()
+}
+
+/// Converts the cursor sent by the client into the keyset used by the query layer
+pub fn decode_cursor(
+    cursor: Option<PostCursor>,
+) -> ApiResult<Option<uchat_query::post::PostCursor>> {
+    cursor
+        .map(|cursor| {
+            cursor
+                .decode()
+                .ok_or_else(|| ApiErr::new(StatusCode::BAD_REQUEST, "invalid cursor"))
+        })
+        .transpose()
+}
+
+/// Returns the cursor for the page after `posts`, or `None` if this was the last page
+///
+/// NOTE Must be called before `many_to_public`, which skips posts that contain invalid data
+pub fn next_cursor(posts: &[Post]) -> Option<PostCursor> {
+    if posts.len() < POSTS_PER_PAGE as usize {
+        return None;
+    }
+    posts
+        .last()
+        .map(|post| PostCursor::new(post.time_posted, post.id))
+}
+
+/// Saves the images sent along with the content, replacing their data with the ids they were saved under
+///
+/// NOTE Only the images in `owned_ids` may be referenced by id, so a post can't claim someone else's
+async fn save_content_image(
+    conn: &mut AsyncConnection,
+    blobs: &dyn BlobStore,
+    user_id: UserId,
+    owned_ids: &[ImageId],
+    mut content: Content,
+) -> ApiResult<Content> {
+    let kinds = match content {
+        Content::Image(ref mut img) => vec![&mut img.kind],
+        Content::Gallery(ref mut gallery) => {
+            gallery
+                .validate()
+                .map_err(|e| ApiErr::new(StatusCode::BAD_REQUEST, e.formatted_error()))?;
+            gallery.images.iter_mut().map(|img| &mut img.kind).collect()
+        }
+        _ => vec![],
+    };
+
+    // ? Checked up front so nothing is saved for a post that gets rejected
+    for kind in kinds.iter() {
+        match kind {
+            ImageKind::DataUrl(_) => (),
+            ImageKind::Id(id) if owned_ids.contains(id) => (),
+            _ => {
+                return Err(ApiErr::new(
+                    StatusCode::BAD_REQUEST,
+                    "images must be uploaded with the post",
+                ))
+            }
+        }
+    }
+
+    for kind in kinds {
+        if let ImageKind::DataUrl(data) = kind {
+            let id = ImageId::new();
+            save_image(conn, blobs, user_id, id, data).await?;
+            *kind = ImageKind::Id(id);
+        }
+    }
+    Ok(content)
+}
+
+/// Replaces the urls clients send back for the images they were given with the images' ids
+///
+/// NOTE Images can be removed or reordered, so they're matched by their url against `original_ids`
+fn resolve_image_urls(content: &mut Content, original_ids: &[ImageId]) -> ApiResult<()> {
+    let kinds = match content {
+        Content::Image(img) => vec![&mut img.kind],
+        Content::Gallery(gallery) => gallery.images.iter_mut().map(|img| &mut img.kind).collect(),
+        _ => vec![],
+    };
+
+    for kind in kinds {
+        if let ImageKind::Url(src_set) = kind {
+            let id = original_ids
+                .iter()
+                .copied()
+                .find(|&id| image_src_set(id, None).src == src_set.src)
+                .ok_or_else(|| ApiErr::new(StatusCode::BAD_REQUEST, "unknown image"))?;
+            *kind = ImageKind::Id(id);
+        }
+    }
+    Ok(())
+}
+
+/// Removes the images referenced by deleted content, given along with the user who posted it
+///
+/// NOTE The posts are already gone at this point, so failures are only logged
+async fn delete_content_images(
+    conn: &mut AsyncConnection,
+    blobs: &dyn BlobStore,
+    contents: Vec<(UserId, uchat_query::post::Content)>,
+) {
+    // ? Revisions usually share images with the content that replaced them
+    let images = contents
+        .into_iter()
+        .flat_map(|(user_id, content)| content.image_ids().into_iter().map(move |id| (user_id, id)))
+        .collect::<HashSet<_>>();
+    for (user_id, id) in images {
+        if let Err(e) = delete_image(conn, blobs, user_id, id).await {
+            tracing::error!(err = %e.err, image_id = ?id, "failed to delete image");
+        }
+    }
 }
 
 #[async_trait]
 impl AuthorizedApiRequest for NewPost {
     type Response = (StatusCode, Json<NewPostOk>);
+    const SCOPE: Option<Scope> = Some(Scope::Post);
 
     async fn process_request(
         self,
         DbConnection(mut conn): DbConnection,
         session: UserSession,
-        _state: AppState,
+        state: AppState,
     ) -> ApiResult<Self::Response> {
-        use uchat_endpoint::post::types::Content;
-        let mut content = self.content;
-        if let Content::Image(ref mut img) = content {
-            if let ImageKind::DataUrl(data) = &img.kind {
-                let id = ImageId::new();
-                save_image(id, &data).await?;
-                img.kind = ImageKind::Id(id);
-            }
-        };
+        // ! Direct messages go through `SendDirectMessage`, which checks who they're sent to
+        if self.options.direct_message_to.is_some() {
+            return Err(ApiErr::new(
+                StatusCode::BAD_REQUEST,
+                "direct messages cannot be sent as posts",
+            ));
+        }
+        if let Some(reply_to) = self.options.reply_to {
+            check_reply_to(&mut conn, reply_to)?;
+        }
+
+        let content = save_content_image(
+            &mut conn,
+            state.blobs.as_ref(),
+            session.user_id,
+            &[],
+            self.content,
+        )
+        .await?;
 
         let post = Post::new(session.user_id, content, self.options)?;
 
@@ -169,6 +374,7 @@ impl AuthorizedApiRequest for NewPost {
 #[async_trait]
 impl AuthorizedApiRequest for TrendingPosts {
     type Response = (StatusCode, Json<TrendingPostsOk>);
+    const SCOPE: Option<Scope> = Some(Scope::Read);
 
     async fn process_request(
         self,
@@ -176,18 +382,39 @@ impl AuthorizedApiRequest for TrendingPosts {
         session: UserSession,
         _state: AppState,
     ) -> ApiResult<Self::Response> {
-        let posts = {
-            let posts = uchat_query::post::get_trending(&mut conn)?;
-            super::post::many_to_public(&mut conn, posts, Some(&session))
+        let cursor = self
+            .cursor
+            .map(|cursor| {
+                cursor
+                    .decode_score()
+                    .ok_or_else(|| ApiErr::new(StatusCode::BAD_REQUEST, "invalid cursor"))
+            })
+            .transpose()?;
+
+        let (posts, next_cursor) = {
+            let ranked_posts = uchat_query::post::get_trending(&mut conn, cursor)?;
+            let next_cursor = if ranked_posts.len() < POSTS_PER_PAGE as usize {
+                None
+            } else {
+                ranked_posts
+                    .last()
+                    .map(|(post, score)| PostCursor::from_score(*score, post.id))
+            };
+            let posts = ranked_posts.into_iter().map(|(post, _)| post).collect();
+            (
+                super::post::many_to_public(&mut conn, posts, Some(&session))?,
+                next_cursor,
+            )
         };
 
-        Ok((StatusCode::OK, Json(TrendingPostsOk { posts })))
+        Ok((StatusCode::OK, Json(TrendingPostsOk { posts, next_cursor })))
     }
 }
 
 #[async_trait]
 impl AuthorizedApiRequest for Bookmark {
     type Response = (StatusCode, Json<BookmarkOk>);
+    const SCOPE: Option<Scope> = Some(Scope::Post);
 
     async fn process_request(
         self,
@@ -216,6 +443,7 @@ impl AuthorizedApiRequest for Bookmark {
 #[async_trait]
 impl AuthorizedApiRequest for Boost {
     type Response = (StatusCode, Json<BoostOk>);
+    const SCOPE: Option<Scope> = Some(Scope::Post);
 
     async fn process_request(
         self,
@@ -244,6 +472,7 @@ impl AuthorizedApiRequest for Boost {
 #[async_trait]
 impl AuthorizedApiRequest for React {
     type Response = (StatusCode, Json<ReactOk>);
+    const SCOPE: Option<Scope> = Some(Scope::Post);
 
     async fn process_request(
         self,
@@ -251,30 +480,62 @@ impl AuthorizedApiRequest for React {
         session: UserSession,
         _state: AppState,
     ) -> ApiResult<Self::Response> {
-        let reaction = uchat_query::post::Reaction {
-            post_id: self.post_id,
-            user_id: session.user_id,
-            reaction: None,
-            like_status: match self.like_status {
-                LikeStatus::Like => 1,
-                LikeStatus::Dislike => -1,
-                LikeStatus::NoReaction => 0,
-            },
-            created_at: Utc::now(),
+        let like_status = match self.action {
+            ReactAction::LikeStatus(like_status) => {
+                let reaction = uchat_query::post::Reaction {
+                    post_id: self.post_id,
+                    user_id: session.user_id,
+                    like_status: match like_status {
+                        LikeStatus::Like => 1,
+                        LikeStatus::Dislike => -1,
+                        LikeStatus::NoReaction => 0,
+                    },
+                    created_at: Utc::now(),
+                };
+
+                uchat_query::post::react(&mut conn, reaction)?;
+                like_status
+            }
+            ReactAction::AddEmoji(emoji) => {
+                uchat_query::post::add_emoji_reaction(
+                    &mut conn,
+                    session.user_id,
+                    self.post_id,
+                    emoji.as_ref(),
+                )?;
+                get_like_status(&mut conn, self.post_id, session.user_id)?
+            }
+            ReactAction::RemoveEmoji(emoji) => {
+                uchat_query::post::remove_emoji_reaction(
+                    &mut conn,
+                    session.user_id,
+                    self.post_id,
+                    emoji.as_ref(),
+                )?;
+                get_like_status(&mut conn, self.post_id, session.user_id)?
+            }
         };
 
-        uchat_query::post::react(&mut conn, reaction)?;
-
         let AggregatePostInfo {
             likes, dislikes, ..
         } = uchat_query::post::aggregate_reactions(&mut conn, self.post_id)?;
 
+        let emoji_reactions = {
+            let counts = uchat_query::post::aggregate_emoji_reactions(
+                &mut conn,
+                self.post_id,
+                Some(session.user_id),
+            )?;
+            to_emoji_reactions(counts)
+        };
+
         Ok((
             StatusCode::OK,
             Json(ReactOk {
-                like_status: self.like_status,
+                like_status,
                 likes,
                 dislikes,
+                emoji_reactions,
             }),
         ))
     }
@@ -283,6 +544,7 @@ impl AuthorizedApiRequest for React {
 #[async_trait]
 impl AuthorizedApiRequest for Vote {
     type Response = (StatusCode, Json<VoteOk>);
+    const SCOPE: Option<Scope> = Some(Scope::Post);
 
     async fn process_request(
         self,
@@ -300,6 +562,7 @@ impl AuthorizedApiRequest for Vote {
 #[async_trait]
 impl AuthorizedApiRequest for HomePosts {
     type Response = (StatusCode, Json<HomePostsOk>);
+    const SCOPE: Option<Scope> = Some(Scope::Read);
 
     async fn process_request(
         self,
@@ -307,18 +570,24 @@ impl AuthorizedApiRequest for HomePosts {
         session: UserSession,
         _state: AppState,
     ) -> ApiResult<Self::Response> {
-        let posts = {
-            let posts = uchat_query::post::get_home_posts(&mut conn, session.user_id)?;
-            super::post::many_to_public(&mut conn, posts, Some(&session))
+        let cursor = decode_cursor(self.cursor)?;
+        let (posts, next_cursor) = {
+            let posts = uchat_query::post::get_home_posts(&mut conn, session.user_id, cursor)?;
+            let next_cursor = next_cursor(&posts);
+            (
+                super::post::many_to_public(&mut conn, posts, Some(&session))?,
+                next_cursor,
+            )
         };
 
-        Ok((StatusCode::OK, Json(HomePostsOk { posts })))
+        Ok((StatusCode::OK, Json(HomePostsOk { posts, next_cursor })))
     }
 }
 
 #[async_trait]
 impl AuthorizedApiRequest for LikedPosts {
     type Response = (StatusCode, Json<LikedPostsOk>);
+    const SCOPE: Option<Scope> = Some(Scope::Read);
 
     async fn process_request(
         self,
@@ -326,18 +595,108 @@ impl AuthorizedApiRequest for LikedPosts {
         session: UserSession,
         _state: AppState,
     ) -> ApiResult<Self::Response> {
-        let posts = {
-            let posts = uchat_query::post::get_liked_posts(&mut conn, session.user_id)?;
-            super::post::many_to_public(&mut conn, posts, Some(&session))
+        let cursor = decode_cursor(self.cursor)?;
+        let (posts, next_cursor) = {
+            let posts = uchat_query::post::get_liked_posts(&mut conn, session.user_id, cursor)?;
+            let next_cursor = next_cursor(&posts);
+            (
+                super::post::many_to_public(&mut conn, posts, Some(&session))?,
+                next_cursor,
+            )
         };
 
-        Ok((StatusCode::OK, Json(LikedPostsOk { posts })))
+        Ok((StatusCode::OK, Json(LikedPostsOk { posts, next_cursor })))
     }
 }
 
 #[async_trait]
 impl AuthorizedApiRequest for BookmarkedPosts {
     type Response = (StatusCode, Json<BookmarkedPostsOk>);
+    const SCOPE: Option<Scope> = Some(Scope::Read);
+
+    async fn process_request(
+        self,
+        DbConnection(mut conn): DbConnection,
+        session: UserSession,
+        _state: AppState,
+    ) -> ApiResult<Self::Response> {
+        let cursor = decode_cursor(self.cursor)?;
+        let (posts, next_cursor) = {
+            let posts =
+                uchat_query::post::get_bookmarked_posts(&mut conn, session.user_id, cursor)?;
+            let next_cursor = next_cursor(&posts);
+            (
+                super::post::many_to_public(&mut conn, posts, Some(&session))?,
+                next_cursor,
+            )
+        };
+
+        Ok((
+            StatusCode::OK,
+            Json(BookmarkedPostsOk { posts, next_cursor }),
+        ))
+    }
+}
+
+#[async_trait]
+impl AuthorizedApiRequest for ViewPost {
+    type Response = (StatusCode, Json<ViewPostOk>);
+    const SCOPE: Option<Scope> = Some(Scope::Read);
+
+    async fn process_request(
+        self,
+        DbConnection(mut conn): DbConnection,
+        session: UserSession,
+        _state: AppState,
+    ) -> ApiResult<Self::Response> {
+        let post = match uchat_query::post::get(&mut conn, self.post_id) {
+            Ok(post) => post,
+            Err(DieselError::NotFound) => {
+                return Err(ApiErr::new(StatusCode::NOT_FOUND, "post not found"))
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        // NOTE Direct messages and other users' scheduled posts aren't part of any public thread
+        let is_scheduled = post.time_posted > Utc::now() && post.user_id != session.user_id;
+        if post.direct_message_to.is_some() || is_scheduled {
+            return Err(ApiErr::new(StatusCode::NOT_FOUND, "post not found"));
+        }
+
+        let ancestors = {
+            let posts = uchat_query::post::get_ancestors(&mut conn, post.id, session.user_id)?;
+            many_to_public(&mut conn, posts, Some(&session))?
+        };
+
+        let cursor = decode_cursor(self.cursor)?;
+        let (descendants, next_cursor) = {
+            let posts =
+                uchat_query::post::get_descendants(&mut conn, post.id, session.user_id, cursor)?;
+            let next_cursor = next_cursor(&posts);
+            (
+                many_to_public(&mut conn, posts, Some(&session))?,
+                next_cursor,
+            )
+        };
+
+        let post = to_public(&mut conn, post, Some(&session))?;
+
+        Ok((
+            StatusCode::OK,
+            Json(ViewPostOk {
+                ancestors,
+                post,
+                descendants,
+                next_cursor,
+            }),
+        ))
+    }
+}
+
+#[async_trait]
+impl AuthorizedApiRequest for ScheduledPosts {
+    type Response = (StatusCode, Json<ScheduledPostsOk>);
+    const SCOPE: Option<Scope> = Some(Scope::Read);
 
     async fn process_request(
         self,
@@ -346,10 +705,196 @@ impl AuthorizedApiRequest for BookmarkedPosts {
         _state: AppState,
     ) -> ApiResult<Self::Response> {
         let posts = {
-            let posts = uchat_query::post::get_bookmarked_posts(&mut conn, session.user_id)?;
-            super::post::many_to_public(&mut conn, posts, Some(&session))
+            let posts = uchat_query::post::get_scheduled_posts(&mut conn, session.user_id)?;
+            many_to_public(&mut conn, posts, Some(&session))?
+        };
+
+        Ok((StatusCode::OK, Json(ScheduledPostsOk { posts })))
+    }
+}
+
+#[async_trait]
+impl AuthorizedApiRequest for ReschedulePost {
+    type Response = (StatusCode, Json<ReschedulePostOk>);
+    const SCOPE: Option<Scope> = Some(Scope::Post);
+
+    async fn process_request(
+        self,
+        DbConnection(mut conn): DbConnection,
+        session: UserSession,
+        _state: AppState,
+    ) -> ApiResult<Self::Response> {
+        // NOTE Publishing a post is rescheduling it to the current time
+        let time_posted = self.time_posted.max(Utc::now());
+
+        let updated =
+            uchat_query::post::reschedule(&mut conn, session.user_id, self.post_id, time_posted)?;
+        if !updated {
+            return Err(ApiErr::new(
+                StatusCode::NOT_FOUND,
+                "scheduled post not found",
+            ));
+        }
+
+        Ok((StatusCode::OK, Json(ReschedulePostOk { time_posted })))
+    }
+}
+
+#[async_trait]
+impl AuthorizedApiRequest for CancelScheduledPost {
+    type Response = (StatusCode, Json<CancelScheduledPostOk>);
+    const SCOPE: Option<Scope> = Some(Scope::Post);
+
+    async fn process_request(
+        self,
+        DbConnection(mut conn): DbConnection,
+        session: UserSession,
+        state: AppState,
+    ) -> ApiResult<Self::Response> {
+        let cancelled =
+            uchat_query::post::cancel_scheduled(&mut conn, session.user_id, self.post_id)?
+                .ok_or_else(|| ApiErr::new(StatusCode::NOT_FOUND, "scheduled post not found"))?;
+        let cancelled = cancelled
+            .into_iter()
+            .map(|content| (session.user_id, content))
+            .collect();
+        delete_content_images(&mut conn, state.blobs.as_ref(), cancelled).await;
+
+        Ok((StatusCode::OK, Json(CancelScheduledPostOk)))
+    }
+}
+
+/// Checks that a reply is to a published post in a public thread
+///
+/// NOTE Replies show who wrote the post they reply to, so hidden posts look like they don't exist
+fn check_reply_to(conn: &mut AsyncConnection, post_id: PostId) -> ApiResult<()> {
+    let not_found = || ApiErr::new(StatusCode::NOT_FOUND, "replied post not found");
+    let parent = match uchat_query::post::get(conn, post_id) {
+        Ok(post) => post,
+        Err(DieselError::NotFound) => return Err(not_found()),
+        Err(e) => return Err(e.into()),
+    };
+
+    if parent.direct_message_to.is_some() || parent.time_posted > Utc::now() {
+        return Err(not_found());
+    }
+    Ok(())
+}
+
+/// Gets a post that the session user wrote, so that it may be changed
+fn get_own_post(
+    conn: &mut AsyncConnection,
+    session: &UserSession,
+    post_id: PostId,
+) -> ApiResult<Post> {
+    let post = match uchat_query::post::get(conn, post_id) {
+        Ok(post) => post,
+        Err(DieselError::NotFound) => {
+            return Err(ApiErr::new(StatusCode::NOT_FOUND, "post not found"))
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    if post.user_id != session.user_id {
+        return Err(ApiErr::new(
+            StatusCode::FORBIDDEN,
+            "only the author may change a post",
+        ));
+    }
+    Ok(post)
+}
+
+#[async_trait]
+impl AuthorizedApiRequest for DeletePost {
+    type Response = (StatusCode, Json<DeletePostOk>);
+    const SCOPE: Option<Scope> = Some(Scope::Post);
+
+    async fn process_request(
+        self,
+        DbConnection(mut conn): DbConnection,
+        session: UserSession,
+        state: AppState,
+    ) -> ApiResult<Self::Response> {
+        get_own_post(&mut conn, &session, self.post_id)?;
+
+        let deleted = uchat_query::post::delete(&mut conn, session.user_id, self.post_id)?
+            .ok_or_else(|| ApiErr::new(StatusCode::NOT_FOUND, "post not found"))?;
+        delete_content_images(&mut conn, state.blobs.as_ref(), deleted).await;
+
+        Ok((StatusCode::OK, Json(DeletePostOk)))
+    }
+}
+
+#[async_trait]
+impl AuthorizedApiRequest for EditPost {
+    type Response = (StatusCode, Json<EditPostOk>);
+    const SCOPE: Option<Scope> = Some(Scope::Post);
+
+    async fn process_request(
+        self,
+        DbConnection(mut conn): DbConnection,
+        session: UserSession,
+        state: AppState,
+    ) -> ApiResult<Self::Response> {
+        let original = get_own_post(&mut conn, &session, self.post_id)?;
+        let original_ids = original.content.image_ids();
+        let original_content: Content = serde_json::from_value(original.content.0)
+            .map_err(|_| ApiErr::from_msg("invalid post data"))?;
+
+        // ! Votes reference the poll choices, so changing them would corrupt the results
+        if matches!(original_content, Content::Poll(_)) {
+            return Err(ApiErr::new(
+                StatusCode::BAD_REQUEST,
+                "polls cannot be edited",
+            ));
+        }
+        // ? A single image can grow into a gallery, and a gallery can shrink back to one image
+        let has_images =
+            |content: &Content| matches!(content, Content::Image(_) | Content::Gallery(_));
+        let same_kind =
+            std::mem::discriminant(&original_content) == std::mem::discriminant(&self.content);
+        if !same_kind && !(has_images(&original_content) && has_images(&self.content)) {
+            return Err(ApiErr::new(
+                StatusCode::BAD_REQUEST,
+                "an edit cannot change the kind of post",
+            ));
+        }
+
+        let mut content = self.content;
+        resolve_image_urls(&mut content, &original_ids)?;
+        let content = save_content_image(
+            &mut conn,
+            state.blobs.as_ref(),
+            session.user_id,
+            &original_ids,
+            content,
+        )
+        .await?;
+        let content = uchat_query::post::Content(serde_json::to_value(content)?);
+        let new_ids = content
+            .image_ids()
+            .into_iter()
+            .filter(|id| !original_ids.contains(id))
+            .collect::<Vec<_>>();
+
+        // NOTE Replaced images stay around for the revisions, until the post is deleted
+        let edited = uchat_query::post::edit(&mut conn, session.user_id, self.post_id, content);
+        if edited.is_err() {
+            for id in new_ids {
+                let deleted =
+                    delete_image(&mut conn, state.blobs.as_ref(), session.user_id, id).await;
+                if let Err(e) = deleted {
+                    tracing::error!(err = %e.err, image_id = ?id, "failed to delete image");
+                }
+            }
+        }
+        edited?;
+
+        let post = {
+            let post = uchat_query::post::get(&mut conn, self.post_id)?;
+            to_public(&mut conn, post, Some(&session))?
         };
 
-        Ok((StatusCode::OK, Json(BookmarkedPostsOk { posts })))
+        Ok((StatusCode::OK, Json(EditPostOk { post })))
     }
 }
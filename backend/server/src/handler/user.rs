@@ -1,33 +1,50 @@
 use crate::{
     error::{ApiErr, ServerErr},
-    extractor::UserSession,
+    extractor::{ClientInfo, UserSession},
     prelude::*,
 };
 use axum::{async_trait, Json};
 use chrono::{Duration, Utc};
-use tracing::info;
+use std::sync::OnceLock;
+use tracing::{error, info};
 use uchat_domain::{
+    access_token::Scope,
     ids::{ImageId, UserId},
     user::DisplayName,
 };
 use uchat_endpoint::{
     user::{
         endpoint::{
-            CreateUser, CreateUserOk, FollowUser, FollowUserOk, GetMyProfile, GetMyProfileOk,
-            IsFollowing, IsFollowingOk, Login, LoginOk, UpdateProfile, UpdateProfileOk,
-            ViewProfile, ViewProfileOk,
+            AccessTokens, AccessTokensOk, ActiveSessions, ActiveSessionsOk, ConfirmEmail,
+            ConfirmEmailOk, ConfirmTotp, ConfirmTotpOk, CreateAccessToken, CreateAccessTokenOk,
+            CreateUser, CreateUserOk, DisableTotp, DisableTotpOk, EnrollTotp, EnrollTotpOk,
+            FollowUser, FollowUserOk, GetMyProfile, GetMyProfileOk, IsFollowing, IsFollowingOk,
+            Login, LoginOk, LoginSession, Logout, LogoutAll, LogoutAllOk, LogoutOk,
+            RevokeAccessToken, RevokeAccessTokenOk, RevokeSession, RevokeSessionOk,
+            SendEmailConfirmation, SendEmailConfirmationOk, UpdateProfile, UpdateProfileOk,
+            VerifyLoginTotp, VerifyLoginTotpOk, ViewProfile, ViewProfileOk,
         },
-        types::{FollowAction, PublicUserProfile},
+        types::{ActiveSession, FollowAction, PublicAccessToken, PublicUserProfile},
     },
     Update,
 };
 use uchat_query::{
     session::Session,
     user::{UpdateProfileParams, User},
+    util::DeleteStatus,
+    QueryError,
 };
 use url::Url;
 
-use crate::{error::ApiResult, extractor::DbConnection, AppState};
+use crate::{
+    error::ApiResult,
+    extractor::DbConnection,
+    mail::Mail,
+    rate_limit::RateLimitKey,
+    session::{self, SessionCookies},
+    token::{self, TokenPurpose},
+    AppState,
+};
 
 use super::{save_image, AuthorizedApiRequest, PublicApiRequest};
 
@@ -58,47 +75,199 @@ fn profile_id_to_url(id: &str) -> Url {
 fn new_session(
     state: &AppState,
     conn: &mut uchat_query::AsyncConnection,
+    client: &ClientInfo,
     user_id: UserId,
 ) -> ApiResult<(Session, SessionSignature, Duration)> {
-    let fingerprint = serde_json::json!({});
     let session_duration = Duration::weeks(3);
-    let session = uchat_query::session::new(conn, user_id, session_duration, fingerprint.into())?;
+    let session = uchat_query::session::new(conn, user_id, session_duration, client.fingerprint())?;
 
     let mut rng = state.rng.clone();
     let signature = state
         .signing_keys
-        .sign(&mut rng, session.id.as_uuid().as_bytes());
-
-    let signature = uchat_crypto::encode_base64(signature);
+        .sign(&mut rng, session.id.as_uuid().as_bytes())
+        .encode();
 
     Ok((session, SessionSignature(signature), session_duration))
 }
 
+// NOTE Frontend route that reads the token from the link and confirms the email
+const CONFIRM_EMAIL_ROUTE: &str = "account/confirm_email/";
+
+async fn send_email_confirmation(state: &AppState, user_id: UserId, email: &str) -> ApiResult<()> {
+    let purpose = TokenPurpose::ConfirmEmail {
+        email: email.to_owned(),
+    };
+    let token = token::issue(state, user_id, purpose, Duration::days(1))?;
+    let link = state.mailer.frontend_link(CONFIRM_EMAIL_ROUTE, &token)?;
+
+    state
+        .mailer
+        .send(Mail {
+            to: email.to_owned(),
+            subject: "Confirm your email".to_owned(),
+            body: format!(
+                "Confirm your email by opening the link below, it expires in 24 hours.\n\n{link}"
+            ),
+        })
+        .await
+}
+
+// NOTE Frontend route where the user picks a new password
+const RESET_PASSWORD_ROUTE: &str = "account/reset_password/";
+
+/// Saves a reset for every account with the email, returning the handles along with their links
+fn new_password_resets(
+    state: &AppState,
+    conn: &mut uchat_query::AsyncConnection<'_>,
+    email: &str,
+) -> ApiResult<Vec<(String, String)>> {
+    // ? A fresh rng, since the one in the state is cloned on every use
+    let mut rng = uchat_crypto::new_rng();
+    let expires_at = Utc::now() + Duration::hours(1);
+
+    let users = uchat_query::user::find_by_confirmed_email(conn, email)?;
+    let mut resets = Vec::with_capacity(users.len());
+    for user in users {
+        let secret = uchat_crypto::token::new_secret(&mut rng);
+        let token_hash = uchat_crypto::token::hash_secret(&secret);
+        uchat_query::password_reset::new(conn, user.id, &token_hash, expires_at)?;
+
+        let link = state.mailer.frontend_link(RESET_PASSWORD_ROUTE, &secret)?;
+        resets.push((user.handle, link));
+    }
+    Ok(resets)
+}
+
+async fn send_password_reset(
+    state: &AppState,
+    email: &str,
+    handle: &str,
+    link: &str,
+) -> ApiResult<()> {
+    state
+        .mailer
+        .send(Mail {
+            to: email.to_owned(),
+            subject: "Reset your password".to_owned(),
+            body: format!(
+                "A password reset was requested for @{handle}, open the link below to choose a new password. \
+                It expires in 1 hour.\n\n{link}\n\nIf you didn't request it, you can ignore this email."
+            ),
+        })
+        .await
+}
+
+fn login_session(
+    state: &AppState,
+    conn: &mut uchat_query::AsyncConnection,
+    client: &ClientInfo,
+    user: User,
+) -> ApiResult<(LoginSession, SessionCookies)> {
+    let (session, signature, duration) = new_session(state, conn, client, user.id)?;
+
+    let profile_image_url = user.profile_image.as_ref().map(|id| profile_id_to_url(id));
+
+    let login_session = LoginSession {
+        session_expires: Utc::now() + duration,
+        csrf_token: session::csrf_token(state, session.id),
+        display_name: user.display_name,
+        email: user.email,
+        profile_image: profile_image_url,
+        user_id: user.id,
+    };
+    let cookies = session::set_cookies(session.id, &signature.0, duration);
+
+    Ok((login_session, cookies))
+}
+
+/// Hash of a password nobody has, so logins for unknown users still do the work of verifying one
+fn dummy_password_hash() -> &'static str {
+    static HASH: OnceLock<String> = OnceLock::new();
+
+    HASH.get_or_init(|| {
+        uchat_crypto::hash_password("not a real password")
+            .expect("failed to hash dummy password")
+            .to_string()
+    })
+}
+
+const TOTP_ISSUER: &str = "uChat";
+const TOTP_CHALLENGE_MINUTES: i64 = 5;
+const RECOVERY_CODE_COUNT: usize = 10;
+
+/// Checks a TOTP code or a recovery code, either one is only accepted once
+fn verify_second_factor(
+    conn: &mut uchat_query::AsyncConnection,
+    user_id: UserId,
+    code: &str,
+) -> ApiResult<bool> {
+    use uchat_crypto::totp::{is_recovery_code, is_totp_code, TotpSecret};
+
+    // ? Anything that can't be a code is turned down before hashing it against every recovery code
+    let code = code.trim();
+    if !is_totp_code(code) && !is_recovery_code(code) {
+        return Ok(false);
+    }
+
+    let totp = uchat_query::totp::get(conn, user_id)?.filter(|totp| totp.is_enabled());
+    let Some(totp) = totp else {
+        return Ok(false);
+    };
+
+    if is_totp_code(code) {
+        let secret = TotpSecret::from_base32(&totp.secret)?;
+        let Some(step) = secret.verify(code, Utc::now().timestamp() as u64) else {
+            return Ok(false);
+        };
+        return Ok(uchat_query::totp::use_step(conn, user_id, step as i64)?);
+    }
+
+    // ? Anything else can only be a recovery code
+    for recovery_code in uchat_query::totp::get_recovery_codes(conn, user_id)? {
+        let hash = uchat_crypto::password::deserialize_hash(&recovery_code.code_hash)?;
+        if uchat_crypto::verify_password(code, &hash).is_ok() {
+            return Ok(uchat_query::totp::use_recovery_code(
+                conn,
+                recovery_code.id,
+            )?);
+        }
+    }
+    Ok(false)
+}
+
 #[async_trait]
 impl PublicApiRequest for CreateUser {
-    type Response = (StatusCode, Json<CreateUserOk>);
+    type Response = (StatusCode, SessionCookies, Json<CreateUserOk>);
 
     async fn process_request(
         self,
         DbConnection(mut conn): DbConnection,
+        client: ClientInfo,
         state: AppState,
     ) -> ApiResult<Self::Response> {
+        let rate_limit_keys = RateLimitKey::register(&client);
+        state.rate_limiter.reserve(&rate_limit_keys)?;
+
         let password_hash = uchat_crypto::hash_password(&self.password)?;
-        let user_id = uchat_query::user::new(&mut conn, password_hash, &self.username)
-            .map_err(|_| ServerErr::account_exists())?;
+        let user_id = uchat_query::user::new(&mut conn, password_hash, &self.username).map_err(
+            |e| match e {
+                QueryError::UniqueViolation => ServerErr::AccountExists.into(),
+                e => ApiErr::from(e),
+            },
+        )?;
 
         info!(username = self.username.as_ref(), "new user created");
 
-        let (session, signature, duration) = new_session(&state, &mut conn, user_id)?;
+        let (session, signature, duration) = new_session(&state, &mut conn, &client, user_id)?;
 
         Ok((
             StatusCode::CREATED,
+            session::set_cookies(session.id, &signature.0, duration),
             Json(CreateUserOk {
                 username: self.username,
                 user_id,
-                session_id: session.id,
                 session_expires: Utc::now() + duration,
-                session_signature: signature.0,
+                csrf_token: session::csrf_token(&state, session.id),
             }),
         ))
     }
@@ -106,51 +275,556 @@ impl PublicApiRequest for CreateUser {
 
 #[async_trait]
 impl PublicApiRequest for Login {
-    type Response = (StatusCode, Json<LoginOk>);
+    type Response = (StatusCode, SessionCookies, Json<LoginOk>);
 
     async fn process_request(
         self,
         DbConnection(mut conn): DbConnection,
+        client: ClientInfo,
         state: AppState,
     ) -> ApiResult<Self::Response> {
         let _span =
             tracing::span!(tracing::Level::INFO, "logging in", user = %self.username.as_ref())
                 .entered();
 
-        let check_password = || -> ApiResult<()> {
-            let hash = uchat_query::user::get_password_hash(&mut conn, &self.username)?;
-            let hash = uchat_crypto::password::deserialize_hash(&hash)?;
+        // ? Reserved before the password is checked, so locked out attempts don't even cost a hash
+        let rate_limit_keys = RateLimitKey::login(&client, self.username.as_ref());
+        state.rate_limiter.reserve(&rate_limit_keys)?;
 
-            uchat_crypto::verify_password(self.password, &hash)?;
-            Ok(())
+        let hash = match uchat_query::user::get_password_hash(&mut conn, &self.username) {
+            Ok(hash) => Some(hash),
+            Err(QueryError::NotFound) => None,
+            Err(e) => return Err(e.into()),
         };
-        check_password().map_err(|_| ServerErr::wrong_password())?;
 
-        let user = uchat_query::user::find(&mut conn, &self.username)
-            .map_err(|_| ServerErr::missing_login())?;
+        // ? Unknown users are checked against a dummy hash, so they take as long to reject as a wrong password
+        let password_matches = {
+            let stored_hash = hash.as_deref().unwrap_or_else(dummy_password_hash);
+            let stored_hash = uchat_crypto::password::deserialize_hash(stored_hash)?;
+            uchat_crypto::verify_password(self.password, &stored_hash).is_ok() && hash.is_some()
+        };
+        if !password_matches {
+            return Err(ServerErr::InvalidCredentials.into());
+        }
+        state.rate_limiter.release(&rate_limit_keys);
+        state
+            .rate_limiter
+            .reset(&[RateLimitKey::login_handle(self.username.as_ref())]);
+
+        let user = uchat_query::user::find(&mut conn, &self.username)?;
+
+        if uchat_query::totp::is_enabled(&mut conn, user.id)? {
+            let challenge = token::issue(
+                &state,
+                user.id,
+                TokenPurpose::TotpLogin,
+                Duration::minutes(TOTP_CHALLENGE_MINUTES),
+            )?;
+            return Ok((
+                StatusCode::OK,
+                session::no_cookies(),
+                Json(LoginOk::TotpRequired { challenge }),
+            ));
+        }
 
-        let (session, signature, duration) = new_session(&state, &mut conn, user.id)?;
+        let (session, cookies) = login_session(&state, &mut conn, &client, user)?;
 
-        let profile_image_url = user.profile_image.as_ref().map(|id| profile_id_to_url(id));
+        Ok((StatusCode::OK, cookies, Json(LoginOk::LoggedIn(session))))
+    }
+}
+
+#[async_trait]
+impl PublicApiRequest for VerifyLoginTotp {
+    type Response = (StatusCode, SessionCookies, Json<VerifyLoginTotpOk>);
+
+    async fn process_request(
+        self,
+        DbConnection(mut conn): DbConnection,
+        client: ClientInfo,
+        state: AppState,
+    ) -> ApiResult<Self::Response> {
+        let claims = token::verify(&state, &self.challenge)?;
+        if claims.purpose != TokenPurpose::TotpLogin {
+            return Err(token::invalid_token());
+        }
+
+        let rate_limit_keys = RateLimitKey::totp(&client, claims.user_id, &self.challenge);
+        state.rate_limiter.reserve(&rate_limit_keys)?;
+
+        if !verify_second_factor(&mut conn, claims.user_id, &self.code)? {
+            return Err(ApiErr::new(StatusCode::BAD_REQUEST, "invalid code"));
+        }
+        state.rate_limiter.release(&rate_limit_keys);
+        state
+            .rate_limiter
+            .reset(&[RateLimitKey::TotpUser(claims.user_id)]);
+
+        let user = uchat_query::user::get(&mut conn, claims.user_id)?;
+        let (session, cookies) = login_session(&state, &mut conn, &client, user)?;
+
+        Ok((StatusCode::OK, cookies, Json(VerifyLoginTotpOk { session })))
+    }
+}
+
+#[async_trait]
+impl AuthorizedApiRequest for Logout {
+    type Response = (StatusCode, SessionCookies, Json<LogoutOk>);
+
+    async fn process_request(
+        self,
+        DbConnection(mut conn): DbConnection,
+        session: UserSession,
+        _state: AppState,
+    ) -> ApiResult<Self::Response> {
+        // ? The session may have been revoked concurrently, either way the user is logged out
+        // ? Only browser sessions reach this endpoint, access tokens are revoked instead
+        if let Some(session_id) = session.session_id() {
+            uchat_query::session::delete(&mut conn, session_id)?;
+        }
+
+        info!(
+            user_id = session.user_id.into_inner().to_string(),
+            "user logged out"
+        );
+
+        Ok((StatusCode::OK, session::removal_cookies(), Json(LogoutOk)))
+    }
+}
+
+#[async_trait]
+impl AuthorizedApiRequest for LogoutAll {
+    type Response = (StatusCode, SessionCookies, Json<LogoutAllOk>);
+
+    async fn process_request(
+        self,
+        DbConnection(mut conn): DbConnection,
+        session: UserSession,
+        _state: AppState,
+    ) -> ApiResult<Self::Response> {
+        let sessions_revoked = uchat_query::session::revoke_all(&mut conn, session.user_id)?;
+
+        info!(
+            user_id = session.user_id.into_inner().to_string(),
+            sessions_revoked, "user logged out of all devices"
+        );
 
         Ok((
             StatusCode::OK,
-            Json(LoginOk {
-                session_id: session.id,
-                session_expires: Utc::now() + duration,
-                session_signature: signature.0,
-                display_name: user.display_name,
-                email: user.email,
-                profile_image: profile_image_url,
-                user_id: user.id,
+            session::removal_cookies(),
+            Json(LogoutAllOk { sessions_revoked }),
+        ))
+    }
+}
+
+#[async_trait]
+impl AuthorizedApiRequest for ActiveSessions {
+    type Response = (StatusCode, Json<ActiveSessionsOk>);
+
+    async fn process_request(
+        self,
+        DbConnection(mut conn): DbConnection,
+        session: UserSession,
+        _state: AppState,
+    ) -> ApiResult<Self::Response> {
+        let sessions = uchat_query::session::get_active(&mut conn, session.user_id)?
+            .into_iter()
+            .map(|active| ActiveSession {
+                id: active.id,
+                user_agent: active.fingerprint.user_agent().map(str::to_owned),
+                ip_address: active.fingerprint.ip_address().map(str::to_owned),
+                created_at: active.created_at,
+                last_seen_at: active.last_seen_at,
+                expires_at: active.expires_at,
+                is_current: session.session_id() == Some(active.id),
+            })
+            .collect();
+
+        Ok((StatusCode::OK, Json(ActiveSessionsOk { sessions })))
+    }
+}
+
+#[async_trait]
+impl AuthorizedApiRequest for RevokeSession {
+    type Response = (StatusCode, Json<RevokeSessionOk>);
+
+    async fn process_request(
+        self,
+        DbConnection(mut conn): DbConnection,
+        session: UserSession,
+        _state: AppState,
+    ) -> ApiResult<Self::Response> {
+        match uchat_query::session::revoke(&mut conn, session.user_id, self.session_id)? {
+            DeleteStatus::Deleted => {
+                info!(
+                    user_id = session.user_id.into_inner().to_string(),
+                    "user revoked a session"
+                );
+                Ok((StatusCode::OK, Json(RevokeSessionOk)))
+            }
+            DeleteStatus::NotFound => Err(ApiErr::new(StatusCode::NOT_FOUND, "session not found")),
+        }
+    }
+}
+
+// NOTE The prefix makes leaked tokens easy to recognize, such as by secret scanners
+const ACCESS_TOKEN_PREFIX: &str = "uchat_pat_";
+
+fn to_public_access_token(token: uchat_query::access_token::AccessToken) -> PublicAccessToken {
+    PublicAccessToken {
+        id: token.id,
+        scopes: token.scopes(),
+        name: token.name,
+        created_at: token.created_at,
+        last_used_at: token.last_used_at,
+    }
+}
+
+#[async_trait]
+impl AuthorizedApiRequest for CreateAccessToken {
+    type Response = (StatusCode, Json<CreateAccessTokenOk>);
+
+    async fn process_request(
+        self,
+        DbConnection(mut conn): DbConnection,
+        session: UserSession,
+        _state: AppState,
+    ) -> ApiResult<Self::Response> {
+        if self.scopes.is_empty() {
+            return Err(ApiErr::new(
+                StatusCode::BAD_REQUEST,
+                "an access token needs at least one scope",
+            ));
+        }
+        let mut scopes = self.scopes;
+        scopes.sort_by_key(|scope| scope.as_str());
+        scopes.dedup();
+
+        // ? A fresh rng, since the one in the state is cloned on every use
+        let mut rng = uchat_crypto::new_rng();
+        let secret = format!(
+            "{ACCESS_TOKEN_PREFIX}{}",
+            uchat_crypto::token::new_secret(&mut rng)
+        );
+        let token_hash = uchat_crypto::token::hash_secret(&secret);
+
+        let token = uchat_query::access_token::new(
+            &mut conn,
+            session.user_id,
+            self.name.as_ref(),
+            &token_hash,
+            &scopes,
+        )?;
+
+        info!(
+            user_id = session.user_id.into_inner().to_string(),
+            "user created an access token"
+        );
+
+        Ok((
+            StatusCode::CREATED,
+            Json(CreateAccessTokenOk {
+                token: to_public_access_token(token),
+                secret,
             }),
         ))
     }
 }
 
+#[async_trait]
+impl AuthorizedApiRequest for AccessTokens {
+    type Response = (StatusCode, Json<AccessTokensOk>);
+
+    async fn process_request(
+        self,
+        DbConnection(mut conn): DbConnection,
+        session: UserSession,
+        _state: AppState,
+    ) -> ApiResult<Self::Response> {
+        let tokens = uchat_query::access_token::get_all(&mut conn, session.user_id)?
+            .into_iter()
+            .map(to_public_access_token)
+            .collect();
+
+        Ok((StatusCode::OK, Json(AccessTokensOk { tokens })))
+    }
+}
+
+#[async_trait]
+impl AuthorizedApiRequest for RevokeAccessToken {
+    type Response = (StatusCode, Json<RevokeAccessTokenOk>);
+
+    async fn process_request(
+        self,
+        DbConnection(mut conn): DbConnection,
+        session: UserSession,
+        _state: AppState,
+    ) -> ApiResult<Self::Response> {
+        match uchat_query::access_token::revoke(&mut conn, session.user_id, self.id)? {
+            DeleteStatus::Deleted => {
+                info!(
+                    user_id = session.user_id.into_inner().to_string(),
+                    "user revoked an access token"
+                );
+                Ok((StatusCode::OK, Json(RevokeAccessTokenOk)))
+            }
+            DeleteStatus::NotFound => {
+                Err(ApiErr::new(StatusCode::NOT_FOUND, "access token not found"))
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl AuthorizedApiRequest for SendEmailConfirmation {
+    type Response = (StatusCode, Json<SendEmailConfirmationOk>);
+
+    async fn process_request(
+        self,
+        DbConnection(mut conn): DbConnection,
+        session: UserSession,
+        state: AppState,
+    ) -> ApiResult<Self::Response> {
+        let user = uchat_query::user::get(&mut conn, session.user_id)?;
+
+        let Some(email) = user.email else {
+            return Err(ApiErr::new(
+                StatusCode::BAD_REQUEST,
+                "there is no email to confirm",
+            ));
+        };
+        if user.email_confirmed.is_some() {
+            return Err(ApiErr::new(
+                StatusCode::CONFLICT,
+                "email is already confirmed",
+            ));
+        }
+
+        send_email_confirmation(&state, user.id, &email).await?;
+
+        Ok((StatusCode::OK, Json(SendEmailConfirmationOk)))
+    }
+}
+
+#[async_trait]
+impl PublicApiRequest for ConfirmEmail {
+    type Response = (StatusCode, Json<ConfirmEmailOk>);
+
+    async fn process_request(
+        self,
+        DbConnection(mut conn): DbConnection,
+        _client: ClientInfo,
+        state: AppState,
+    ) -> ApiResult<Self::Response> {
+        let claims = token::verify(&state, &self.token)?;
+        let TokenPurpose::ConfirmEmail { email } = claims.purpose else {
+            return Err(token::invalid_token());
+        };
+
+        // ? The link stops working once the user changes their email again
+        if !uchat_query::user::confirm_email(&mut conn, claims.user_id, &email)? {
+            return Err(ApiErr::new(
+                StatusCode::BAD_REQUEST,
+                "this email is no longer linked to the account",
+            ));
+        }
+
+        info!(
+            user_id = claims.user_id.into_inner().to_string(),
+            "email confirmed"
+        );
+
+        Ok((StatusCode::OK, Json(ConfirmEmailOk { email })))
+    }
+}
+
+#[async_trait]
+impl PublicApiRequest for RequestPasswordReset {
+    type Response = (StatusCode, Json<RequestPasswordResetOk>);
+
+    async fn process_request(
+        self,
+        DbConnection(mut conn): DbConnection,
+        client: ClientInfo,
+        state: AppState,
+    ) -> ApiResult<Self::Response> {
+        let email = self.email.into_inner();
+
+        // ? Counted whether or not the email has an account, so the limit doesn't tell either
+        let rate_limit_keys = RateLimitKey::password_reset(&client, &email);
+        state.rate_limiter.reserve(&rate_limit_keys)?;
+
+        // ? Always succeeds right away, so neither the response nor how long it takes tells which
+        // emails have an account
+        tokio::spawn(async move {
+            let resets = new_password_resets(&state, &mut conn, &email);
+            // ! Back to the pool before waiting on the mail server, which can take a while
+            drop(conn);
+
+            let resets = match resets {
+                Ok(resets) => resets,
+                Err(e) => {
+                    error!(err = %e.err, "failed to save password resets");
+                    return;
+                }
+            };
+            for (handle, link) in resets {
+                if let Err(e) = send_password_reset(&state, &email, &handle, &link).await {
+                    error!(err = %e.err, "failed to send password reset");
+                }
+            }
+        });
+
+        Ok((StatusCode::OK, Json(RequestPasswordResetOk)))
+    }
+}
+
+#[async_trait]
+impl PublicApiRequest for ResetPassword {
+    type Response = (StatusCode, Json<ResetPasswordOk>);
+
+    async fn process_request(
+        self,
+        DbConnection(mut conn): DbConnection,
+        client: ClientInfo,
+        state: AppState,
+    ) -> ApiResult<Self::Response> {
+        let invalid_token = || ApiErr::new(StatusCode::BAD_REQUEST, "invalid or expired token");
+
+        let rate_limit_keys = RateLimitKey::reset_password(&client);
+        state.rate_limiter.reserve(&rate_limit_keys)?;
+
+        // ? Checked before the new password is hashed, so guessing tokens doesn't cost a hash each
+        let token_hash = uchat_crypto::token::hash_secret(&self.token);
+        if uchat_query::password_reset::find(&mut conn, &token_hash)?.is_none() {
+            return Err(invalid_token());
+        }
+
+        let password_hash = uchat_crypto::hash_password(&self.password)?;
+        let user_id =
+            uchat_query::password_reset::reset_password(&mut conn, &token_hash, password_hash)?
+                .ok_or_else(invalid_token)?;
+        state.rate_limiter.release(&rate_limit_keys);
+
+        info!(user_id = user_id.into_inner().to_string(), "password reset");
+        Ok((StatusCode::OK, Json(ResetPasswordOk)))
+    }
+}
+
+#[async_trait]
+impl AuthorizedApiRequest for EnrollTotp {
+    type Response = (StatusCode, Json<EnrollTotpOk>);
+
+    async fn process_request(
+        self,
+        DbConnection(mut conn): DbConnection,
+        session: UserSession,
+        _state: AppState,
+    ) -> ApiResult<Self::Response> {
+        let user = uchat_query::user::get(&mut conn, session.user_id)?;
+
+        let mut rng = uchat_crypto::new_rng();
+        let secret = uchat_crypto::totp::TotpSecret::generate(&mut rng);
+        let encoded_secret = secret.to_base32();
+
+        if !uchat_query::totp::set_pending(&mut conn, user.id, &encoded_secret)? {
+            return Err(ApiErr::new(
+                StatusCode::CONFLICT,
+                "two-factor authentication is already enabled",
+            ));
+        }
+
+        Ok((
+            StatusCode::OK,
+            Json(EnrollTotpOk {
+                provisioning_uri: secret.provisioning_uri(TOTP_ISSUER, &user.handle),
+                secret: encoded_secret,
+            }),
+        ))
+    }
+}
+
+#[async_trait]
+impl AuthorizedApiRequest for ConfirmTotp {
+    type Response = (StatusCode, Json<ConfirmTotpOk>);
+
+    async fn process_request(
+        self,
+        DbConnection(mut conn): DbConnection,
+        session: UserSession,
+        _state: AppState,
+    ) -> ApiResult<Self::Response> {
+        let pending = uchat_query::totp::get(&mut conn, session.user_id)?
+            .filter(|totp| !totp.is_enabled())
+            .ok_or_else(|| {
+                ApiErr::new(
+                    StatusCode::BAD_REQUEST,
+                    "there is no authenticator app waiting to be confirmed",
+                )
+            })?;
+
+        let secret = uchat_crypto::totp::TotpSecret::from_base32(&pending.secret)?;
+        let step = secret
+            .verify(self.code.trim(), Utc::now().timestamp() as u64)
+            .ok_or_else(|| ApiErr::new(StatusCode::BAD_REQUEST, "invalid code"))?;
+
+        let mut rng = uchat_crypto::new_rng();
+        let recovery_codes =
+            uchat_crypto::totp::generate_recovery_codes(&mut rng, RECOVERY_CODE_COUNT);
+        let recovery_code_hashes = recovery_codes
+            .iter()
+            .map(|code| uchat_crypto::hash_password(code).map(|hash| hash.to_string()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if !uchat_query::totp::confirm(
+            &mut conn,
+            session.user_id,
+            step as i64,
+            &recovery_code_hashes,
+        )? {
+            return Err(ApiErr::new(
+                StatusCode::CONFLICT,
+                "two-factor authentication is already enabled",
+            ));
+        }
+
+        info!(
+            user_id = session.user_id.into_inner().to_string(),
+            "two-factor authentication enabled"
+        );
+
+        Ok((StatusCode::OK, Json(ConfirmTotpOk { recovery_codes })))
+    }
+}
+
+#[async_trait]
+impl AuthorizedApiRequest for DisableTotp {
+    type Response = (StatusCode, Json<DisableTotpOk>);
+
+    async fn process_request(
+        self,
+        DbConnection(mut conn): DbConnection,
+        session: UserSession,
+        _state: AppState,
+    ) -> ApiResult<Self::Response> {
+        if !verify_second_factor(&mut conn, session.user_id, &self.code)? {
+            return Err(ApiErr::new(StatusCode::BAD_REQUEST, "invalid code"));
+        }
+
+        uchat_query::totp::delete(&mut conn, session.user_id)?;
+
+        info!(
+            user_id = session.user_id.into_inner().to_string(),
+            "two-factor authentication disabled"
+        );
+
+        Ok((StatusCode::OK, Json(DisableTotpOk)))
+    }
+}
+
 #[async_trait]
 impl AuthorizedApiRequest for GetMyProfile {
     type Response = (StatusCode, Json<GetMyProfileOk>);
+    const SCOPE: Option<Scope> = Some(Scope::Read);
 
     async fn process_request(
         self,
@@ -167,6 +841,8 @@ impl AuthorizedApiRequest for GetMyProfile {
             Json(GetMyProfileOk {
                 display_name: user.display_name,
                 email: user.email,
+                email_confirmed: user.email_confirmed.is_some(),
+                totp_enabled: uchat_query::totp::is_enabled(&mut conn, user.id)?,
                 profile_image: profile_image_url,
                 user_id: user.id,
             }),
@@ -182,8 +858,23 @@ impl AuthorizedApiRequest for UpdateProfile {
         self,
         DbConnection(mut conn): DbConnection,
         session: UserSession,
-        _state: AppState,
+        state: AppState,
     ) -> ApiResult<Self::Response> {
+        let current_email = uchat_query::user::get(&mut conn, session.user_id)?.email;
+        // ? The form always sends the email, an unchanged email must keep its confirmation
+        let email = match self.email {
+            Update::Change(email) if current_email.as_deref() == Some(email.as_ref()) => {
+                Update::NoChange
+            }
+            Update::Change(email) => Update::Change(email.into_inner()),
+            Update::NoChange => Update::NoChange,
+            Update::SetNull => Update::SetNull,
+        };
+        let new_email = match email {
+            Update::Change(ref email) => Some(email.clone()),
+            Update::NoChange | Update::SetNull => None,
+        };
+
         let password = {
             if let Update::Change(ref password) = self.password {
                 Update::Change(uchat_crypto::hash_password(password)?)
@@ -194,7 +885,7 @@ impl AuthorizedApiRequest for UpdateProfile {
 
         let profile_image = if let Update::Change(ref img) = self.profile_image {
             let id = ImageId::new();
-            save_image(id, img).await?;
+            save_image(&mut conn, state.blobs.as_ref(), session.user_id, id, img).await?;
             Update::Change(id.to_string())
         } else {
             self.profile_image
@@ -203,13 +894,20 @@ impl AuthorizedApiRequest for UpdateProfile {
         let query_params = UpdateProfileParams {
             id: session.user_id,
             display_name: self.display_name,
-            email: self.email,
+            email,
             password_hash: password,
             profile_image,
         };
 
         uchat_query::user::update_profile(&mut conn, query_params)?;
 
+        if let Some(email) = new_email {
+            // ? The profile is already updated, the user can ask for another confirmation link
+            if let Err(e) = send_email_confirmation(&state, session.user_id, &email).await {
+                error!(err = %e.err, "failed to send email confirmation");
+            }
+        }
+
         let profile_image_url = {
             let user = uchat_query::user::get(&mut conn, session.user_id)?;
             user.profile_image.as_ref().map(|id| profile_id_to_url(id))
@@ -227,6 +925,7 @@ impl AuthorizedApiRequest for UpdateProfile {
 #[async_trait]
 impl AuthorizedApiRequest for IsFollowing {
     type Response = (StatusCode, Json<IsFollowingOk>);
+    const SCOPE: Option<Scope> = Some(Scope::Read);
 
     async fn process_request(
         self,
@@ -244,6 +943,7 @@ impl AuthorizedApiRequest for IsFollowing {
 #[async_trait]
 impl AuthorizedApiRequest for FollowUser {
     type Response = (StatusCode, Json<FollowUserOk>);
+    const SCOPE: Option<Scope> = Some(Scope::Follow);
 
     async fn process_request(
         self,
@@ -276,6 +976,7 @@ impl AuthorizedApiRequest for FollowUser {
 #[async_trait]
 impl AuthorizedApiRequest for ViewProfile {
     type Response = (StatusCode, Json<ViewProfileOk>);
+    const SCOPE: Option<Scope> = Some(Scope::Read);
 
     async fn process_request(
         self,
@@ -285,9 +986,14 @@ impl AuthorizedApiRequest for ViewProfile {
     ) -> ApiResult<Self::Response> {
         let user_id = self.user_id;
         let profile_user = uchat_query::user::get_profile(&mut conn, user_id)?;
-        let profile_posts = {
-            let posts = uchat_query::post::get_public_posts(&mut conn, user_id)?;
-            super::post::many_to_public(&mut conn, posts, Some(&session))
+        let cursor = super::post::decode_cursor(self.cursor)?;
+        let (profile_posts, next_cursor) = {
+            let posts = uchat_query::post::get_public_posts(&mut conn, user_id, cursor)?;
+            let next_cursor = super::post::next_cursor(&posts);
+            (
+                super::post::many_to_public(&mut conn, posts, Some(&session))?,
+                next_cursor,
+            )
         };
         let is_following = uchat_query::user::is_following(&mut conn, session.user_id, user_id)?;
 
@@ -308,6 +1014,7 @@ impl AuthorizedApiRequest for ViewProfile {
                     am_following: is_following,
                 },
                 posts: profile_posts,
+                next_cursor,
             }),
         ))
     }
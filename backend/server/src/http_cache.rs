@@ -0,0 +1,168 @@
+use std::ops::Range;
+
+use chrono::{DateTime, Utc};
+use hyper::{header, HeaderMap};
+
+/// For content that never changes once saved, such as images, which get a new id when replaced
+pub const IMMUTABLE: &str = "public, max-age=31536000, immutable";
+
+/// A strong validator for content stored under `key`, which is enough since it never changes
+pub fn etag(key: &str) -> String {
+    format!("\"{key}\"")
+}
+
+/// Formats a date the way HTTP headers expect, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`
+pub fn http_date(date: DateTime<Utc>) -> String {
+    date.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+fn parse_http_date(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(value)
+        .ok()
+        .map(|date| date.with_timezone(&Utc))
+}
+
+fn header_str(headers: &HeaderMap, name: header::HeaderName) -> Option<&str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+/// Whether the copy the client already has is current, so a `304 Not Modified` can be sent
+///
+/// NOTE `If-Modified-Since` is only used when there's no `If-None-Match`, as RFC 9110 requires
+pub fn is_fresh(headers: &HeaderMap, etag: &str, last_modified: Option<DateTime<Utc>>) -> bool {
+    if let Some(if_none_match) = header_str(headers, header::IF_NONE_MATCH) {
+        // ? Weak comparison, a `W/` prefix doesn't stop the tags from matching
+        return if_none_match
+            .split(',')
+            .map(|tag| tag.trim().trim_start_matches("W/"))
+            .any(|tag| tag == "*" || tag == etag);
+    }
+
+    match (
+        last_modified,
+        header_str(headers, header::IF_MODIFIED_SINCE).and_then(parse_http_date),
+    ) {
+        // ? HTTP dates don't have fractions of a second
+        (Some(last_modified), Some(since)) => last_modified.timestamp() <= since.timestamp(),
+        _ => false,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ByteRange {
+    /// no usable range was requested, so all of the content is sent
+    Full,
+    Partial(Range<usize>),
+    /// the range starts past the end of the content
+    Unsatisfiable,
+}
+
+/// Reads the `Range` header for content of `len` bytes
+///
+/// NOTE Requests for several ranges get all of the content, which RFC 9110 allows
+pub fn byte_range(headers: &HeaderMap, etag: &str, len: usize) -> ByteRange {
+    let Some(range) = header_str(headers, header::RANGE) else {
+        return ByteRange::Full;
+    };
+
+    // ? The range is only for the version the client has, otherwise it needs everything
+    if let Some(if_range) = header_str(headers, header::IF_RANGE) {
+        if if_range.trim() != etag {
+            return ByteRange::Full;
+        }
+    }
+
+    let Some(spec) = range.trim().strip_prefix("bytes=") else {
+        return ByteRange::Full;
+    };
+    if spec.contains(',') {
+        return ByteRange::Full;
+    }
+    let Some((start, end)) = spec.trim().split_once('-') else {
+        return ByteRange::Full;
+    };
+
+    let range = match (start.parse::<usize>(), end.parse::<usize>()) {
+        // ? `bytes=-500` is the last 500 bytes
+        (Err(_), Ok(suffix)) if start.is_empty() => {
+            if suffix == 0 {
+                return ByteRange::Unsatisfiable;
+            }
+            len.saturating_sub(suffix)..len
+        }
+        (Ok(start), Err(_)) if end.is_empty() => start..len,
+        (Ok(start), Ok(end)) if start <= end => start..(end + 1).min(len),
+        _ => return ByteRange::Full,
+    };
+
+    if range.start >= len {
+        ByteRange::Unsatisfiable
+    } else {
+        ByteRange::Partial(range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use hyper::header::HeaderValue;
+
+    use super::*;
+
+    fn headers(pairs: &[(header::HeaderName, &str)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.clone(), HeaderValue::from_str(value).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn fresh_when_the_client_has_the_current_version() {
+        let etag = etag("image");
+        let saved_at = Utc.with_ymd_and_hms(2023, 9, 18, 12, 0, 0).unwrap();
+
+        assert!(!is_fresh(&HeaderMap::new(), &etag, Some(saved_at)));
+        assert!(is_fresh(
+            &headers(&[(header::IF_NONE_MATCH, "\"other\", W/\"image\"")]),
+            &etag,
+            None
+        ));
+        assert!(!is_fresh(
+            &headers(&[(header::IF_NONE_MATCH, "\"other\"")]),
+            &etag,
+            Some(saved_at)
+        ));
+        assert!(is_fresh(
+            &headers(&[(header::IF_MODIFIED_SINCE, &http_date(saved_at))]),
+            &etag,
+            Some(saved_at)
+        ));
+        assert!(!is_fresh(
+            &headers(&[(header::IF_MODIFIED_SINCE, "Sun, 17 Sep 2023 12:00:00 GMT")]),
+            &etag,
+            Some(saved_at)
+        ));
+    }
+
+    #[test]
+    fn reads_byte_ranges() {
+        let etag = etag("image");
+        let range = |value: &str| byte_range(&headers(&[(header::RANGE, value)]), &etag, 100);
+
+        assert_eq!(byte_range(&HeaderMap::new(), &etag, 100), ByteRange::Full);
+        assert_eq!(range("bytes=0-9"), ByteRange::Partial(0..10));
+        assert_eq!(range("bytes=90-"), ByteRange::Partial(90..100));
+        assert_eq!(range("bytes=-10"), ByteRange::Partial(90..100));
+        assert_eq!(range("bytes=50-500"), ByteRange::Partial(50..100));
+        assert_eq!(range("bytes=100-"), ByteRange::Unsatisfiable);
+        assert_eq!(range("bytes=0-1, 5-6"), ByteRange::Full);
+        assert_eq!(range("items=0-1"), ByteRange::Full);
+        assert_eq!(range("bytes=9-0"), ByteRange::Full);
+
+        let stale = headers(&[
+            (header::RANGE, "bytes=0-9"),
+            (header::IF_RANGE, "\"other\""),
+        ]);
+        assert_eq!(byte_range(&stale, &etag, 100), ByteRange::Full);
+    }
+}
@@ -0,0 +1,419 @@
+use std::io::Cursor;
+
+use hyper::StatusCode;
+use image::{imageops::FilterType, DynamicImage, ImageOutputFormat};
+use uchat_domain::ids::ImageId;
+use uchat_endpoint::app_url::user_content::ImageSize;
+
+use crate::error::ApiErr;
+
+// NOTE Uploads are also capped by the request body limit, this applies to the decoded image
+pub const MAX_IMAGE_BYTES: usize = 5 * 1024 * 1024;
+pub const MAX_IMAGE_DIMENSION: u32 = 4096;
+
+/// The image types that can be uploaded, anything else is rejected
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageType {
+    Png,
+    Jpeg,
+    Gif,
+    Webp,
+}
+
+impl ImageType {
+    /// Reads the type from the image's magic bytes, whatever type the client claimed is ignored
+    pub fn sniff(data: &[u8]) -> Option<Self> {
+        use image::ImageFormat;
+
+        match image::guess_format(data).ok()? {
+            ImageFormat::Png => Some(Self::Png),
+            ImageFormat::Jpeg => Some(Self::Jpeg),
+            ImageFormat::Gif => Some(Self::Gif),
+            ImageFormat::WebP => Some(Self::Webp),
+            _ => None,
+        }
+    }
+
+    pub fn from_mime_type(mime_type: &str) -> Option<Self> {
+        match mime_type {
+            "image/png" => Some(Self::Png),
+            "image/jpeg" => Some(Self::Jpeg),
+            "image/gif" => Some(Self::Gif),
+            "image/webp" => Some(Self::Webp),
+            _ => None,
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            Self::Png => "image/png",
+            Self::Jpeg => "image/jpeg",
+            Self::Gif => "image/gif",
+            Self::Webp => "image/webp",
+        }
+    }
+
+    /// The type the downscaled variants are encoded in
+    ///
+    /// NOTE Only the first frame of a GIF is kept when it's resized, so it's stored as a PNG
+    pub fn variant_type(&self) -> Self {
+        match self {
+            Self::Gif => Self::Png,
+            other => *other,
+        }
+    }
+
+    fn format(&self) -> image::ImageFormat {
+        match self {
+            Self::Png => image::ImageFormat::Png,
+            Self::Jpeg => image::ImageFormat::Jpeg,
+            Self::Gif => image::ImageFormat::Gif,
+            Self::Webp => image::ImageFormat::WebP,
+        }
+    }
+}
+
+/// An uploaded image that passed every check, ready to be stored
+#[derive(Clone, Debug)]
+pub struct ValidImage {
+    pub data: Vec<u8>,
+    pub image_type: ImageType,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl ValidImage {
+    pub fn from_data_url(raw: &str) -> Result<Self, ApiErr> {
+        Self::from_bytes(decode_data_url(raw)?)
+    }
+
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, ApiErr> {
+        if data.len() > MAX_IMAGE_BYTES {
+            return Err(too_large());
+        }
+
+        let image_type = ImageType::sniff(&data).ok_or_else(|| {
+            ApiErr::new(
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "only PNG, JPEG, GIF and WebP images are supported",
+            )
+        })?;
+
+        // ? Only the header is read, so oversized images are rejected without being decoded
+        let (width, height) =
+            image::io::Reader::with_format(Cursor::new(&data), image_type.format())
+                .into_dimensions()
+                .map_err(|_| corrupt())?;
+        if width == 0 || height == 0 {
+            return Err(ApiErr::new(StatusCode::BAD_REQUEST, "the image is empty"));
+        }
+        if width > MAX_IMAGE_DIMENSION || height > MAX_IMAGE_DIMENSION {
+            return Err(ApiErr::new(
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "images must be at most {MAX_IMAGE_DIMENSION}x{MAX_IMAGE_DIMENSION} pixels"
+                ),
+            ));
+        }
+
+        Ok(Self {
+            data,
+            image_type,
+            width,
+            height,
+        })
+    }
+}
+
+/// An image encoded in one of the sizes it's served in
+#[derive(Clone, Debug)]
+pub struct EncodedImage {
+    pub size: ImageSize,
+    pub data: Vec<u8>,
+    pub image_type: ImageType,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl ValidImage {
+    /// Decodes the image once, then encodes it in full and in every smaller size it needs
+    ///
+    /// NOTE Re-encoding drops the EXIF metadata, so the orientation it held is applied first.
+    /// This takes a while for large images, so it should run on a blocking thread
+    pub fn encode_sizes(self) -> Result<Vec<EncodedImage>, ApiErr> {
+        let decoded = image::load_from_memory_with_format(&self.data, self.image_type.format())
+            .map_err(|_| corrupt())?;
+        let decoded = orient(decoded, exif_orientation(&self.data));
+        let (width, height) = (decoded.width(), decoded.height());
+
+        let mut encoded = vec![];
+        for size in ImageSize::VARIANTS {
+            let Some((variant_width, variant_height)) = variant_dimensions(size, width, height)
+            else {
+                continue;
+            };
+            let image_type = self.image_type.variant_type();
+            let resized = decoded.resize_exact(variant_width, variant_height, FilterType::Lanczos3);
+            encoded.push(EncodedImage {
+                size,
+                data: encode(&resized, image_type)?,
+                image_type,
+                width: variant_width,
+                height: variant_height,
+            });
+        }
+
+        // ? Re-encoding a GIF would lose its animation, and GIFs don't carry EXIF data
+        let data = match self.image_type {
+            ImageType::Gif => self.data,
+            image_type => encode(&decoded, image_type)?,
+        };
+        encoded.push(EncodedImage {
+            size: ImageSize::Full,
+            data,
+            image_type: self.image_type,
+            width,
+            height,
+        });
+
+        Ok(encoded)
+    }
+}
+
+/// The dimensions of a downscaled variant, `None` when the image is small enough to be served
+/// in full instead
+pub fn variant_dimensions(size: ImageSize, width: u32, height: u32) -> Option<(u32, u32)> {
+    let max = size.max_dimension()?;
+    if width.max(height) <= max {
+        return None;
+    }
+
+    // ? Rounded to the nearest pixel, without letting a thin image shrink to nothing
+    let scale = |side: u32, longest: u32| {
+        let scaled =
+            (u64::from(side) * u64::from(max) + u64::from(longest) / 2) / u64::from(longest);
+        scaled.max(1) as u32
+    };
+    if width >= height {
+        Some((max, scale(height, width)))
+    } else {
+        Some((scale(width, height), max))
+    }
+}
+
+/// The storage key of an image in one of its sizes, the full size keeps the image's id
+pub fn storage_key(id: ImageId, size: ImageSize) -> String {
+    match size {
+        ImageSize::Full => id.to_string(),
+        size => format!("{id}-{}", size.as_str()),
+    }
+}
+
+fn encode(image: &DynamicImage, image_type: ImageType) -> Result<Vec<u8>, ApiErr> {
+    let format = match image_type {
+        ImageType::Png => ImageOutputFormat::Png,
+        ImageType::Jpeg => ImageOutputFormat::Jpeg(85),
+        ImageType::Gif => ImageOutputFormat::Gif,
+        ImageType::Webp => ImageOutputFormat::WebP,
+    };
+
+    let mut data = Cursor::new(vec![]);
+    image.write_to(&mut data, format)?;
+    Ok(data.into_inner())
+}
+
+/// Reads the EXIF orientation tag, images without one are already upright
+fn exif_orientation(data: &[u8]) -> u32 {
+    exif::Reader::new()
+        .read_from_container(&mut Cursor::new(data))
+        .ok()
+        .and_then(|exif| {
+            exif.get_field(exif::Tag::Orientation, exif::In::PRIMARY)?
+                .value
+                .get_uint(0)
+        })
+        .unwrap_or(1)
+}
+
+// ? The orientations are numbered by how the stored pixels are flipped and rotated
+fn orient(image: DynamicImage, orientation: u32) -> DynamicImage {
+    match orientation {
+        2 => image.fliph(),
+        3 => image.rotate180(),
+        4 => image.flipv(),
+        5 => image.rotate90().fliph(),
+        6 => image.rotate90(),
+        7 => image.rotate270().fliph(),
+        8 => image.rotate270(),
+        _ => image,
+    }
+}
+
+fn corrupt() -> ApiErr {
+    ApiErr::new(StatusCode::BAD_REQUEST, "the image is corrupt")
+}
+
+fn too_large() -> ApiErr {
+    ApiErr::new(
+        StatusCode::PAYLOAD_TOO_LARGE,
+        format!(
+            "images must be at most {} MB",
+            MAX_IMAGE_BYTES / 1024 / 1024
+        ),
+    )
+}
+
+/// Decodes a `data:<mime type>;base64,<data>` url into the bytes it holds
+pub fn decode_data_url(raw: &str) -> Result<Vec<u8>, ApiErr> {
+    use base64::{engine::general_purpose, Engine as _};
+
+    let malformed = || {
+        ApiErr::new(
+            StatusCode::BAD_REQUEST,
+            "images must be sent as a base64 data url",
+        )
+    };
+
+    let (header, data) = raw.split_once(',').ok_or_else(malformed)?;
+    if !header.starts_with("data:") || !header.ends_with(";base64") {
+        return Err(malformed());
+    }
+
+    // ? Checked before decoding, since base64 takes 4 characters for every 3 bytes
+    if data.len() / 4 * 3 > MAX_IMAGE_BYTES + 3 {
+        return Err(too_large());
+    }
+
+    general_purpose::STANDARD
+        .decode(data.trim())
+        .map_err(|_| malformed())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // NOTE The smallest valid PNG, a single transparent pixel
+    const PIXEL_PNG: &str = "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNkYPhfDwAChwGA60e6kgAAAABJRU5ErkJggg==";
+
+    #[test]
+    fn accepts_images_of_the_sniffed_type() {
+        // ? The claimed type is wrong on purpose, the bytes decide
+        let image = ValidImage::from_data_url(&format!("data:image/gif;base64,{PIXEL_PNG}"))
+            .unwrap_or_else(|e| panic!("valid image rejected: {}", e.err));
+
+        assert_eq!(image.image_type, ImageType::Png);
+        assert_eq!((image.width, image.height), (1, 1));
+    }
+
+    #[test]
+    fn encodes_smaller_sizes_only_when_needed() {
+        let image = ValidImage::from_data_url(&format!("data:image/png;base64,{PIXEL_PNG}"))
+            .unwrap_or_else(|e| panic!("valid image rejected: {}", e.err));
+
+        let encoded = image
+            .encode_sizes()
+            .unwrap_or_else(|e| panic!("failed to encode image: {}", e.err));
+
+        assert_eq!(encoded.len(), 1);
+        assert_eq!(encoded[0].size, ImageSize::Full);
+        assert_eq!(ImageType::sniff(&encoded[0].data), Some(ImageType::Png));
+
+        let mut data = Cursor::new(vec![]);
+        DynamicImage::new_rgb8(1000, 500)
+            .write_to(&mut data, ImageOutputFormat::Jpeg(85))
+            .unwrap();
+        let image = ValidImage::from_bytes(data.into_inner())
+            .unwrap_or_else(|e| panic!("valid image rejected: {}", e.err));
+
+        let sizes = image
+            .encode_sizes()
+            .unwrap_or_else(|e| panic!("failed to encode image: {}", e.err))
+            .into_iter()
+            .map(|encoded| {
+                (
+                    encoded.size,
+                    encoded.width,
+                    encoded.height,
+                    encoded.image_type,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sizes,
+            vec![
+                (ImageSize::Avatar, 96, 48, ImageType::Jpeg),
+                (ImageSize::Feed, 640, 320, ImageType::Jpeg),
+                (ImageSize::Full, 1000, 500, ImageType::Jpeg),
+            ]
+        );
+    }
+
+    #[test]
+    fn applies_and_strips_the_exif_orientation() {
+        let mut data = Cursor::new(vec![]);
+        DynamicImage::new_rgb8(100, 50)
+            .write_to(&mut data, ImageOutputFormat::Jpeg(85))
+            .unwrap();
+        let mut data = data.into_inner();
+
+        // ? An APP1 segment holding a single tag, orientation 6 (rotated 90 degrees clockwise)
+        let mut exif = b"Exif\0\0MM\0\x2a\0\0\0\x08".to_vec();
+        exif.extend_from_slice(&[0, 1, 0x01, 0x12, 0, 3, 0, 0, 0, 1, 0, 6, 0, 0, 0, 0, 0, 0]);
+        let mut segment = vec![0xff, 0xe1];
+        segment.extend_from_slice(&(exif.len() as u16 + 2).to_be_bytes());
+        segment.extend_from_slice(&exif);
+        data.splice(2..2, segment);
+        assert_eq!(exif_orientation(&data), 6);
+
+        let image = ValidImage::from_bytes(data)
+            .unwrap_or_else(|e| panic!("valid image rejected: {}", e.err));
+        let full = image
+            .encode_sizes()
+            .unwrap_or_else(|e| panic!("failed to encode image: {}", e.err))
+            .pop()
+            .unwrap();
+
+        assert_eq!((full.width, full.height), (50, 100));
+        assert_eq!(exif_orientation(&full.data), 1);
+    }
+
+    #[test]
+    fn variants_keep_the_aspect_ratio() {
+        assert_eq!(variant_dimensions(ImageSize::Avatar, 96, 96), None);
+        assert_eq!(variant_dimensions(ImageSize::Full, 4000, 3000), None);
+        assert_eq!(
+            variant_dimensions(ImageSize::Feed, 1280, 720),
+            Some((640, 360))
+        );
+        assert_eq!(
+            variant_dimensions(ImageSize::Avatar, 300, 600),
+            Some((48, 96))
+        );
+        assert_eq!(
+            variant_dimensions(ImageSize::Avatar, 4000, 1),
+            Some((96, 1))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_uploads() {
+        let status = |raw: &str| ValidImage::from_data_url(raw).err().and_then(|e| e.code);
+
+        assert_eq!(status("not a data url"), Some(StatusCode::BAD_REQUEST));
+        assert_eq!(
+            status("data:image/png;base64,not base64!"),
+            Some(StatusCode::BAD_REQUEST)
+        );
+        assert_eq!(
+            status("data:image/png;base64,PHN2Zz48L3N2Zz4="),
+            Some(StatusCode::UNSUPPORTED_MEDIA_TYPE)
+        );
+
+        let huge = "A".repeat(MAX_IMAGE_BYTES / 3 * 4 + 8);
+        assert_eq!(
+            status(&format!("data:image/png;base64,{huge}")),
+            Some(StatusCode::PAYLOAD_TOO_LARGE)
+        );
+    }
+}
@@ -0,0 +1,184 @@
+use std::{path::Path, sync::Arc};
+
+use chrono::{DateTime, Utc};
+use color_eyre::{eyre::Context, Help};
+use rand_core::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use uchat_crypto::sign::{Algorithm, KeyId, Keyring, SharedKey, LEGACY_KEY_ID};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StoredKey {
+    pub id: KeyId,
+    // ? Keyrings saved before other algorithms were supported only held RSA keys
+    #[serde(default = "rsa_pss")]
+    pub algorithm: Algorithm,
+    pub private_key: String,
+    pub created_at: DateTime<Utc>,
+}
+
+fn rsa_pss() -> Algorithm {
+    Algorithm::RsaPss
+}
+
+/// The signing keys saved to disk, only the active one signs new sessions and tokens
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KeyringFile {
+    pub active: KeyId,
+    pub keys: Vec<StoredKey>,
+}
+
+impl KeyringFile {
+    pub fn load(path: &Path) -> color_eyre::Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let contents = std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("failed to read keyring at {}", path.display()))?;
+        let keyring = serde_json::from_str(&contents).wrap_err("failed to parse keyring")?;
+        Ok(Some(keyring))
+    }
+
+    /// Replaces the file at `path`, which only its owner can read since it holds private keys
+    pub fn save(&self, path: &Path) -> color_eyre::Result<()> {
+        let contents = serde_json::to_string_pretty(self)?;
+
+        // ? Written beside the keyring and renamed over it, so a failed write leaves the old one intact
+        let tmp_path = path.with_extension("tmp");
+        write_private(&tmp_path, contents.as_bytes())
+            .and_then(|_| std::fs::rename(&tmp_path, path))
+            .wrap_err_with(|| format!("failed to write keyring at {}", path.display()))
+    }
+
+    /// Generates a key and makes it the active one, the previous keys are kept to verify what they signed
+    pub fn add<R>(&mut self, rng: &mut R, algorithm: Algorithm) -> color_eyre::Result<KeyId>
+    where
+        R: CryptoRng + RngCore,
+    {
+        let key = new_stored_key(rng, algorithm)?;
+        let id = key.id.clone();
+
+        self.keys.push(key);
+        self.active = id.clone();
+        Ok(id)
+    }
+
+    pub fn retire(&mut self, id: &KeyId) -> color_eyre::Result<()> {
+        if *id == self.active {
+            return Err(color_eyre::eyre::eyre!("the active key can't be retired"))
+                .suggestion("add a new key first, so it becomes the active one");
+        }
+
+        let total = self.keys.len();
+        self.keys.retain(|key| key.id != *id);
+        if self.keys.len() == total {
+            return Err(color_eyre::eyre::eyre!("key {id} is not in the keyring"));
+        }
+        Ok(())
+    }
+
+    pub fn to_keyring(&self) -> color_eyre::Result<Keyring> {
+        let active = self
+            .keys
+            .iter()
+            .find(|key| key.id == self.active)
+            .ok_or_else(|| color_eyre::eyre::eyre!("active key {} is missing", self.active))?;
+
+        let mut keyring = Keyring::new(active.id.clone(), active.decode()?);
+        for key in self.keys.iter().filter(|key| key.id != self.active) {
+            keyring.insert(key.id.clone(), key.decode()?);
+        }
+        Ok(keyring)
+    }
+}
+
+impl StoredKey {
+    fn decode(&self) -> color_eyre::Result<SharedKey> {
+        uchat_crypto::sign::decode_key(self.algorithm, &self.private_key)
+            .wrap_err_with(|| format!("failed to load key {}", self.id))
+    }
+}
+
+fn new_stored_key<R>(rng: &mut R, algorithm: Algorithm) -> color_eyre::Result<StoredKey>
+where
+    R: CryptoRng + RngCore,
+{
+    let (private_key, _) = uchat_crypto::sign::generate_key(algorithm, rng)?;
+
+    Ok(StoredKey {
+        id: KeyId::generate(rng),
+        algorithm,
+        private_key,
+        created_at: Utc::now(),
+    })
+}
+
+/// Adds a key to the keyring at `path`, creating it when needed
+pub fn add_key<R>(path: &Path, rng: &mut R, algorithm: Algorithm) -> color_eyre::Result<KeyId>
+where
+    R: CryptoRng + RngCore,
+{
+    let mut keyring = match KeyringFile::load(path)? {
+        Some(keyring) => keyring,
+        None => {
+            let key = new_stored_key(rng, algorithm)?;
+            let mut keyring = KeyringFile {
+                active: key.id.clone(),
+                keys: vec![key],
+            };
+
+            // ? Sessions signed with the RSA key from before the keyring existed stay valid
+            if let Ok(private_key) = std::env::var("API_PRIVATE_KEY") {
+                keyring.keys.insert(
+                    0,
+                    StoredKey {
+                        id: KeyId::new(LEGACY_KEY_ID)?,
+                        algorithm: Algorithm::RsaPss,
+                        private_key,
+                        created_at: Utc::now(),
+                    },
+                );
+            }
+
+            keyring.save(path)?;
+            return Ok(keyring.active);
+        }
+    };
+
+    let id = keyring.add(rng, algorithm)?;
+    keyring.save(path)?;
+    Ok(id)
+}
+
+#[cfg(unix)]
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::{io::Write, os::unix::fs::OpenOptionsExt};
+
+    // NOTE The mode only applies to new files, so a leftover temp file is removed first
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+        _ => (),
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)?;
+    file.write_all(contents)?;
+    file.sync_all()
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    std::fs::write(path, contents)
+}
+
+/// Loads the keyring at `path`, falling back to the single key in `API_PRIVATE_KEY`
+pub fn load(path: &Path) -> color_eyre::Result<Keyring> {
+    if let Some(keyring) = KeyringFile::load(path)? {
+        return keyring.to_keyring();
+    }
+
+    let keys = crate::cli::load_keys()?;
+    Ok(Keyring::new(KeyId::new(LEGACY_KEY_ID)?, Arc::new(keys)))
+}
@@ -1,8 +1,20 @@
+pub mod blob;
 pub mod error;
 pub mod extractor;
 pub mod handler;
+pub mod http_cache;
+pub mod image;
+pub mod keyring;
 pub mod logging;
+pub mod mail;
+pub mod periodic;
+pub mod rate_limit;
 pub mod router;
+pub mod session;
+pub mod token;
+pub mod trending;
+
+use std::sync::Arc;
 
 use axum::extract::FromRef;
 use uchat_query::{AsyncConnection, AsyncConnectionPool, QueryError};
@@ -10,8 +22,13 @@ use uchat_query::{AsyncConnection, AsyncConnectionPool, QueryError};
 #[derive(FromRef, Clone)]
 pub struct AppState {
     pub db_pool: AsyncConnectionPool,
-    pub signing_keys: uchat_crypto::sign::Keys,
+    pub signing_keys: uchat_crypto::sign::Keyring,
     pub rng: rand::rngs::StdRng,
+    pub trending: trending::TrendingConfig,
+    pub mailer: mail::Mailer,
+    pub rate_limiter: rate_limit::RateLimiter,
+    pub blobs: Arc<dyn blob::BlobStore>,
+    pub proxy: extractor::ProxyConfig,
 }
 
 impl AppState {
@@ -65,13 +82,26 @@ pub mod tests {
             response::{IntoResponse, Response},
             Router,
         };
-        use hyper::Request;
+        use hyper::{Request, StatusCode};
         use serde::Serialize;
+        use std::sync::Arc;
         use tower::ServiceExt;
-        use uchat_crypto::sign::Keys;
+        use uchat_crypto::sign::{Ed25519Key, KeyId, Keyring};
+        use uchat_domain::{ids::UserId, Password, Username};
+        use uchat_endpoint::{
+            user::endpoint::{CreateUser, CreateUserOk},
+            Endpoint,
+        };
         use uchat_query::AsyncConnectionPool;
 
-        use crate::AppState;
+        use crate::{
+            blob::BlobConfig,
+            extractor::ProxyConfig,
+            mail::{MailConfig, Mailer},
+            rate_limit::RateLimiter,
+            trending::TrendingConfig,
+            AppState,
+        };
 
         pub async fn new_state() -> AppState {
             let connection_url = dotenvy::var("TEST_DATABASE_URL")
@@ -81,8 +111,16 @@ pub mod tests {
 
             AppState {
                 db_pool: AsyncConnectionPool::new(&connection_url).await.unwrap(),
-                signing_keys: Keys::generate(&mut rng).unwrap().1,
+                signing_keys: Keyring::new(
+                    KeyId::generate(&mut rng),
+                    Arc::new(Ed25519Key::generate(&mut rng)),
+                ),
                 rng,
+                trending: TrendingConfig::default(),
+                mailer: Mailer::from_config(&MailConfig::default()),
+                rate_limiter: RateLimiter::default(),
+                blobs: crate::blob::from_config(&BlobConfig::default()).unwrap(),
+                proxy: ProxyConfig::default(),
             }
         }
 
@@ -110,6 +148,85 @@ pub mod tests {
                 .into_response()
         }
 
+        /// Sends a request with the session cookies, and the CSRF token when there is one
+        pub async fn authorized_request_with_router<P>(
+            router: Router,
+            uri: &str,
+            payload: P,
+            cookies: &str,
+            csrf_token: Option<&str>,
+        ) -> Response
+        where
+            P: Serialize,
+        {
+            let payload = serde_json::to_string(&payload).unwrap();
+            let mut request = Request::builder()
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .header(hyper::header::COOKIE, cookies)
+                .uri(uri);
+            if let Some(token) = csrf_token {
+                request = request.header(uchat_endpoint::CSRF_HEADER, token);
+            }
+
+            router
+                .oneshot(request.body(payload.into()).unwrap())
+                .await
+                .unwrap()
+                .into_response()
+        }
+
+        /// Sends a request authenticated with a personal access token
+        pub async fn bearer_request_with_router<P>(
+            router: Router,
+            uri: &str,
+            payload: P,
+            token: &str,
+        ) -> Response
+        where
+            P: Serialize,
+        {
+            let payload = serde_json::to_string(&payload).unwrap();
+            let request = Request::builder()
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .header(hyper::header::AUTHORIZATION, format!("Bearer {token}"))
+                .uri(uri)
+                .body(payload.into())
+                .unwrap();
+
+            router.oneshot(request).await.unwrap().into_response()
+        }
+
+        /// Sends a `GET` request with the given headers, for routes that aren't API endpoints
+        pub async fn get_request_with_router(
+            router: Router,
+            uri: &str,
+            headers: &[(hyper::header::HeaderName, &str)],
+        ) -> Response {
+            let mut request = Request::builder().method("GET").uri(uri);
+            for (name, value) in headers {
+                request = request.header(name, *value);
+            }
+
+            router
+                .oneshot(request.body(hyper::Body::empty()).unwrap())
+                .await
+                .unwrap()
+                .into_response()
+        }
+
+        /// Turns the `Set-Cookie` headers of a response into the `Cookie` header a browser would send
+        pub fn session_cookies(response: &Response) -> String {
+            response
+                .headers()
+                .get_all(hyper::header::SET_COOKIE)
+                .iter()
+                .filter_map(|cookie| cookie.to_str().ok()?.split(';').next())
+                .collect::<Vec<_>>()
+                .join("; ")
+        }
+
         pub async fn api_request<P>(uri: &str, payload: P) -> Response
         where
             P: Serialize,
@@ -117,6 +234,50 @@ pub mod tests {
             let router = new_router().await;
             api_request_with_router(router, uri, payload).await
         }
+
+        // NOTE The smallest valid PNG, a single transparent pixel
+        pub const PIXEL_PNG_DATA_URL: &str = "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNkYPhfDwAChwGA60e6kgAAAABJRU5ErkJggg==";
+
+        pub fn random_username() -> String {
+            use rand::distributions::Alphanumeric;
+            use rand::{thread_rng, Rng};
+
+            thread_rng()
+                .sample_iter(&Alphanumeric)
+                .take(20)
+                .map(char::from)
+                .collect()
+        }
+
+        pub struct SignedUpUser {
+            pub user_id: UserId,
+            pub username: String,
+            /// the `Cookie` header for the session created on sign up
+            pub cookies: String,
+            pub csrf_token: String,
+        }
+
+        /// Creates a user with a random name and the password `password`, keeping its session
+        pub async fn signed_up_user(router: Router) -> SignedUpUser {
+            let username = random_username();
+            let payload = CreateUser {
+                password: Password::new("password").unwrap(),
+                username: Username::new(&username).unwrap(),
+            };
+            let response = api_request_with_router(router, CreateUser::URL, payload).await;
+            assert_eq!(StatusCode::CREATED, response.status());
+
+            let cookies = session_cookies(&response);
+            let response = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            let created: CreateUserOk = serde_json::from_slice(&response).unwrap();
+
+            SignedUpUser {
+                user_id: created.user_id,
+                username,
+                cookies,
+                csrf_token: created.csrf_token,
+            }
+        }
     }
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
@@ -141,6 +302,20 @@ pub mod tests {
 
             assert_eq!(StatusCode::CREATED, response.status());
 
+            let cookies: Vec<_> = response
+                .headers()
+                .get_all(hyper::header::SET_COOKIE)
+                .iter()
+                .map(|cookie| cookie.to_str().unwrap().to_owned())
+                .collect();
+            for name in [uchat_cookie::SESSION_ID, uchat_cookie::SESSION_SIGNATURE] {
+                let cookie = cookies
+                    .iter()
+                    .find(|cookie| cookie.starts_with(&format!("{name}=")))
+                    .expect("session cookie not set");
+                assert!(cookie.contains("HttpOnly"));
+            }
+
             let response = hyper::body::to_bytes(response.into_body()).await?;
             let response: CreateUserOk = serde_json::from_slice(&response)?;
 
@@ -160,4 +335,689 @@ pub mod tests {
 
         Ok(())
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn direct_messages_are_sent_and_listed() -> Result<()> {
+        use uchat_domain::{ids::UserId, post::Message};
+        use uchat_endpoint::{
+            message::endpoint::{
+                Conversations, ConversationsOk, SendDirectMessage, SendDirectMessageOk,
+                ViewConversation, ViewConversationOk,
+            },
+            post::{
+                endpoint::NewPost,
+                types::{Chat, Content, NewPostOptions},
+            },
+        };
+
+        let router = util::new_router().await;
+        let sender = util::signed_up_user(router.clone()).await;
+        let recipient = util::signed_up_user(router.clone()).await;
+
+        let send = |to: UserId| {
+            let payload = SendDirectMessage {
+                to,
+                message: Message::new("hello").unwrap(),
+            };
+            util::authorized_request_with_router(
+                router.clone(),
+                SendDirectMessage::URL,
+                payload,
+                &sender.cookies,
+                Some(&sender.csrf_token),
+            )
+        };
+
+        // * to themselves
+        let response = send(sender.user_id).await;
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+
+        // * to a user that doesn't exist
+        let response = send(UserId::new()).await;
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+
+        let response = send(recipient.user_id).await;
+        assert_eq!(StatusCode::OK, response.status());
+        let response = hyper::body::to_bytes(response.into_body()).await?;
+        let sent = serde_json::from_slice::<SendDirectMessageOk>(&response)?.message;
+        assert_eq!(sent.from, sender.user_id);
+        assert_eq!(sent.to, recipient.user_id);
+
+        // * the recipient sees the conversation and the message in it
+        let response = util::authorized_request_with_router(
+            router.clone(),
+            Conversations::URL,
+            Conversations,
+            &recipient.cookies,
+            Some(&recipient.csrf_token),
+        )
+        .await;
+        assert_eq!(StatusCode::OK, response.status());
+        let response = hyper::body::to_bytes(response.into_body()).await?;
+        let conversations = serde_json::from_slice::<ConversationsOk>(&response)?.conversations;
+        assert_eq!(conversations.len(), 1);
+        assert_eq!(conversations[0].with_user.id, sender.user_id);
+        assert_eq!(conversations[0].last_message.id, sent.id);
+
+        let payload = ViewConversation {
+            with_user: sender.user_id,
+        };
+        let response = util::authorized_request_with_router(
+            router.clone(),
+            ViewConversation::URL,
+            payload,
+            &recipient.cookies,
+            Some(&recipient.csrf_token),
+        )
+        .await;
+        assert_eq!(StatusCode::OK, response.status());
+        let response = hyper::body::to_bytes(response.into_body()).await?;
+        let messages = serde_json::from_slice::<ViewConversationOk>(&response)?.messages;
+        let ids = messages
+            .iter()
+            .map(|message| message.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![sent.id]);
+
+        // * posts can't be messages, nor reply to them
+        for options in [
+            NewPostOptions {
+                direct_message_to: Some(recipient.user_id),
+                ..Default::default()
+            },
+            NewPostOptions {
+                reply_to: Some(sent.id),
+                ..Default::default()
+            },
+        ] {
+            let payload = NewPost {
+                content: Content::Chat(Chat {
+                    headline: None,
+                    message: Message::new("hello").unwrap(),
+                }),
+                options,
+            };
+            let response = util::authorized_request_with_router(
+                router.clone(),
+                NewPost::URL,
+                payload,
+                &sender.cookies,
+                Some(&sender.csrf_token),
+            )
+            .await;
+            assert!(response.status().is_client_error());
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn authorized_requests_need_a_csrf_token() -> Result<()> {
+        use uchat_endpoint::user::endpoint::GetMyProfile;
+
+        let router = util::new_router().await;
+        let util::SignedUpUser {
+            cookies,
+            csrf_token,
+            ..
+        } = util::signed_up_user(router.clone()).await;
+
+        // * without the token
+        let response = util::authorized_request_with_router(
+            router.clone(),
+            GetMyProfile::URL,
+            GetMyProfile,
+            &cookies,
+            None,
+        )
+        .await;
+        assert_eq!(StatusCode::FORBIDDEN, response.status());
+
+        // * with a token that isn't from this session
+        let response = util::authorized_request_with_router(
+            router.clone(),
+            GetMyProfile::URL,
+            GetMyProfile,
+            &cookies,
+            Some("not.a-token"),
+        )
+        .await;
+        assert_eq!(StatusCode::FORBIDDEN, response.status());
+
+        // * with the token issued along with the session
+        let response = util::authorized_request_with_router(
+            router,
+            GetMyProfile::URL,
+            GetMyProfile,
+            &cookies,
+            Some(&csrf_token),
+        )
+        .await;
+        assert_eq!(StatusCode::OK, response.status());
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn access_tokens_are_limited_to_their_scopes() -> Result<()> {
+        use uchat_domain::access_token::{Scope, TokenName};
+        use uchat_endpoint::user::endpoint::{
+            CreateAccessToken, CreateAccessTokenOk, GetMyProfile, Logout, RevokeAccessToken,
+        };
+
+        let router = util::new_router().await;
+        let util::SignedUpUser {
+            cookies,
+            csrf_token,
+            ..
+        } = util::signed_up_user(router.clone()).await;
+
+        let payload = CreateAccessToken {
+            name: TokenName::new("bot")?,
+            scopes: vec![Scope::Read],
+        };
+        let response = util::authorized_request_with_router(
+            router.clone(),
+            CreateAccessToken::URL,
+            payload,
+            &cookies,
+            Some(&csrf_token),
+        )
+        .await;
+        assert_eq!(StatusCode::CREATED, response.status());
+        let response = hyper::body::to_bytes(response.into_body()).await?;
+        let created: CreateAccessTokenOk = serde_json::from_slice(&response)?;
+
+        // * a scope the token has
+        let response = util::bearer_request_with_router(
+            router.clone(),
+            GetMyProfile::URL,
+            GetMyProfile,
+            &created.secret,
+        )
+        .await;
+        assert_eq!(StatusCode::OK, response.status());
+
+        // * an endpoint that's kept to browser sessions
+        let response =
+            util::bearer_request_with_router(router.clone(), Logout::URL, Logout, &created.secret)
+                .await;
+        assert_eq!(StatusCode::FORBIDDEN, response.status());
+
+        // * once revoked
+        let payload = RevokeAccessToken {
+            id: created.token.id,
+        };
+        let response = util::authorized_request_with_router(
+            router.clone(),
+            RevokeAccessToken::URL,
+            payload,
+            &cookies,
+            Some(&csrf_token),
+        )
+        .await;
+        assert_eq!(StatusCode::OK, response.status());
+
+        let response = util::bearer_request_with_router(
+            router,
+            GetMyProfile::URL,
+            GetMyProfile,
+            &created.secret,
+        )
+        .await;
+        assert_eq!(StatusCode::UNAUTHORIZED, response.status());
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn repeated_failed_logins_lock_out_the_account() -> Result<()> {
+        use uchat_endpoint::user::endpoint::Login;
+
+        use crate::rate_limit::RateLimitConfig;
+
+        let router = util::new_router().await;
+        let username = util::signed_up_user(router.clone()).await.username;
+
+        let wrong_login = Login {
+            username: Username::new(&username)?,
+            password: Password::new("wrong password")?,
+        };
+        for _ in 0..RateLimitConfig::default().login_max_failures {
+            let response =
+                util::api_request_with_router(router.clone(), Login::URL, wrong_login.clone())
+                    .await;
+            assert_eq!(StatusCode::UNAUTHORIZED, response.status());
+        }
+
+        // * even the right password is rejected during the lockout
+        let login = Login {
+            username: Username::new(&username)?,
+            password: Password::new("password")?,
+        };
+        let response = util::api_request_with_router(router, Login::URL, login).await;
+        assert_eq!(StatusCode::TOO_MANY_REQUESTS, response.status());
+        assert!(response.headers().contains_key(hyper::header::RETRY_AFTER));
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn images_are_cached_and_served_in_ranges() -> Result<()> {
+        use hyper::header;
+        use uchat_endpoint::post::{
+            endpoint::{NewPost, NewPostOk, ViewPost, ViewPostOk},
+            types::{Content, Image, ImageKind, NewPostOptions},
+        };
+
+        let router = util::new_router().await;
+        let util::SignedUpUser {
+            cookies,
+            csrf_token,
+            ..
+        } = util::signed_up_user(router.clone()).await;
+
+        let payload = NewPost {
+            content: Content::Image(Image {
+                kind: ImageKind::DataUrl(util::PIXEL_PNG_DATA_URL.to_owned()),
+                caption: None,
+            }),
+            options: NewPostOptions::default(),
+        };
+        let response = util::authorized_request_with_router(
+            router.clone(),
+            NewPost::URL,
+            payload,
+            &cookies,
+            Some(&csrf_token),
+        )
+        .await;
+        assert_eq!(StatusCode::OK, response.status());
+        let response = hyper::body::to_bytes(response.into_body()).await?;
+        let post_id = serde_json::from_slice::<NewPostOk>(&response)?.post_id;
+
+        let payload = ViewPost {
+            post_id,
+            cursor: None,
+        };
+        let response = util::authorized_request_with_router(
+            router.clone(),
+            ViewPost::URL,
+            payload,
+            &cookies,
+            Some(&csrf_token),
+        )
+        .await;
+        let response = hyper::body::to_bytes(response.into_body()).await?;
+        let post = serde_json::from_slice::<ViewPostOk>(&response)?.post;
+        let Content::Image(Image {
+            kind: ImageKind::Url(src_set),
+            ..
+        }) = post.content
+        else {
+            panic!("the post should have an image url");
+        };
+        let path = src_set.src.path();
+
+        // * the full image, cacheable forever
+        let response = util::get_request_with_router(router.clone(), path, &[]).await;
+        assert_eq!(StatusCode::OK, response.status());
+        let headers = response.headers().clone();
+        assert!(headers[header::CACHE_CONTROL]
+            .to_str()?
+            .contains("immutable"));
+        let etag = headers[header::ETAG].to_str()?;
+        let image = hyper::body::to_bytes(response.into_body()).await?;
+
+        // * a copy the client already has
+        let response =
+            util::get_request_with_router(router.clone(), path, &[(header::IF_NONE_MATCH, etag)])
+                .await;
+        assert_eq!(StatusCode::NOT_MODIFIED, response.status());
+
+        // * part of the image
+        let response =
+            util::get_request_with_router(router.clone(), path, &[(header::RANGE, "bytes=0-3")])
+                .await;
+        assert_eq!(StatusCode::PARTIAL_CONTENT, response.status());
+        assert_eq!(
+            response.headers()[header::CONTENT_RANGE].to_str()?,
+            format!("bytes 0-3/{}", image.len())
+        );
+        let part = hyper::body::to_bytes(response.into_body()).await?;
+        assert_eq!(part, image.slice(0..4));
+
+        // * someone else's image can't be posted again
+        let other = util::signed_up_user(router.clone()).await;
+        let image_id = path.rsplit('/').next().unwrap().parse::<uuid::Uuid>()?;
+        for kind in [
+            ImageKind::Url(src_set.clone()),
+            ImageKind::Id(image_id.into()),
+        ] {
+            let payload = NewPost {
+                content: Content::Image(Image {
+                    kind,
+                    caption: None,
+                }),
+                options: NewPostOptions::default(),
+            };
+            let response = util::authorized_request_with_router(
+                router.clone(),
+                NewPost::URL,
+                payload,
+                &other.cookies,
+                Some(&other.csrf_token),
+            )
+            .await;
+            assert_eq!(StatusCode::BAD_REQUEST, response.status());
+        }
+
+        // * an image that doesn't exist
+        let missing = format!(
+            "{}{}",
+            path.trim_end_matches(|c| c != '/'),
+            uuid::Uuid::new_v4()
+        );
+        let response = util::get_request_with_router(router.clone(), &missing, &[]).await;
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+
+        // * validators can't make an image that doesn't exist look cached
+        let response =
+            util::get_request_with_router(router, &missing, &[(header::IF_NONE_MATCH, "*")]).await;
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn gallery_images_are_saved_and_removed_with_the_post() -> Result<()> {
+        use uchat_domain::post::AltText;
+        use uchat_endpoint::post::{
+            endpoint::{
+                DeletePost, EditPost, EditPostOk, NewPost, NewPostOk, ViewPost, ViewPostOk,
+            },
+            types::{Content, Gallery, GalleryImage, Image, ImageKind, NewPostOptions},
+        };
+
+        let router = util::new_router().await;
+        let util::SignedUpUser {
+            cookies,
+            csrf_token,
+            ..
+        } = util::signed_up_user(router.clone()).await;
+
+        let gallery = |len: usize| Gallery {
+            images: (0..len)
+                .map(|i| GalleryImage {
+                    kind: ImageKind::DataUrl(util::PIXEL_PNG_DATA_URL.to_owned()),
+                    alt: Some(AltText::new(format!("pixel {i}")).unwrap()),
+                })
+                .collect(),
+        };
+
+        // * too many images
+        let payload = NewPost {
+            content: gallery(5).into(),
+            options: NewPostOptions::default(),
+        };
+        let response = util::authorized_request_with_router(
+            router.clone(),
+            NewPost::URL,
+            payload,
+            &cookies,
+            Some(&csrf_token),
+        )
+        .await;
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+
+        let payload = NewPost {
+            content: gallery(3).into(),
+            options: NewPostOptions::default(),
+        };
+        let response = util::authorized_request_with_router(
+            router.clone(),
+            NewPost::URL,
+            payload,
+            &cookies,
+            Some(&csrf_token),
+        )
+        .await;
+        assert_eq!(StatusCode::OK, response.status());
+        let response = hyper::body::to_bytes(response.into_body()).await?;
+        let post_id = serde_json::from_slice::<NewPostOk>(&response)?.post_id;
+
+        let payload = ViewPost {
+            post_id,
+            cursor: None,
+        };
+        let response = util::authorized_request_with_router(
+            router.clone(),
+            ViewPost::URL,
+            payload,
+            &cookies,
+            Some(&csrf_token),
+        )
+        .await;
+        let response = hyper::body::to_bytes(response.into_body()).await?;
+        let post = serde_json::from_slice::<ViewPostOk>(&response)?.post;
+        let Content::Gallery(Gallery { images }) = post.content else {
+            panic!("the post should be a gallery");
+        };
+        let urls = |images: &[GalleryImage]| {
+            images
+                .iter()
+                .map(|image| match &image.kind {
+                    ImageKind::Url(src_set) => src_set.src.clone(),
+                    _ => panic!("gallery images should have urls"),
+                })
+                .collect::<Vec<_>>()
+        };
+        let original = urls(&images);
+        assert_eq!(original.len(), 3);
+
+        // * images sent back by url are kept in their new order, the missing one by the revision
+        let payload = EditPost {
+            post_id,
+            content: Gallery {
+                images: vec![images[2].clone(), images[0].clone()],
+            }
+            .into(),
+        };
+        let response = util::authorized_request_with_router(
+            router.clone(),
+            EditPost::URL,
+            payload,
+            &cookies,
+            Some(&csrf_token),
+        )
+        .await;
+        assert_eq!(StatusCode::OK, response.status());
+        let response = hyper::body::to_bytes(response.into_body()).await?;
+        let post = serde_json::from_slice::<EditPostOk>(&response)?.post;
+        let Content::Gallery(Gallery { images: edited }) = post.content else {
+            panic!("the post should still be a gallery");
+        };
+        assert_eq!(
+            urls(&edited),
+            vec![original[2].clone(), original[0].clone()]
+        );
+
+        // ? the removed image is still referenced by the revision
+        let response = util::get_request_with_router(router.clone(), original[1].path(), &[]).await;
+        assert_eq!(StatusCode::OK, response.status());
+
+        // * a gallery shrunk back to a single image keeps it by url
+        let payload = EditPost {
+            post_id,
+            content: Content::Image(Image {
+                kind: images[2].kind.clone(),
+                caption: None,
+            }),
+        };
+        let response = util::authorized_request_with_router(
+            router.clone(),
+            EditPost::URL,
+            payload,
+            &cookies,
+            Some(&csrf_token),
+        )
+        .await;
+        assert_eq!(StatusCode::OK, response.status());
+        let response = hyper::body::to_bytes(response.into_body()).await?;
+        let post = serde_json::from_slice::<EditPostOk>(&response)?.post;
+        let Content::Image(Image {
+            kind: ImageKind::Url(src_set),
+            ..
+        }) = post.content
+        else {
+            panic!("the post should be a single image");
+        };
+        assert_eq!(src_set.src, original[2]);
+
+        // * an image that isn't part of the post anymore
+        let payload = EditPost {
+            post_id,
+            content: Gallery {
+                images: vec![images[2].clone(), images[1].clone()],
+            }
+            .into(),
+        };
+        let response = util::authorized_request_with_router(
+            router.clone(),
+            EditPost::URL,
+            payload,
+            &cookies,
+            Some(&csrf_token),
+        )
+        .await;
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+
+        // * every image the post ever had is removed along with it
+        let response = util::authorized_request_with_router(
+            router.clone(),
+            DeletePost::URL,
+            DeletePost { post_id },
+            &cookies,
+            Some(&csrf_token),
+        )
+        .await;
+        assert_eq!(StatusCode::OK, response.status());
+        for url in &original {
+            let response = util::get_request_with_router(router.clone(), url.path(), &[]).await;
+            assert_eq!(StatusCode::NOT_FOUND, response.status());
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn password_reset() -> Result<()> {
+        use std::sync::Arc;
+        use uchat_endpoint::{
+            user::endpoint::{Login, RequestPasswordReset, ResetPassword},
+            Update,
+        };
+
+        use crate::{
+            mail::{FileMailSender, MailConfig, Mailer},
+            rate_limit::RateLimitConfig,
+        };
+
+        // ? Mail is saved to a directory of its own, so the reset link can be read back
+        let mail_dir = std::env::temp_dir().join(util::random_username());
+        let mut state = util::new_state().await;
+        state.mailer = Mailer::new(
+            Arc::new(FileMailSender::new(mail_dir.clone())),
+            MailConfig::default().frontend_url,
+        );
+        let router = crate::router::new_router(state.clone());
+
+        let util::SignedUpUser {
+            user_id, username, ..
+        } = util::signed_up_user(router.clone()).await;
+        let email = format!("{username}@example.com");
+
+        {
+            let mut conn = state.connect().await?;
+            let params = uchat_query::user::UpdateProfileParams {
+                id: user_id,
+                display_name: Update::NoChange,
+                email: Update::Change(email.clone()),
+                password_hash: Update::NoChange,
+                profile_image: Update::NoChange,
+            };
+            uchat_query::user::update_profile(&mut conn, params)?;
+            uchat_query::user::confirm_email(&mut conn, user_id, &email)?;
+        }
+
+        let payload = RequestPasswordReset {
+            email: uchat_domain::user::Email::new(&email)?,
+        };
+        let response =
+            util::api_request_with_router(router.clone(), RequestPasswordReset::URL, payload).await;
+        assert_eq!(StatusCode::OK, response.status());
+
+        // ? The mail is sent in the background, so it's polled for until it's written in full
+        let mut mail = None;
+        for _ in 0..50 {
+            mail = std::fs::read_dir(&mail_dir)
+                .ok()
+                .and_then(|mut entries| entries.next())
+                .and_then(|entry| std::fs::read_to_string(entry.ok()?.path()).ok())
+                .filter(|mail| mail.contains("ignore this email"));
+            if mail.is_some() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+
+        let token = {
+            let mail = mail.expect("the password reset mail should have been sent");
+            let (_, token) = mail.split_once("reset_password/").unwrap();
+            token.split_whitespace().next().unwrap().to_owned()
+        };
+        std::fs::remove_dir_all(&mail_dir)?;
+
+        let reset = ResetPassword {
+            token,
+            password: Password::new("new password")?,
+        };
+        let response =
+            util::api_request_with_router(router.clone(), ResetPassword::URL, reset.clone()).await;
+        assert_eq!(StatusCode::OK, response.status());
+
+        // * tokens can only be used once
+        let response =
+            util::api_request_with_router(router.clone(), ResetPassword::URL, reset).await;
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+
+        let login = Login {
+            username: Username::new(&username)?,
+            password: Password::new("new password")?,
+        };
+        let response = util::api_request_with_router(router.clone(), Login::URL, login).await;
+        assert_eq!(StatusCode::OK, response.status());
+
+        // * resets are limited per email, whether or not it has an account
+        let payload = RequestPasswordReset {
+            email: uchat_domain::user::Email::new(format!(
+                "{}@example.com",
+                util::random_username()
+            ))?,
+        };
+        for _ in 0..RateLimitConfig::default().password_reset_max_attempts {
+            let response = util::api_request_with_router(
+                router.clone(),
+                RequestPasswordReset::URL,
+                payload.clone(),
+            )
+            .await;
+            assert_eq!(StatusCode::OK, response.status());
+        }
+        let response =
+            util::api_request_with_router(router, RequestPasswordReset::URL, payload).await;
+        assert_eq!(StatusCode::TOO_MANY_REQUESTS, response.status());
+
+        Ok(())
+    }
 }
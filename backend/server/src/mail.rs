@@ -0,0 +1,135 @@
+use std::{path::PathBuf, sync::Arc};
+
+use axum::async_trait;
+use chrono::Utc;
+use clap::Args;
+use tracing::info;
+use url::Url;
+use uuid::Uuid;
+
+use crate::error::ApiResult;
+
+const MAIL_TARGET: &str = "uchat_server::mail";
+
+#[derive(Args, Clone, Debug)]
+pub struct MailConfig {
+    /// directory where outgoing mail is saved to, mail is only logged when unset
+    #[clap(long = "mail-dir", env = "API_MAIL_DIR")]
+    pub dir: Option<PathBuf>,
+
+    /// frontend url, used to build the links sent by mail
+    #[clap(
+        long = "frontend-url",
+        default_value = "http://127.0.0.1:8080/",
+        env = "FRONTEND_URL"
+    )]
+    pub frontend_url: Url,
+}
+
+// NOTE Matches the clap defaults, used when the config isn't parsed from the cli (e.g. tests)
+impl Default for MailConfig {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            frontend_url: Url::parse("http://127.0.0.1:8080/").unwrap(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Delivers mail to users, implement it to plug in a real mail provider
+#[async_trait]
+pub trait MailSender: Send + Sync {
+    async fn send(&self, mail: Mail) -> ApiResult<()>;
+}
+
+/// Only logs the mail, meant for local development
+pub struct LogMailSender;
+
+#[async_trait]
+impl MailSender for LogMailSender {
+    async fn send(&self, mail: Mail) -> ApiResult<()> {
+        info!(
+            target: MAIL_TARGET,
+            to = mail.to,
+            subject = mail.subject,
+            body = mail.body,
+            "mail sent"
+        );
+        Ok(())
+    }
+}
+
+/// Saves every mail as a file in `dir`, meant for local development
+pub struct FileMailSender {
+    dir: PathBuf,
+}
+
+impl FileMailSender {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+}
+
+#[async_trait]
+impl MailSender for FileMailSender {
+    async fn send(&self, mail: Mail) -> ApiResult<()> {
+        use tokio::fs;
+
+        fs::create_dir_all(&self.dir).await?;
+
+        // ? Prefixed with the date so mail is listed in the order it was sent
+        let file_name = format!(
+            "{}-{}.eml",
+            Utc::now().format("%Y%m%d%H%M%S"),
+            Uuid::new_v4()
+        );
+        let path = self.dir.join(file_name);
+        let contents = format!(
+            "To: {}\nSubject: {}\n\n{}\n",
+            mail.to, mail.subject, mail.body
+        );
+        fs::write(&path, contents).await?;
+
+        info!(target: MAIL_TARGET, to = mail.to, path = %path.display(), "mail saved");
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct Mailer {
+    sender: Arc<dyn MailSender>,
+    frontend_url: Url,
+}
+
+impl Mailer {
+    pub fn new(sender: Arc<dyn MailSender>, frontend_url: Url) -> Self {
+        Self {
+            sender,
+            frontend_url,
+        }
+    }
+
+    pub fn from_config(config: &MailConfig) -> Self {
+        let sender: Arc<dyn MailSender> = match config.dir {
+            Some(ref dir) => Arc::new(FileMailSender::new(dir.clone())),
+            None => Arc::new(LogMailSender),
+        };
+        Self::new(sender, config.frontend_url.clone())
+    }
+
+    /// Builds a link to a frontend page, e.g. `frontend_link("account/confirm_email/", token)`
+    pub fn frontend_link(&self, route: &str, param: &str) -> ApiResult<Url> {
+        Ok(self.frontend_url.join(route)?.join(param)?)
+    }
+
+    pub async fn send(&self, mail: Mail) -> ApiResult<()> {
+        self.sender.send(mail).await
+    }
+}
@@ -0,0 +1,32 @@
+use std::time::Duration;
+
+use tracing::error;
+use uchat_query::AsyncConnection;
+
+use crate::AppState;
+
+const PERIODIC_TARGET: &str = "uchat_server::periodic";
+
+/// Runs `task` with a database connection every `period`, should be spawned as a background task
+///
+/// ? The first tick completes immediately, so the task also runs on startup
+pub async fn run<F>(state: AppState, period: Duration, name: &'static str, mut task: F)
+where
+    F: FnMut(&mut AsyncConnection) + Send,
+{
+    let mut interval = tokio::time::interval(period);
+
+    loop {
+        interval.tick().await;
+
+        let mut conn = match state.db_pool.get().await {
+            Ok(conn) => conn,
+            Err(e) => {
+                error!(target: PERIODIC_TARGET, task = name, err = %e, "failed to connect to database");
+                continue;
+            }
+        };
+
+        task(&mut conn);
+    }
+}
@@ -0,0 +1,341 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use clap::Args;
+use tracing::debug;
+use uchat_domain::ids::UserId;
+
+use crate::{error::ServerErr, extractor::ClientInfo, AppState};
+
+const RATE_LIMIT_TARGET: &str = "uchat_server::rate_limit";
+
+// NOTE Shared by the clap defaults and `Default`, so the two can't drift apart
+const DEFAULT_LOGIN_MAX_FAILURES: u32 = 5;
+const DEFAULT_LOCKOUT_SECS: u64 = 30;
+const DEFAULT_MAX_LOCKOUT_SECS: u64 = 3600;
+const DEFAULT_REGISTER_MAX_ATTEMPTS: u32 = 5;
+const DEFAULT_PASSWORD_RESET_MAX_ATTEMPTS: u32 = 3;
+const DEFAULT_ATTEMPT_WINDOW_SECS: u64 = 900;
+
+#[derive(Args, Clone, Debug)]
+pub struct RateLimitConfig {
+    /// failed logins allowed for an IP or a username before it gets locked out
+    #[clap(
+        long = "login-max-failures",
+        default_value_t = DEFAULT_LOGIN_MAX_FAILURES,
+        env = "API_LOGIN_MAX_FAILURES"
+    )]
+    pub login_max_failures: u32,
+
+    /// first lockout after too many failed logins, doubled on each failure after that
+    #[clap(
+        long = "login-lockout-secs",
+        default_value_t = DEFAULT_LOCKOUT_SECS,
+        env = "API_LOGIN_LOCKOUT_SECS"
+    )]
+    pub lockout_secs: u64,
+
+    /// the longest a lockout can last
+    #[clap(
+        long = "login-max-lockout-secs",
+        default_value_t = DEFAULT_MAX_LOCKOUT_SECS,
+        env = "API_LOGIN_MAX_LOCKOUT_SECS"
+    )]
+    pub max_lockout_secs: u64,
+
+    /// accounts that can be created from the same IP before it gets locked out
+    #[clap(
+        long = "register-max-attempts",
+        default_value_t = DEFAULT_REGISTER_MAX_ATTEMPTS,
+        env = "API_REGISTER_MAX_ATTEMPTS"
+    )]
+    pub register_max_attempts: u32,
+
+    /// password reset mails that can be requested from the same IP or for the same email before
+    /// it gets locked out
+    #[clap(
+        long = "password-reset-max-attempts",
+        default_value_t = DEFAULT_PASSWORD_RESET_MAX_ATTEMPTS,
+        env = "API_PASSWORD_RESET_MAX_ATTEMPTS"
+    )]
+    pub password_reset_max_attempts: u32,
+
+    /// attempts are forgotten after this long without a new one, as long as there's no lockout in place
+    #[clap(
+        long = "rate-limit-window-secs",
+        default_value_t = DEFAULT_ATTEMPT_WINDOW_SECS,
+        env = "API_RATE_LIMIT_WINDOW_SECS"
+    )]
+    pub attempt_window_secs: u64,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            login_max_failures: DEFAULT_LOGIN_MAX_FAILURES,
+            lockout_secs: DEFAULT_LOCKOUT_SECS,
+            max_lockout_secs: DEFAULT_MAX_LOCKOUT_SECS,
+            register_max_attempts: DEFAULT_REGISTER_MAX_ATTEMPTS,
+            password_reset_max_attempts: DEFAULT_PASSWORD_RESET_MAX_ATTEMPTS,
+            attempt_window_secs: DEFAULT_ATTEMPT_WINDOW_SECS,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RateLimitKey {
+    LoginIp(String),
+    LoginHandle(String),
+    RegisterIp(String),
+    PasswordResetIp(String),
+    PasswordResetEmail(String),
+    TotpUser(UserId),
+    /// a login challenge, by the hash of its token
+    TotpChallenge(String),
+}
+
+impl RateLimitKey {
+    /// Logins are limited both by where they come from and by the account being guessed
+    pub fn login(client: &ClientInfo, handle: &str) -> Vec<Self> {
+        let mut keys = vec![Self::login_handle(handle)];
+        keys.extend(client.ip_address.clone().map(Self::LoginIp));
+        keys
+    }
+
+    pub fn login_handle(handle: &str) -> Self {
+        Self::LoginHandle(handle.to_lowercase())
+    }
+
+    /// Second factor codes are short, so guessing them is limited per account and per challenge as well
+    pub fn totp(client: &ClientInfo, user_id: UserId, challenge: &str) -> Vec<Self> {
+        let mut keys = vec![
+            Self::TotpUser(user_id),
+            Self::TotpChallenge(uchat_crypto::token::hash_secret(challenge)),
+        ];
+        keys.extend(client.ip_address.clone().map(Self::LoginIp));
+        keys
+    }
+
+    /// Every reset request sends a mail, so they're limited by where they come from and by who
+    /// would receive it
+    pub fn password_reset(client: &ClientInfo, email: &str) -> Vec<Self> {
+        let mut keys = vec![Self::PasswordResetEmail(email.to_lowercase())];
+        keys.extend(client.ip_address.clone().map(Self::PasswordResetIp));
+        keys
+    }
+
+    /// Reset tokens are guessed without knowing the account, so only where they come from is
+    /// limited, against the same budget as logins
+    pub fn reset_password(client: &ClientInfo) -> Vec<Self> {
+        client
+            .ip_address
+            .clone()
+            .map(Self::LoginIp)
+            .into_iter()
+            .collect()
+    }
+
+    pub fn register(client: &ClientInfo) -> Vec<Self> {
+        client
+            .ip_address
+            .clone()
+            .map(Self::RegisterIp)
+            .into_iter()
+            .collect()
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Attempts {
+    count: u32,
+    last_attempt: Instant,
+    locked_until: Option<Instant>,
+}
+
+// NOTE Codes for a login challenge can be retried a few times, then the password has to be entered again
+const TOTP_CHALLENGE_MAX_FAILURES: u32 = 3;
+// ? Longer than a challenge stays valid, so a challenge that ran out of attempts is spent
+const TOTP_CHALLENGE_LOCKOUT: Duration = Duration::from_secs(60 * 60);
+
+/// Counts login and registration attempts in memory, locking out keys that go over the limit
+#[derive(Clone, Debug, Default)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    attempts: Arc<Mutex<HashMap<RateLimitKey, Attempts>>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            attempts: Arc::default(),
+        }
+    }
+
+    fn window(&self) -> Duration {
+        Duration::from_secs(self.config.attempt_window_secs)
+    }
+
+    fn max_attempts(&self, key: &RateLimitKey) -> u32 {
+        match key {
+            RateLimitKey::LoginIp(_) | RateLimitKey::LoginHandle(_) | RateLimitKey::TotpUser(_) => {
+                self.config.login_max_failures
+            }
+            RateLimitKey::RegisterIp(_) => self.config.register_max_attempts,
+            RateLimitKey::PasswordResetIp(_) | RateLimitKey::PasswordResetEmail(_) => {
+                self.config.password_reset_max_attempts
+            }
+            RateLimitKey::TotpChallenge(_) => TOTP_CHALLENGE_MAX_FAILURES,
+        }
+    }
+
+    /// Lockouts double with every attempt over the limit, up to `max_lockout_secs`
+    fn lockout(&self, key: &RateLimitKey, attempts_over_limit: u32) -> Duration {
+        if let RateLimitKey::TotpChallenge(_) = key {
+            return TOTP_CHALLENGE_LOCKOUT;
+        }
+
+        let multiplier = 2u64.saturating_pow(attempts_over_limit);
+        let secs = self
+            .config
+            .lockout_secs
+            .saturating_mul(multiplier)
+            .min(self.config.max_lockout_secs);
+        Duration::from_secs(secs)
+    }
+
+    /// Counts an attempt against every key before it's made, failing with a 429 if any of them
+    /// is locked out
+    ///
+    /// NOTE The check and the count happen under one lock, so concurrent attempts can't all slip in
+    /// before the first failure is recorded
+    pub fn reserve(&self, keys: &[RateLimitKey]) -> Result<(), ServerErr> {
+        let now = Instant::now();
+        let window = self.window();
+        let mut attempts = self.attempts.lock().expect("rate limiter lock poisoned");
+
+        let retry_after = keys
+            .iter()
+            .filter_map(|key| attempts.get(key)?.locked_until)
+            .filter(|locked_until| *locked_until > now)
+            .max();
+        if let Some(locked_until) = retry_after {
+            return Err(ServerErr::TooManyRequests(locked_until - now));
+        }
+
+        for key in keys {
+            let max_attempts = self.max_attempts(key);
+            let entry = attempts.entry(key.clone()).or_insert(Attempts {
+                count: 0,
+                last_attempt: now,
+                locked_until: None,
+            });
+
+            if entry.is_stale(now, window) {
+                *entry = Attempts {
+                    count: 0,
+                    last_attempt: now,
+                    locked_until: None,
+                };
+            }
+
+            entry.count += 1;
+            entry.last_attempt = now;
+            if entry.count >= max_attempts {
+                entry.locked_until = Some(now + self.lockout(key, entry.count - max_attempts));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Takes back an attempt counted by `reserve` once it turned out to succeed
+    ///
+    /// ! Lockouts are left in place, since concurrent failed attempts may have set them
+    pub fn release(&self, keys: &[RateLimitKey]) {
+        let mut attempts = self.attempts.lock().expect("rate limiter lock poisoned");
+        for key in keys {
+            if let Some(entry) = attempts.get_mut(key) {
+                entry.count = entry.count.saturating_sub(1);
+            }
+        }
+    }
+
+    /// Forgets the attempts made so far, used once a login succeeds
+    pub fn reset(&self, keys: &[RateLimitKey]) {
+        let mut attempts = self.attempts.lock().expect("rate limiter lock poisoned");
+        for key in keys {
+            attempts.remove(key);
+        }
+    }
+
+    /// Forgets keys that haven't been used in a while and aren't locked out, returning how many
+    pub fn prune(&self) -> usize {
+        let now = Instant::now();
+        let window = self.window();
+        let mut attempts = self.attempts.lock().expect("rate limiter lock poisoned");
+
+        let before = attempts.len();
+        attempts.retain(|_, entry| !entry.is_stale(now, window));
+        before - attempts.len()
+    }
+}
+
+/// Prunes stale attempts once every window, should be spawned as a background task
+pub async fn prune_periodically(state: AppState) {
+    let rate_limiter = state.rate_limiter.clone();
+    let period = rate_limiter.window();
+
+    // ? Doesn't need the connection, but shares the scheduling and logging of the other tasks
+    crate::periodic::run(state, period, "rate_limit", move |_| {
+        let pruned = rate_limiter.prune();
+        debug!(target: RATE_LIMIT_TARGET, pruned, "rate limiter pruned");
+    })
+    .await
+}
+
+impl Attempts {
+    fn is_stale(&self, now: Instant, window: Duration) -> bool {
+        let locked = self.locked_until.map_or(false, |until| until > now);
+        !locked && now.duration_since(self.last_attempt) > window
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn release_keeps_lockouts_in_place() {
+        let rate_limiter = RateLimiter::default();
+        let keys = [RateLimitKey::login_handle("user")];
+
+        for _ in 0..DEFAULT_LOGIN_MAX_FAILURES {
+            rate_limiter.reserve(&keys).unwrap();
+        }
+        rate_limiter.release(&keys);
+        assert!(rate_limiter.reserve(&keys).is_err());
+    }
+
+    #[test]
+    fn prune_forgets_stale_keys_that_arent_locked_out() {
+        let rate_limiter = RateLimiter::new(RateLimitConfig {
+            attempt_window_secs: 0,
+            ..Default::default()
+        });
+        let locked = [RateLimitKey::login_handle("locked")];
+        let stale = [RateLimitKey::login_handle("stale")];
+
+        for _ in 0..DEFAULT_LOGIN_MAX_FAILURES {
+            rate_limiter.reserve(&locked).unwrap();
+        }
+        rate_limiter.reserve(&stale).unwrap();
+        std::thread::sleep(Duration::from_millis(10));
+
+        assert_eq!(rate_limiter.prune(), 1);
+        assert!(rate_limiter.reserve(&locked).is_err());
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
     handler::{self, with_handler, with_public_handler},
+    image::MAX_IMAGE_BYTES,
     AppState,
 };
 use axum::{
@@ -7,28 +8,40 @@ use axum::{
     routing::{get, post},
     Router,
 };
-use hyper::{header::CONTENT_TYPE, http::HeaderValue, Method};
+use hyper::{
+    header::{HeaderName, AUTHORIZATION, CONTENT_TYPE, COOKIE, SET_COOKIE},
+    http::HeaderValue,
+    Method,
+};
 use tower::ServiceBuilder;
 use tower_http::{
     cors::CorsLayer,
     limit::RequestBodyLimitLayer,
+    sensitive_headers::{SetSensitiveRequestHeadersLayer, SetSensitiveResponseHeadersLayer},
     trace::{DefaultMakeSpan, DefaultOnRequest, DefaultOnResponse, TraceLayer},
     LatencyUnit,
 };
 use tracing::Level;
 
+use uchat_domain::post::MAX_GALLERY_IMAGES;
 use uchat_endpoint::{
+    message::endpoint::{Conversations, SendDirectMessage, ViewConversation},
     post::endpoint::{
-        Bookmark, BookmarkedPosts, Boost, HomePosts, LikedPosts, NewPost, React, TrendingPosts,
-        Vote,
+        Bookmark, BookmarkedPosts, Boost, CancelScheduledPost, DeletePost, EditPost, HomePosts,
+        LikedPosts, NewPost, React, ReschedulePost, ScheduledPosts, TrendingPosts, ViewPost, Vote,
     },
     user::endpoint::{
-        CreateUser, FollowUser, GetMyProfile, IsFollowing, Login, UpdateProfile, ViewProfile,
+        AccessTokens, ActiveSessions, ConfirmEmail, ConfirmTotp, CreateAccessToken, CreateUser,
+        DisableTotp, EnrollTotp, FollowUser, GetMyProfile, IsFollowing, Login, Logout, LogoutAll,
+        RequestPasswordReset, ResetPassword, RevokeAccessToken, RevokeSession,
+        SendEmailConfirmation, UpdateProfile, VerifyLoginTotp, ViewProfile,
     },
     Endpoint,
 };
 
-const EIGHT_MEGABYTES: usize = 8 * 1024 * 1024;
+// ? A gallery of the largest images allowed, base64 encoded, with a megabyte left for the rest
+// of the request
+const MAX_REQUEST_BYTES: usize = MAX_GALLERY_IMAGES * (MAX_IMAGE_BYTES / 3 + 1) * 4 + 1024 * 1024;
 
 pub fn new_router(state: AppState) -> Router {
     let img_route = {
@@ -41,7 +54,20 @@ pub fn new_router(state: AppState) -> Router {
         // NOTE Dynamic route for images
         .route(&format!("/{img_route}:id"), get(handler::load_image))
         .route(CreateUser::URL, post(with_public_handler::<CreateUser>))
-        .route(Login::URL, post(with_public_handler::<Login>));
+        .route(Login::URL, post(with_public_handler::<Login>))
+        .route(
+            VerifyLoginTotp::URL,
+            post(with_public_handler::<VerifyLoginTotp>),
+        )
+        .route(ConfirmEmail::URL, post(with_public_handler::<ConfirmEmail>))
+        .route(
+            RequestPasswordReset::URL,
+            post(with_public_handler::<RequestPasswordReset>),
+        )
+        .route(
+            ResetPassword::URL,
+            post(with_public_handler::<ResetPassword>),
+        );
 
     let authorized_routes = Router::new()
         .route(NewPost::URL, post(with_handler::<NewPost>))
@@ -53,13 +79,51 @@ pub fn new_router(state: AppState) -> Router {
         .route(Boost::URL, post(with_handler::<Boost>))
         .route(Vote::URL, post(with_handler::<Vote>))
         .route(React::URL, post(with_handler::<React>))
+        .route(ViewPost::URL, post(with_handler::<ViewPost>))
+        .route(ScheduledPosts::URL, post(with_handler::<ScheduledPosts>))
+        .route(ReschedulePost::URL, post(with_handler::<ReschedulePost>))
+        .route(
+            CancelScheduledPost::URL,
+            post(with_handler::<CancelScheduledPost>),
+        )
+        .route(DeletePost::URL, post(with_handler::<DeletePost>))
+        .route(EditPost::URL, post(with_handler::<EditPost>))
+        .route(Logout::URL, post(with_handler::<Logout>))
+        .route(LogoutAll::URL, post(with_handler::<LogoutAll>))
+        .route(ActiveSessions::URL, post(with_handler::<ActiveSessions>))
+        .route(RevokeSession::URL, post(with_handler::<RevokeSession>))
+        .route(AccessTokens::URL, post(with_handler::<AccessTokens>))
+        .route(
+            CreateAccessToken::URL,
+            post(with_handler::<CreateAccessToken>),
+        )
+        .route(
+            RevokeAccessToken::URL,
+            post(with_handler::<RevokeAccessToken>),
+        )
+        .route(
+            SendEmailConfirmation::URL,
+            post(with_handler::<SendEmailConfirmation>),
+        )
+        .route(EnrollTotp::URL, post(with_handler::<EnrollTotp>))
+        .route(ConfirmTotp::URL, post(with_handler::<ConfirmTotp>))
+        .route(DisableTotp::URL, post(with_handler::<DisableTotp>))
         .route(GetMyProfile::URL, post(with_handler::<GetMyProfile>))
         .route(UpdateProfile::URL, post(with_handler::<UpdateProfile>))
         .route(IsFollowing::URL, post(with_handler::<IsFollowing>))
         .route(FollowUser::URL, post(with_handler::<FollowUser>))
         .route(ViewProfile::URL, post(with_handler::<ViewProfile>))
+        .route(
+            SendDirectMessage::URL,
+            post(with_handler::<SendDirectMessage>),
+        )
+        .route(Conversations::URL, post(with_handler::<Conversations>))
+        .route(
+            ViewConversation::URL,
+            post(with_handler::<ViewConversation>),
+        )
         .layer(DefaultBodyLimit::disable())
-        .layer(RequestBodyLimitLayer::new(EIGHT_MEGABYTES));
+        .layer(RequestBodyLimitLayer::new(MAX_REQUEST_BYTES));
 
     Router::new()
         .merge(public_routes)
@@ -67,6 +131,13 @@ pub fn new_router(state: AppState) -> Router {
         .layer(
             // NOTE With ServiceBuilder, the added layers will run in the order in which they are added
             ServiceBuilder::new()
+                // ! Headers marked as sensitive show up as "Sensitive" in the request span, so
+                // sessions and tokens don't end up in the logs
+                .layer(SetSensitiveRequestHeadersLayer::new([
+                    AUTHORIZATION,
+                    COOKIE,
+                    HeaderName::from_static(uchat_endpoint::CSRF_HEADER),
+                ]))
                 .layer(
                     TraceLayer::new_for_http()
                         .make_span_with(DefaultMakeSpan::new().include_headers(true))
@@ -77,6 +148,7 @@ pub fn new_router(state: AppState) -> Router {
                                 .latency_unit(LatencyUnit::Micros),
                         ),
                 )
+                .layer(SetSensitiveResponseHeadersLayer::new([SET_COOKIE]))
                 .layer(
                     CorsLayer::new()
                         .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
@@ -87,7 +159,10 @@ pub fn new_router(state: AppState) -> Router {
                                 .parse::<HeaderValue>()
                                 .unwrap(),
                         )
-                        .allow_headers([CONTENT_TYPE]),
+                        .allow_headers([
+                            CONTENT_TYPE,
+                            HeaderName::from_static(uchat_endpoint::CSRF_HEADER),
+                        ]),
                 )
                 // ? Attaching the application state to the layers
                 .layer(axum::Extension(state.clone())),
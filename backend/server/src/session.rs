@@ -0,0 +1,109 @@
+use std::time::Duration;
+
+use axum::response::AppendHeaders;
+use hyper::header::{HeaderName, SET_COOKIE};
+use tracing::{error, info};
+use uchat_cookie::{Cookie, SameSite};
+use uchat_domain::ids::SessionId;
+
+use crate::AppState;
+
+// NOTE The session id is prefixed, so a session signature can't be passed off as a CSRF token
+const CSRF_CONTEXT: &[u8] = b"csrf:";
+
+fn csrf_data(session_id: SessionId) -> Vec<u8> {
+    [CSRF_CONTEXT, session_id.as_uuid().as_bytes()].concat()
+}
+
+/// Token the frontend sends back in the CSRF header, it's only valid for the session it was issued with
+///
+/// Cookies are attached to requests made by any site, but other sites can't read this token to set the header
+pub fn csrf_token(state: &AppState, session_id: SessionId) -> String {
+    let mut rng = state.rng.clone();
+    state
+        .signing_keys
+        .sign(&mut rng, &csrf_data(session_id))
+        .encode()
+}
+
+pub fn verify_csrf_token(state: &AppState, session_id: SessionId, token: &str) -> bool {
+    uchat_crypto::sign::KeyedSignature::decode(token)
+        .and_then(|signature| {
+            state
+                .signing_keys
+                .verify(&csrf_data(session_id), &signature)
+        })
+        .is_ok()
+}
+
+pub type SessionCookies = AppendHeaders<Vec<(HeaderName, String)>>;
+
+// NOTE Local development is served over plain http, where secure cookies are never sent
+const SECURE_COOKIES: bool = cfg!(not(debug_assertions));
+
+fn session_cookie<'a>(name: &'a str, value: &'a str) -> Cookie<'a> {
+    Cookie::new(name, value)
+        .http_only(true)
+        .secure(SECURE_COOKIES)
+        .same_site(SameSite::Strict)
+}
+
+/// `Set-Cookie` headers that store the session in the browser, out of reach of scripts
+pub fn set_cookies(
+    session_id: SessionId,
+    signature: &str,
+    expires_in: chrono::Duration,
+) -> SessionCookies {
+    let session_id = session_id.into_inner().to_string();
+    let max_age = expires_in.num_seconds();
+
+    AppendHeaders(vec![
+        (
+            SET_COOKIE,
+            session_cookie(uchat_cookie::SESSION_ID, &session_id)
+                .max_age(max_age)
+                .to_string(),
+        ),
+        (
+            SET_COOKIE,
+            session_cookie(uchat_cookie::SESSION_SIGNATURE, signature)
+                .max_age(max_age)
+                .to_string(),
+        ),
+    ])
+}
+
+/// `Set-Cookie` headers that make the browser forget the session
+pub fn removal_cookies() -> SessionCookies {
+    AppendHeaders(
+        [uchat_cookie::SESSION_ID, uchat_cookie::SESSION_SIGNATURE]
+            .into_iter()
+            .map(|name| (SET_COOKIE, session_cookie(name, "").max_age(0).to_string()))
+            .collect(),
+    )
+}
+
+/// For responses that don't change the session
+pub fn no_cookies() -> SessionCookies {
+    AppendHeaders(Vec::new())
+}
+
+const SESSION_TARGET: &str = "uchat_server::session";
+
+/// Deletes expired sessions and password resets every `interval`, should be spawned as a background task
+pub async fn purge_expired_periodically(state: AppState, interval: Duration) {
+    crate::periodic::run(state, interval, "purge_expired", |conn| {
+        match uchat_query::session::delete_expired(conn) {
+            Ok(total) => info!(target: SESSION_TARGET, total, "expired sessions purged"),
+            Err(e) => error!(target: SESSION_TARGET, err = %e, "failed to purge expired sessions"),
+        }
+
+        match uchat_query::password_reset::delete_expired(conn) {
+            Ok(total) => info!(target: SESSION_TARGET, total, "expired password resets purged"),
+            Err(e) => {
+                error!(target: SESSION_TARGET, err = %e, "failed to purge expired password resets")
+            }
+        }
+    })
+    .await
+}
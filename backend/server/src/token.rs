@@ -0,0 +1,61 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uchat_domain::ids::UserId;
+
+use crate::{
+    error::{ApiErr, ApiResult},
+    prelude::*,
+    AppState,
+};
+
+/// What a token grants, so a token issued for one action can't be used for another
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TokenPurpose {
+    ConfirmEmail {
+        email: String,
+    },
+    /// the password was checked, only the second factor is missing to log in
+    TotpLogin,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TokenClaims {
+    pub user_id: UserId,
+    pub purpose: TokenPurpose,
+    pub expires_at: DateTime<Utc>,
+}
+
+pub fn issue(
+    state: &AppState,
+    user_id: UserId,
+    purpose: TokenPurpose,
+    valid_for: Duration,
+) -> ApiResult<String> {
+    let claims = TokenClaims {
+        user_id,
+        purpose,
+        expires_at: Utc::now() + valid_for,
+    };
+
+    let mut rng = state.rng.clone();
+    Ok(uchat_crypto::token::sign(
+        &state.signing_keys,
+        &mut rng,
+        &claims,
+    )?)
+}
+
+pub fn invalid_token() -> ApiErr {
+    ApiErr::new(StatusCode::BAD_REQUEST, "invalid or expired token")
+}
+
+/// Returns the claims of a token that was issued by this server and hasn't expired yet
+pub fn verify(state: &AppState, token: &str) -> ApiResult<TokenClaims> {
+    let claims: TokenClaims =
+        uchat_crypto::token::verify(&state.signing_keys, token).map_err(|_| invalid_token())?;
+
+    if claims.expires_at <= Utc::now() {
+        return Err(invalid_token());
+    }
+    Ok(claims)
+}
@@ -0,0 +1,125 @@
+use std::time::Duration;
+
+use clap::Args;
+use tracing::{error, info};
+use uchat_query::post::TrendingParams;
+
+use crate::AppState;
+
+const TRENDING_TARGET: &str = "uchat_server::trending";
+
+// NOTE Also used by `Default`, for configs that aren't parsed from the cli (e.g. tests)
+const DEFAULT_WINDOW_HOURS: i32 = 48;
+const DEFAULT_LIKE_WEIGHT: f64 = 1.0;
+const DEFAULT_DISLIKE_WEIGHT: f64 = 1.0;
+const DEFAULT_BOOST_WEIGHT: f64 = 2.0;
+const DEFAULT_REPLY_WEIGHT: f64 = 1.5;
+const DEFAULT_GRAVITY: f64 = 1.5;
+const DEFAULT_MAX_POSTS: i64 = 300;
+const DEFAULT_REFRESH_SECS: u64 = 300;
+
+#[derive(Args, Clone, Debug)]
+pub struct TrendingConfig {
+    /// only posts made within this many hours can trend
+    #[clap(
+        long = "trending-window-hours",
+        default_value_t = DEFAULT_WINDOW_HOURS,
+        env = "API_TRENDING_WINDOW_HOURS"
+    )]
+    pub window_hours: i32,
+
+    #[clap(
+        long = "trending-like-weight",
+        default_value_t = DEFAULT_LIKE_WEIGHT,
+        env = "API_TRENDING_LIKE_WEIGHT"
+    )]
+    pub like_weight: f64,
+
+    #[clap(
+        long = "trending-dislike-weight",
+        default_value_t = DEFAULT_DISLIKE_WEIGHT,
+        env = "API_TRENDING_DISLIKE_WEIGHT"
+    )]
+    pub dislike_weight: f64,
+
+    #[clap(
+        long = "trending-boost-weight",
+        default_value_t = DEFAULT_BOOST_WEIGHT,
+        env = "API_TRENDING_BOOST_WEIGHT"
+    )]
+    pub boost_weight: f64,
+
+    #[clap(
+        long = "trending-reply-weight",
+        default_value_t = DEFAULT_REPLY_WEIGHT,
+        env = "API_TRENDING_REPLY_WEIGHT"
+    )]
+    pub reply_weight: f64,
+
+    /// how fast older posts stop trending
+    #[clap(
+        long = "trending-gravity",
+        default_value_t = DEFAULT_GRAVITY,
+        env = "API_TRENDING_GRAVITY"
+    )]
+    pub gravity: f64,
+
+    /// maximum amount of trending posts
+    #[clap(
+        long = "trending-max-posts",
+        default_value_t = DEFAULT_MAX_POSTS,
+        env = "API_TRENDING_MAX_POSTS"
+    )]
+    pub max_posts: i64,
+
+    /// how often trending posts are recomputed, in seconds
+    #[clap(
+        long = "trending-refresh-secs",
+        default_value_t = DEFAULT_REFRESH_SECS,
+        env = "API_TRENDING_REFRESH_SECS"
+    )]
+    pub refresh_secs: u64,
+}
+
+impl Default for TrendingConfig {
+    fn default() -> Self {
+        Self {
+            window_hours: DEFAULT_WINDOW_HOURS,
+            like_weight: DEFAULT_LIKE_WEIGHT,
+            dislike_weight: DEFAULT_DISLIKE_WEIGHT,
+            boost_weight: DEFAULT_BOOST_WEIGHT,
+            reply_weight: DEFAULT_REPLY_WEIGHT,
+            gravity: DEFAULT_GRAVITY,
+            max_posts: DEFAULT_MAX_POSTS,
+            refresh_secs: DEFAULT_REFRESH_SECS,
+        }
+    }
+}
+
+impl From<&TrendingConfig> for TrendingParams {
+    fn from(config: &TrendingConfig) -> Self {
+        Self {
+            window_hours: config.window_hours,
+            like_weight: config.like_weight,
+            dislike_weight: config.dislike_weight,
+            boost_weight: config.boost_weight,
+            reply_weight: config.reply_weight,
+            gravity: config.gravity,
+            max_posts: config.max_posts,
+        }
+    }
+}
+
+/// Recomputes the trending posts every `refresh_secs`, should be spawned as a background task
+pub async fn refresh_periodically(state: AppState) {
+    let params = TrendingParams::from(&state.trending);
+    let period = Duration::from_secs(state.trending.refresh_secs);
+
+    crate::periodic::run(state, period, "trending", move |conn| {
+        match uchat_query::post::refresh_trending(conn, &params) {
+            Ok(total) => info!(target: TRENDING_TARGET, total, "trending posts refreshed"),
+            Err(e) => error!(target: TRENDING_TARGET, err = %e, "failed to refresh trending posts"),
+        }
+    })
+    .await
+}
@@ -18,22 +18,33 @@ pub static SIDEBAR: AtomRef<SidebarManager> = |_| SidebarManager::default();
 pub fn Init(cx: Scope) -> Element {
     let api_client = ApiClient::global();
     let router = use_router(cx);
+    let route = use_route(cx);
     let toaster = use_toaster(cx);
     let local_profile = use_local_profile(cx);
 
+    let on_public_page = page::is_public(route.url().path());
 
     let _fetch_local_profile = {
         to_owned![api_client, toaster, router, local_profile];
-        use_future(cx, (), |_| async move {
+        use_future(cx, (), move |_| async move {
             use uchat_endpoint::user::endpoint::{GetMyProfile, GetMyProfileOk};
-            let response = fetch_json!(<GetMyProfileOk>, api_client, GetMyProfile);
+
+            // ? Without the hint there's no session to fetch the profile for
+            let response = if crate::util::cookie::is_logged_in() {
+                fetch_json!(<GetMyProfileOk>, api_client, GetMyProfile).ok()
+            } else {
+                None
+            };
             match response {
-                Ok(res) => {
+                Some(res) => {
                     local_profile.write().image = res.profile_image;
                     local_profile.write().user_id = Some(res.user_id);
                 },
-                Err(_) => {
-                    
+                None => {
+                    crate::util::cookie::clear_logged_in();
+                    if on_public_page {
+                        return;
+                    }
                     toasty!(toaster => error: format!("Please log in or create an account to continue."));
                 router.navigate_to(page::ACCOUNT_LOGIN);
                 }
@@ -59,14 +70,24 @@ pub fn App(cx: Scope) -> Element {
                 Route { to: page::HOME, page::Home {} }
                 Route { to: page::HOME_LIKED, page::Liked {} }
                 Route { to: page::HOME_BOOKMARKED, page::Bookmarked {} }
+                Route { to: page::HOME_SCHEDULED, page::Scheduled {} }
                 Route { to: page::ACCOUNT_REGISTER, page::Register {} }
                 Route { to: page::ACCOUNT_LOGIN, page::Login {} }
+                Route { to: page::ACCOUNT_SESSIONS, page::Sessions {} }
+                Route { to: page::ACCOUNT_TWO_FACTOR, page::TwoFactor {} }
+                Route { to: page::ACCOUNT_ACCESS_TOKENS, page::AccessTokens {} }
+                Route { to: page::ACCOUNT_CONFIRM_EMAIL, page::ConfirmEmail {} }
+                Route { to: page::ACCOUNT_FORGOT_PASSWORD, page::ForgotPassword {} }
+                Route { to: page::ACCOUNT_RESET_PASSWORD, page::ResetPassword {} }
                 Route { to: page::POST_NEW_CHAT, page::NewChat {} }
                 Route { to: page::POST_NEW_IMAGE, page::NewImage {} }
                 Route { to: page::POST_NEW_POLL, page::NewPoll {} }
+                Route { to: page::POST_VIEW, page::ViewPost {} }
                 Route { to: page::POSTS_TRENDING, page::Trending {} }
                 Route { to: page::PROFILE_EDIT, page::EditProfile {} }
                 Route { to: page::PROFILE_VIEW, page::ViewProfile {} }
+                Route { to: page::MESSAGES, page::Inbox {} }
+                Route { to: page::MESSAGES_VIEW, page::Conversation {} }
             }
             ToastRoot { toaster: toaster }
             Navbar {}
@@ -1,6 +1,7 @@
 #![allow(non_snake_case)]
 
 use dioxus::prelude::*;
+use uchat_endpoint::app_url::user_content::{self, ImageSize};
 
 use crate::prelude::{use_local_profile, use_sidebar};
 
@@ -59,8 +60,8 @@ pub fn AppBar<'a>(cx: Scope<'a, AppBarProps<'a>>) -> Element {
     let profile_img_src = local_profile
         .image
         .as_ref()
-        .map(|url| url.as_str())
-        .unwrap_or_else(|| "");
+        .map(|url| user_content::sized(url, ImageSize::Avatar).to_string())
+        .unwrap_or_default();
 
     cx.render(rsx! {
         div { class: "max-w-[var(--content-max-width)] h-[var(--appbar-height)] fixed top-0 right-0 left-0 mx-auto z-50 bg-slate-200",
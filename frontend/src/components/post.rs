@@ -14,7 +14,10 @@ use dioxus_router::{use_router, RouterContext};
 use fermi::{use_atom_ref, UseAtomRef};
 use indexmap::IndexMap;
 use uchat_domain::ids::{PostId, UserId};
-use uchat_endpoint::post::types::PublicPost;
+use uchat_endpoint::{
+    app_url::user_content::{self, ImageSize},
+    post::types::PublicPost,
+};
 
 pub fn use_post_manager(cx: &ScopeState) -> &UseAtomRef<PostManager> {
     use_atom_ref(cx, crate::app::POST_MANAGER)
@@ -49,6 +52,16 @@ impl PostManager {
         }
     }
 
+    /// Adds posts after the ones already loaded, such as when loading the next page of a feed
+    pub fn append<T>(&mut self, posts: T)
+    where
+        T: Iterator<Item = PublicPost>,
+    {
+        for post in posts {
+            self.posts.insert(post.id, post);
+        }
+    }
+
     pub fn clear(&mut self) {
         self.posts.clear();
     }
@@ -89,11 +102,11 @@ pub fn ProfileImage<'a>(cx: Scope<'a>, post: &'a PublicPost) -> Element {
 
     let poster_info = &post.by_user;
 
-    let profile_img_src = &poster_info
+    let profile_img_src = poster_info
         .profile_image
         .as_ref()
-        .map(|url| url.as_str())
-        .unwrap_or_else(|| "");
+        .map(|url| user_content::sized(url, ImageSize::Avatar).to_string())
+        .unwrap_or_default();
 
     cx.render(rsx! {
         div {
@@ -107,6 +120,8 @@ pub fn ProfileImage<'a>(cx: Scope<'a>, post: &'a PublicPost) -> Element {
 }
 #[inline_props]
 pub fn Header<'a>(cx: Scope<'a>, post: &'a PublicPost) -> Element {
+    let router = use_router(cx);
+
     let (post_date, post_time) = {
         let date = post.time_posted.format("%Y-%m-%d");
         let time = post.time_posted.format("%H:%M:%S");
@@ -119,15 +134,37 @@ pub fn Header<'a>(cx: Scope<'a>, post: &'a PublicPost) -> Element {
     };
 
     let handle = &post.by_user.handle;
+    let post_id = post.id;
+
+    let edited_el = post.edited_at.map(|edited_at| {
+        let edited_at = edited_at.format("%Y-%m-%d %H:%M:%S").to_string();
+        rsx! { div { class: "text-sm opacity-70", title: "{edited_at}", "(edited)" } }
+    });
+
+    let reply_to_el = post.reply_to.as_ref().map(|(reply_handle, _, reply_post_id)| {
+        let reply_post_id = *reply_post_id;
+        let reply_handle: &str = reply_handle.as_ref();
+        rsx! {
+            a {
+                class: "link text-sm",
+                onclick: move |_| router.navigate_to(&crate::page::route::post_view(reply_post_id)),
+                "replying to @{reply_handle}"
+            }
+        }
+    });
 
     cx.render(rsx! {
         div { class: "flex justify-between", onclick: move |_| (),
             div { "{display_name} " }
             div { class: "font-light", "{handle}" }
         }
-        div { class: "text-right",
+        reply_to_el,
+        div {
+            class: "text-right cursor-pointer",
+            onclick: move |_| router.navigate_to(&crate::page::route::post_view(post_id)),
             div { "{post_date}" }
             div { "{post_time}" }
+            edited_el
         }
     })
 }
@@ -136,10 +173,16 @@ pub fn Header<'a>(cx: Scope<'a>, post: &'a PublicPost) -> Element {
 pub fn PublicPostEntry(cx: Scope, post_id: PostId) -> Element {
     let post_manager = use_post_manager(cx);
 
-    let this_post = {
-        let post = post_manager.read().get(post_id).unwrap().clone();
-        use_state(cx, || post)
-    };
+    let post = post_manager.read().get(post_id).unwrap().clone();
+    let this_post = use_state(cx, || post.clone());
+
+    // NOTE Keeps the entry in sync with changes made through the post manager, such as edits
+    use_effect(cx, (&post,), |(post,)| {
+        to_owned![this_post];
+        async move {
+            this_post.set(post);
+        }
+    });
 
     cx.render(rsx! {
         div { key: "{this_post.id.to_string()}", class: "grid grid-cols-[50px_1fr] gap-2 mb-4",
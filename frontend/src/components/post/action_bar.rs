@@ -1,11 +1,22 @@
 #![allow(non_snake_case)]
 
-use crate::{components::post::quick_respond::QuickRespond, prelude::*, toasty};
+use crate::{
+    components::post::quick_respond::{MessageInput, QuickRespond},
+    prelude::*,
+    toasty,
+};
 use dioxus::prelude::*;
-use uchat_domain::ids::PostId;
-use uchat_endpoint::post::types::LikeStatus;
+use fermi::UseAtomRef;
+use uchat_domain::{
+    ids::PostId,
+    post::{Emoji, REACTION_EMOJI},
+};
+use uchat_endpoint::post::{
+    endpoint::{React, ReactOk},
+    types::{Content, LikeStatus, ReactAction},
+};
 
-use super::use_post_manager;
+use super::{use_post_manager, PostManager};
 
 #[derive(Props)]
 struct ActionBarIconProps<'a, F>
@@ -60,7 +71,6 @@ pub fn LikeDislike(
         &cx,
         [api_client, post_manager, toaster, post_id],
         move |like_status| async move {
-            use uchat_endpoint::post::endpoint::{React, ReactOk};
             let like_status = {
                 if post_manager.read().get(&post_id).unwrap().like_status == like_status {
                     LikeStatus::NoReaction
@@ -70,17 +80,11 @@ pub fn LikeDislike(
             };
 
             let request = React {
-                like_status,
+                action: ReactAction::LikeStatus(like_status),
                 post_id,
             };
             match fetch_json!(<ReactOk>, api_client, request) {
-                Ok(res) => {
-                    post_manager.write().update(post_id, |post| {
-                        post.like_status = res.like_status;
-                        post.likes = res.likes;
-                        post.dislikes = res.dislikes;
-                    });
-                }
+                Ok(res) => update_reactions(&post_manager, post_id, res),
                 Err(e) => toasty!(toaster => error: format!("Failed to react to post: {e}")),
             }
         }
@@ -100,6 +104,105 @@ pub fn LikeDislike(
     })
 }
 
+fn update_reactions(post_manager: &UseAtomRef<PostManager>, post_id: PostId, res: ReactOk) {
+    post_manager.write().update(post_id, |post| {
+        post.like_status = res.like_status;
+        post.likes = res.likes;
+        post.dislikes = res.dislikes;
+        post.emoji_reactions = res.emoji_reactions.clone();
+    });
+}
+
+#[inline_props]
+pub fn EmojiReactions(cx: Scope, post_id: PostId) -> Element {
+    let post_manager = use_post_manager(cx);
+    let toaster = use_toaster(cx);
+    let api_client = ApiClient::global();
+    let picker_opened = use_state(cx, || false);
+
+    let emoji_onclick = async_handler!(
+        &cx,
+        [api_client, post_manager, toaster, post_id],
+        move |emoji: Emoji| async move {
+            let reacted = post_manager
+                .read()
+                .get(&post_id)
+                .map(|post| {
+                    post.emoji_reactions
+                        .iter()
+                        .any(|reaction| reaction.reacted && reaction.emoji == emoji)
+                })
+                .unwrap_or(false);
+
+            let request = React {
+                action: if reacted {
+                    ReactAction::RemoveEmoji(emoji)
+                } else {
+                    ReactAction::AddEmoji(emoji)
+                },
+                post_id,
+            };
+            match fetch_json!(<ReactOk>, api_client, request) {
+                Ok(res) => update_reactions(&post_manager, post_id, res),
+                Err(e) => toasty!(toaster => error: format!("Failed to react to post: {e}")),
+            }
+        }
+    );
+
+    let reactions = post_manager
+        .read()
+        .get(post_id)
+        .map(|post| post.emoji_reactions.clone())
+        .unwrap_or_default();
+
+    let reactions_el = reactions.into_iter().map(move |reaction| {
+        let selected = maybe_class!("font-bold", reaction.reacted);
+        let label = format!("{} {}", reaction.emoji.as_ref(), reaction.count);
+        let emoji = reaction.emoji;
+        rsx! {
+            button {
+                class: "px-2 rounded-full border {selected}",
+                r#type: "button",
+                onclick: move |_| emoji_onclick(emoji.clone()),
+                "{label}"
+            }
+        }
+    });
+
+    let picker_el = picker_opened.get().then(|| {
+        let emoji_el = REACTION_EMOJI.iter().filter_map(move |&emoji| {
+            let emoji = Emoji::new(emoji).ok()?;
+            let label = emoji.as_ref().to_string();
+            Some(rsx! {
+                button {
+                    class: "px-1",
+                    r#type: "button",
+                    onclick: move |_| {
+                        emoji_onclick(emoji.clone());
+                        picker_opened.set(false);
+                    },
+                    "{label}"
+                }
+            })
+        });
+        rsx! { div { class: "flex gap-1", emoji_el } }
+    });
+
+    cx.render(rsx! {
+        div { class: "flex flex-wrap items-center gap-2 mt-2",
+            reactions_el,
+            button {
+                class: "px-2 rounded-full border opacity-70",
+                r#type: "button",
+                title: "Add a reaction",
+                onclick: move |_| picker_opened.set(!picker_opened.get()),
+                "+"
+            }
+            picker_el
+        }
+    })
+}
+
 #[inline_props]
 pub fn Bookmark(cx: Scope, post_id: PostId, bookmarked: bool) -> Element {
     let post_manager = use_post_manager(cx);
@@ -199,11 +302,11 @@ pub fn Comment(cx: Scope, opened: UseState<bool>) -> Element {
 }
 
 #[inline_props]
-pub fn QuickRespondBox(cx: Scope, opened: UseState<bool>) -> Element {
+pub fn QuickRespondBox(cx: Scope, post_id: PostId, opened: UseState<bool>) -> Element {
     let element = match *opened.get() {
         true => {
             to_owned![opened];
-            Some(rsx! { QuickRespond { opened: opened } })
+            Some(rsx! { QuickRespond { post_id: *post_id, opened: opened } })
         }
         false => None,
     };
@@ -211,6 +314,134 @@ pub fn QuickRespondBox(cx: Scope, opened: UseState<bool>) -> Element {
     cx.render(rsx! {element})
 }
 
+#[inline_props]
+pub fn EditChat(cx: Scope, post_id: PostId, opened: UseState<bool>) -> Element {
+    let api_client = ApiClient::global();
+    let toaster = use_toaster(cx);
+    let post_manager = use_post_manager(cx);
+
+    let original = match post_manager.read().get(post_id).map(|post| &post.content) {
+        Some(Content::Chat(chat)) => Some(chat.clone()),
+        _ => None,
+    };
+    let message = use_state(cx, || {
+        original
+            .as_ref()
+            .map(|chat| chat.message.as_ref().to_string())
+            .unwrap_or_default()
+    });
+    let Some(original) = original else {
+        return None;
+    };
+
+    let form_onsubmit = async_handler!(
+        &cx,
+        [
+            api_client,
+            toaster,
+            post_manager,
+            post_id,
+            message,
+            opened,
+            original
+        ],
+        move |_| async move {
+            use uchat_domain::post::Message;
+            use uchat_endpoint::post::endpoint::{EditPost, EditPostOk};
+            use uchat_endpoint::post::types::Chat;
+
+            let Ok(new_message) = Message::new(message.get()) else {
+                return;
+            };
+            let request = EditPost {
+                post_id,
+                content: Chat {
+                    headline: original.headline,
+                    message: new_message,
+                }
+                .into(),
+            };
+            match fetch_json!(<EditPostOk>, api_client, request) {
+                Ok(res) => {
+                    post_manager
+                        .write()
+                        .update(post_id, |post| *post = res.post.clone());
+                    toasty!(toaster => success: "Post edited!", 3);
+                    opened.set(false);
+                }
+                Err(e) => toasty!(toaster => error: format!("Failed to edit post: {e}")),
+            }
+        }
+    );
+
+    cx.render(rsx! {
+        form { onsubmit: form_onsubmit, prevent_default: "onsubmit",
+            MessageInput {
+                message: message,
+                on_input: move |ev: FormEvent| {
+                    message.set(ev.value.clone());
+                }
+            }
+            div { class: "w-full flex justify-end mt-2",
+                Button::<fn()> { r#type: BtnTypes::Submit, "Save" }
+            }
+        }
+    })
+}
+
+/// Actions only available to the author of a post
+#[inline_props]
+pub fn AuthorActions(cx: Scope, post_id: PostId) -> Element {
+    let api_client = ApiClient::global();
+    let toaster = use_toaster(cx);
+    let post_manager = use_post_manager(cx);
+    let local_profile = use_local_profile(cx);
+    let edit_opened = use_state(cx, || false);
+
+    let (is_author, is_chat) = match post_manager.read().get(post_id) {
+        Some(post) => (
+            local_profile.read().user_id == Some(post.by_user.id),
+            matches!(post.content, Content::Chat(_)),
+        ),
+        None => (false, false),
+    };
+    if !is_author {
+        return None;
+    }
+
+    let delete_onclick = async_handler!(
+        &cx,
+        [api_client, toaster, post_manager, post_id],
+        move |_| async move {
+            use uchat_endpoint::post::endpoint::{DeletePost, DeletePostOk};
+
+            let request = DeletePost { post_id };
+            match fetch_json!(<DeletePostOk>, api_client, request) {
+                Ok(_) => {
+                    post_manager.write().remove(&post_id);
+                    toasty!(toaster => success: "Post deleted", 3);
+                }
+                Err(e) => toasty!(toaster => error: format!("Failed to delete post: {e}")),
+            }
+        }
+    );
+
+    let edit_el = match (is_chat, *edit_opened.get()) {
+        (true, true) => rsx! { EditChat { post_id: *post_id, opened: edit_opened.clone() } },
+        _ => rsx! {""},
+    };
+
+    cx.render(rsx! {
+        div { class: "flex justify-end gap-4 text-sm opacity-70",
+            is_chat.then(|| rsx! {
+                a { class: "link", onclick: move |_| edit_opened.set(!edit_opened.get()), "Edit" }
+            }),
+            a { class: "link", onclick: delete_onclick, "Delete" }
+        }
+        edit_el
+    })
+}
+
 #[inline_props]
 pub fn ActionBar(cx: Scope, post_id: PostId) -> Element {
     let post_manager = use_post_manager(cx);
@@ -234,6 +465,8 @@ pub fn ActionBar(cx: Scope, post_id: PostId) -> Element {
             }
             Comment { opened: quick_respond_opened.clone() }
         }
-        QuickRespondBox { opened: quick_respond_opened }
+        EmojiReactions { post_id: this_post_id }
+        QuickRespondBox { post_id: this_post_id, opened: quick_respond_opened }
+        AuthorActions { post_id: this_post_id }
     })
 }
@@ -7,17 +7,18 @@ use dioxus::prelude::*;
 use itertools::Itertools;
 use uchat_domain::ids::{PollChoiceId, PostId};
 use uchat_endpoint::post::types::{
-    Chat as EndpointChat, Content as EndpointContent, Image as EndpointImage, ImageKind,
-    Poll as EndpointPoll, PublicPost, VoteCast,
+    Chat as EndpointChat, Content as EndpointContent, Gallery as EndpointGallery, GalleryImage,
+    Image as EndpointImage, ImageKind, Poll as EndpointPoll, PublicPost, VoteCast,
 };
 
 #[inline_props]
 pub fn Image<'a>(cx: Scope<'a>, content: &'a EndpointImage) -> Element {
-    let url = if let ImageKind::Url(url) = &content.kind {
-        url
+    let src_set = if let ImageKind::Url(src_set) = &content.kind {
+        src_set
     } else {
         return cx.render(rsx! {"image not found"});
     };
+    let srcset = src_set.srcset();
 
     let caption_el = content.caption.as_ref().map(|caption| {
         rsx! {
@@ -28,11 +29,95 @@ pub fn Image<'a>(cx: Scope<'a>, content: &'a EndpointImage) -> Element {
     cx.render(rsx! {
         figure { class: "flex flex-col gap-2",
             caption_el,
-            img { class: "w-full object-contain max-h-[80vh]", src: "{url}" }
+            img { class: "w-full object-contain max-h-[80vh]", src: "{src_set.src}", srcset: "{srcset}" }
         }
     })
 }
 
+fn alt_text(image: &GalleryImage) -> &str {
+    image.alt.as_ref().map_or("", |alt| alt.as_ref())
+}
+
+#[inline_props]
+pub fn Gallery<'a>(cx: Scope<'a>, content: &'a EndpointGallery) -> Element {
+    // ? Index of the image opened in the lightbox
+    let opened = use_state(cx, || None::<usize>);
+    let count = content.images.len();
+
+    let grid_cols = if count == 1 {
+        "grid-cols-1"
+    } else {
+        "grid-cols-2"
+    };
+
+    let images_el = content.images.iter().enumerate().map(|(i, image)| {
+        let ImageKind::Url(src_set) = &image.kind else {
+            return rsx! { div { key: "{i}", "image not found" } };
+        };
+        let srcset = src_set.srcset();
+        let alt = alt_text(image);
+
+        rsx! {
+            img {
+                key: "{i}",
+                class: "w-full aspect-square object-cover rounded cursor-pointer",
+                src: "{src_set.src}",
+                srcset: "{srcset}",
+                alt: "{alt}",
+                title: "{alt}",
+                onclick: move |_| opened.set(Some(i))
+            }
+        }
+    });
+
+    let lightbox_el = opened.get().and_then(|i| {
+        let image = content.images.get(i)?;
+        let ImageKind::Url(src_set) = &image.kind else {
+            return None;
+        };
+        let alt = alt_text(image);
+        let (prev, next) = ((i + count - 1) % count, (i + 1) % count);
+
+        Some(rsx! {
+            div {
+                class: "fixed top-0 left-0 w-full h-full bg-black opacity-80 z-[99]",
+                onclick: move |_| opened.set(None)
+            }
+            figure { class: "fixed top-0 left-0 w-full h-full z-[100] flex flex-col items-center justify-center gap-2 pointer-events-none",
+                img {
+                    class: "max-w-[90vw] max-h-[80vh] object-contain pointer-events-auto cursor-pointer",
+                    src: "{src_set.src}",
+                    alt: "{alt}",
+                    onclick: move |_| opened.set(None)
+                }
+                figcaption { class: "text-white", em { "{alt}" } }
+                div { class: "flex items-center gap-4 text-white pointer-events-auto",
+                    button {
+                        class: "px-2",
+                        r#type: "button",
+                        title: "Previous image",
+                        onclick: move |_| opened.set(Some(prev)),
+                        "<"
+                    }
+                    span { "{i + 1}/{count}" }
+                    button {
+                        class: "px-2",
+                        r#type: "button",
+                        title: "Next image",
+                        onclick: move |_| opened.set(Some(next)),
+                        ">"
+                    }
+                }
+            }
+        })
+    });
+
+    cx.render(rsx! {
+        div { class: "grid {grid_cols} gap-1", images_el }
+        lightbox_el
+    })
+}
+
 #[inline_props]
 pub fn Chat<'a>(cx: Scope<'a>, content: &'a EndpointChat) -> Element {
     let headline_el = content.headline.as_ref().map(|headline| {
@@ -130,6 +215,7 @@ pub fn Content<'a>(cx: Scope<'a>, post: &'a PublicPost) -> Element {
                 EndpointContent::Chat(content) => rsx! {Chat { content: content }},
                 EndpointContent::Image(content) => rsx! {Image { content: content }},
                 EndpointContent::Poll(content) => rsx! {Poll { post_id: post.id, content: content }},
+                EndpointContent::Gallery(content) => rsx! {Gallery { content: content }},
             }
         }
     })
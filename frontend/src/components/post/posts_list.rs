@@ -1,10 +1,93 @@
 #![allow(non_snake_case)]
 
-use crate::prelude::*;
+use crate::{prelude::*, util::RequestError};
 use dioxus::prelude::*;
+use futures::Future;
+use uchat_endpoint::post::types::{PostCursor, PublicPost};
 
 use super::use_post_manager;
 
+/// Where the next page of a feed starts
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum FeedCursor {
+    #[default]
+    Start,
+    Next(PostCursor),
+    End,
+}
+
+impl From<Option<PostCursor>> for FeedCursor {
+    fn from(next_cursor: Option<PostCursor>) -> Self {
+        match next_cursor {
+            Some(cursor) => Self::Next(cursor),
+            None => Self::End,
+        }
+    }
+}
+
+struct Feed<K> {
+    key: K,
+    cursor: FeedCursor,
+    // ? Incremented to request a new page
+    request: usize,
+}
+
+/// Loads a feed into the post manager, fetching the next page whenever the user scrolls to the bottom
+///
+/// NOTE Changing `key` restarts the feed from the first page, e.g. when viewing another profile
+pub fn use_feed<K, F, Fut>(cx: &ScopeState, key: K, fetch: F)
+where
+    K: Clone + PartialEq + 'static,
+    F: FnOnce(Option<PostCursor>) -> Fut + 'static,
+    Fut: Future<Output = Result<(Vec<PublicPost>, Option<PostCursor>), RequestError>> + 'static,
+{
+    let post_manager = use_post_manager(cx);
+    let toaster = use_toaster(cx);
+    let feed = use_ref(cx, || Feed {
+        key: key.clone(),
+        cursor: FeedCursor::Start,
+        request: 0,
+    });
+
+    if feed.read().key != key {
+        feed.with_mut(|feed| {
+            feed.key = key;
+            feed.cursor = FeedCursor::Start;
+            feed.request += 1;
+        });
+    }
+
+    {
+        to_owned![feed];
+        crate::util::scroll::use_scroll_bottom(cx, move || {
+            if matches!(feed.read().cursor, FeedCursor::Next(_)) {
+                feed.with_mut(|feed| feed.request += 1);
+            }
+        });
+    }
+
+    let request = feed.read().request;
+    to_owned![post_manager, toaster, feed];
+    use_future(cx, (&request,), move |_| async move {
+        let cursor = match feed.read().cursor.clone() {
+            FeedCursor::Start => {
+                post_manager.write().clear();
+                None
+            }
+            FeedCursor::Next(cursor) => Some(cursor),
+            FeedCursor::End => return,
+        };
+
+        match fetch(cursor).await {
+            Ok((posts, next_cursor)) => {
+                post_manager.write().append(posts.into_iter());
+                feed.with_mut(|feed| feed.cursor = next_cursor.into());
+            }
+            Err(e) => toasty!(toaster => error: format!("Failed to retrieve posts: {e}")),
+        }
+    });
+}
+
 #[inline_props]
 pub fn PostsList(cx: Scope, empty_message: String) -> Element {
     let post_manager = use_post_manager(cx);
@@ -2,7 +2,7 @@
 
 use crate::{prelude::*, toasty};
 use dioxus::prelude::*;
-use uchat_domain::post::Message;
+use uchat_domain::{ids::PostId, post::Message};
 
 fn can_submit(message: &str) -> bool {
     message.len() <= Message::MAX_CHARS && !message.is_empty()
@@ -33,7 +33,7 @@ pub fn MessageInput<'a>(
 }
 
 #[inline_props]
-pub fn QuickRespond(cx: Scope, opened: UseState<bool>) -> Element {
+pub fn QuickRespond(cx: Scope, post_id: PostId, opened: UseState<bool>) -> Element {
     let api_client = ApiClient::global();
     let toaster = use_toaster(cx);
 
@@ -41,7 +41,7 @@ pub fn QuickRespond(cx: Scope, opened: UseState<bool>) -> Element {
 
     let form_onsubmit = async_handler!(
         &cx,
-        [toaster, api_client, message, opened],
+        [toaster, api_client, message, opened, post_id],
         move |_| async move {
             use uchat_domain::post::Message;
             use uchat_endpoint::post::endpoint::{NewPost, NewPostOk};
@@ -53,7 +53,10 @@ pub fn QuickRespond(cx: Scope, opened: UseState<bool>) -> Element {
                     message: Message::new(message.get()).unwrap(),
                 }
                 .into(),
-                options: NewPostOptions::default(),
+                options: NewPostOptions {
+                    reply_to: Some(post_id),
+                    ..Default::default()
+                },
             };
 
             let response = fetch_json!(<NewPostOk>, api_client, request);
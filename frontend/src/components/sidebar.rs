@@ -28,10 +28,35 @@ impl SidebarManager {
 }
 
 pub fn Sidebar(cx: Scope) -> Element {
+    let api_client = ApiClient::global();
     let router = use_router(cx);
+    let toaster = use_toaster(cx);
     let sidebar = use_sidebar(cx);
     let local_profile = use_local_profile(cx);
 
+    let logout = move |all_devices: bool| {
+        to_owned![api_client, router, toaster, sidebar, local_profile];
+        cx.spawn(async move {
+            use uchat_endpoint::user::endpoint::{Logout, LogoutAll, LogoutAllOk, LogoutOk};
+
+            let response = if all_devices {
+                fetch_json!(<LogoutAllOk>, api_client, LogoutAll).map(|_| ())
+            } else {
+                fetch_json!(<LogoutOk>, api_client, Logout).map(|_| ())
+            };
+            // ? The server clears the session cookies, the local state is cleared regardless since it may have already expired
+            if let Err(e) = response {
+                toasty!(toaster => error: format!("Failed to logout: {e}"));
+            }
+
+            crate::util::cookie::clear_logged_in();
+            local_profile.write().user_id = None;
+            local_profile.write().image = None;
+            sidebar.write().close();
+            router.navigate_to(page::ACCOUNT_LOGIN);
+        });
+    };
+
     let sidebar_width = if sidebar.read().is_open() {
         "w-[var(--sidebar-width)]"
     } else {
@@ -91,16 +116,45 @@ pub fn Sidebar(cx: Scope) -> Element {
             a {
                 class: "sidebar-navlink",
                 onclick: move |_| {
-                    use chrono::Utc;
-                    use uchat_domain::ids::SessionId;
-                    crate::util::cookie::set_session("".to_string(), SessionId::new(), Utc::now());
-                    local_profile.write().user_id = None;
-                    local_profile.write().image = None;
                     sidebar.write().close();
-                    router.navigate_to(page::ACCOUNT_LOGIN);
+                    router.navigate_to(page::MESSAGES);
                 },
+                "Messages"
+            }
+            a {
+                class: "sidebar-navlink",
+                onclick: move |_| {
+                    sidebar.write().close();
+                    router.navigate_to(page::ACCOUNT_SESSIONS);
+                },
+                "Sessions"
+            }
+            a {
+                class: "sidebar-navlink",
+                onclick: move |_| {
+                    sidebar.write().close();
+                    router.navigate_to(page::ACCOUNT_TWO_FACTOR);
+                },
+                "Two-Factor Authentication"
+            }
+            a {
+                class: "sidebar-navlink",
+                onclick: move |_| {
+                    sidebar.write().close();
+                    router.navigate_to(page::ACCOUNT_ACCESS_TOKENS);
+                },
+                "Access Tokens"
+            }
+            a {
+                class: "sidebar-navlink",
+                onclick: move |_| logout(false),
                 "Logout"
             }
+            a {
+                class: "sidebar-navlink",
+                onclick: move |_| logout(true),
+                "Logout of all devices"
+            }
         }
     })
 }
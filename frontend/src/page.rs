@@ -1,36 +1,79 @@
+pub mod access_tokens;
+pub mod confirm_email;
+pub mod conversation;
 pub mod edit_profile;
 pub mod home;
+pub mod inbox;
 pub mod login;
 pub mod new_post;
+pub mod password_reset;
 pub mod register;
+pub mod sessions;
 pub mod trending;
+pub mod two_factor;
+pub mod view_post;
 pub mod view_profile;
 
+pub use access_tokens::AccessTokens;
+pub use confirm_email::ConfirmEmail;
+pub use conversation::Conversation;
 pub use edit_profile::EditProfile;
-pub use home::{Bookmarked, Home, Liked};
+pub use home::{Bookmarked, Home, Liked, Scheduled};
+pub use inbox::Inbox;
 pub use login::Login;
 pub use new_post::*;
+pub use password_reset::{ForgotPassword, ResetPassword};
 pub use register::Register;
 pub use route::*;
+pub use sessions::Sessions;
 pub use trending::Trending;
+pub use two_factor::TwoFactor;
+pub use view_post::ViewPost;
 pub use view_profile::ViewProfile;
 
 pub mod route {
-    use uchat_domain::ids::UserId;
+    use uchat_domain::ids::{PostId, UserId};
 
     pub const ACCOUNT_REGISTER: &str = "/account/register";
     pub const ACCOUNT_LOGIN: &str = "/account/login";
+    pub const ACCOUNT_SESSIONS: &str = "/account/sessions";
+    pub const ACCOUNT_CONFIRM_EMAIL: &str = "/account/confirm_email/:token";
+    pub const ACCOUNT_FORGOT_PASSWORD: &str = "/account/forgot_password";
+    pub const ACCOUNT_RESET_PASSWORD: &str = "/account/reset_password/:token";
+    pub const ACCOUNT_TWO_FACTOR: &str = "/account/two_factor";
+    pub const ACCOUNT_ACCESS_TOKENS: &str = "/account/access_tokens";
     pub const HOME: &str = "/home";
     pub const POST_NEW_CHAT: &str = "/post/new_chat";
     pub const POSTS_TRENDING: &str = "/posts/trending";
     pub const POST_NEW_IMAGE: &str = "/post/new_image";
     pub const POST_NEW_POLL: &str = "/post/new_poll";
+    pub const POST_VIEW: &str = "/post/view/:id";
     pub const HOME_LIKED: &str = "/home/liked";
     pub const HOME_BOOKMARKED: &str = "/home/bookmarked";
+    pub const HOME_SCHEDULED: &str = "/home/scheduled";
     pub const PROFILE_EDIT: &str = "/profile/edit";
     pub const PROFILE_VIEW: &str = "/profile/view/:id";
+    pub const MESSAGES: &str = "/messages";
+    pub const MESSAGES_VIEW: &str = "/messages/view/:id";
+
+    /// Pages that can be opened without being logged in, such as the ones linked to by email
+    pub fn is_public(path: &str) -> bool {
+        let confirm_email_root = ACCOUNT_CONFIRM_EMAIL.trim_end_matches(":token");
+        let reset_password_root = ACCOUNT_RESET_PASSWORD.trim_end_matches(":token");
+        path == ACCOUNT_FORGOT_PASSWORD
+            || path.starts_with(confirm_email_root)
+            || path.starts_with(reset_password_root)
+    }
 
     pub fn profile_view(user_id: UserId) -> String {
         PROFILE_VIEW.replace(":id", &user_id.to_string())
     }
+
+    pub fn post_view(post_id: PostId) -> String {
+        POST_VIEW.replace(":id", &post_id.to_string())
+    }
+
+    pub fn messages_view(user_id: UserId) -> String {
+        MESSAGES_VIEW.replace(":id", &user_id.to_string())
+    }
 }
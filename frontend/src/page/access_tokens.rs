@@ -0,0 +1,190 @@
+#![allow(non_snake_case)]
+
+use crate::{prelude::*, toasty};
+use dioxus::prelude::*;
+use uchat_domain::{
+    access_token::{Scope as TokenScope, TokenName},
+    ids::AccessTokenId,
+    UserFacingError,
+};
+use uchat_endpoint::user::types::PublicAccessToken;
+
+fn scope_description(scope: TokenScope) -> &'static str {
+    match scope {
+        TokenScope::Read => "Read posts, profiles and messages",
+        TokenScope::Post => "Create, edit and react to posts",
+        TokenScope::Follow => "Follow and unfollow users",
+    }
+}
+
+pub fn AccessTokens(cx: Scope) -> Element {
+    let api_client = ApiClient::global();
+    let router = use_router(cx);
+    let toaster = use_toaster(cx);
+    let tokens = use_ref(cx, Vec::<PublicAccessToken>::new);
+    let name = use_state(cx, String::new);
+    let scopes = use_ref(cx, || vec![TokenScope::Read]);
+    // ? The secret is only returned when the token is created, so it's kept until the page is left
+    let new_secret = use_state(cx, || None::<String>);
+
+    let _fetch_tokens = {
+        to_owned![api_client, toaster, tokens];
+        use_future(cx, (), |_| async move {
+            use uchat_endpoint::user::endpoint::{AccessTokens, AccessTokensOk};
+
+            let response = fetch_json!(<AccessTokensOk>, api_client, AccessTokens);
+            match response {
+                Ok(res) => tokens.with_mut(|t| *t = res.tokens),
+                Err(e) => {
+                    toasty!(toaster => error: format!("Failed to retrieve access tokens: {e}"))
+                }
+            }
+        })
+    };
+
+    let name_error = match TokenName::new(name.get()) {
+        Err(e) if !name.get().is_empty() => Some(e.formatted_error()),
+        _ => None,
+    };
+    let can_submit = !name.get().is_empty() && name_error.is_none() && !scopes.read().is_empty();
+
+    let form_onsubmit = async_handler!(
+        &cx,
+        [api_client, toaster, tokens, name, scopes, new_secret],
+        move |_| async move {
+            use uchat_endpoint::user::endpoint::{CreateAccessToken, CreateAccessTokenOk};
+
+            let Ok(token_name) = TokenName::new(name.get()) else {
+                return;
+            };
+            let request = CreateAccessToken {
+                name: token_name,
+                scopes: scopes.read().clone(),
+            };
+            match fetch_json!(<CreateAccessTokenOk>, api_client, request) {
+                Ok(res) => {
+                    tokens.with_mut(|t| t.insert(0, res.token));
+                    new_secret.set(Some(res.secret));
+                    name.set(String::new());
+                }
+                Err(e) => toasty!(toaster => error: format!("Failed to create access token: {e}")),
+            }
+        }
+    );
+
+    let revoke = move |id: AccessTokenId| {
+        to_owned![api_client, toaster, tokens];
+        cx.spawn(async move {
+            use uchat_endpoint::user::endpoint::{RevokeAccessToken, RevokeAccessTokenOk};
+
+            let request = RevokeAccessToken { id };
+            match fetch_json!(<RevokeAccessTokenOk>, api_client, request) {
+                Ok(_) => {
+                    tokens.with_mut(|t| t.retain(|token| token.id != id));
+                    toasty!(toaster => success: "Access token revoked", 3);
+                }
+                Err(e) => toasty!(toaster => error: format!("Failed to revoke access token: {e}")),
+            }
+        });
+    };
+
+    let scopes_el = TokenScope::ALL.into_iter().map(|scope| {
+        let checked = scopes.read().contains(&scope);
+        let description = scope_description(scope);
+        rsx! {
+            label {
+                key: "{scope}",
+                class: "flex gap-2 items-center",
+                input {
+                    r#type: "checkbox",
+                    checked: "{checked}",
+                    onchange: move |_| scopes.with_mut(|s| {
+                        if s.contains(&scope) {
+                            s.retain(|selected| *selected != scope);
+                        } else {
+                            s.push(scope);
+                        }
+                    })
+                }
+                span { class: "font-bold", "{scope}" }
+                span { class: "font-light text-sm", "{description}" }
+            }
+        }
+    });
+
+    let new_secret_el = new_secret.get().clone().map(|secret| {
+        rsx! {
+            div { class: "flex flex-col gap-1",
+                p { class: "font-bold", "Copy this token now, it won't be shown again:" }
+                code { class: "break-all", "{secret}" }
+            }
+        }
+    });
+
+    let tokens_el = tokens
+        .read()
+        .clone()
+        .into_iter()
+        .map(|token| {
+            let id = token.id;
+            let token_scopes = token
+                .scopes
+                .iter()
+                .map(TokenScope::as_str)
+                .collect::<Vec<_>>()
+                .join(", ");
+            let created_at = token.created_at.format("%Y-%m-%d %H:%M").to_string();
+            let last_used = token
+                .last_used_at
+                .map(|at| format!("last used {}", at.format("%Y-%m-%d %H:%M")))
+                .unwrap_or_else(|| "never used".to_string());
+
+            rsx! {
+                div {
+                    key: "{id.to_string()}",
+                    class: "flex flex-col gap-1 mb-4",
+                    div { class: "flex justify-between items-center gap-2",
+                        div { class: "truncate", "{token.name}" }
+                        Button {
+                            r#type: BtnTypes::Button,
+                            handle_onclick: move || revoke(id),
+                            "Revoke"
+                        }
+                    }
+                    div { class: "font-light text-sm", "Scopes: {token_scopes}" }
+                    div { class: "font-light text-sm", "Created {created_at}, {last_used}" }
+                    hr {}
+                }
+            }
+        })
+        .collect::<Vec<LazyNodes>>();
+
+    cx.render(rsx! {
+        AppBar { title: "Access Tokens",
+            AppBarImgButton {
+                handle_onclick: move |_| router.pop_route(),
+                img: "/static/icons/icon-back.svg",
+                label: "Back",
+                title: "Go to the previous page"
+            }
+        }
+        form { class: "flex flex-col gap-3 mb-6", prevent_default: "onsubmit", onsubmit: form_onsubmit,
+            p { "Bots and scripts can use a token with the `Authorization: Bearer` header, limited to the scopes picked here." }
+            div { class: "flex flex-col",
+                label { r#for: "token-name", "Name" }
+                input {
+                    id: "token-name",
+                    class: "input-field",
+                    placeholder: "What the token is for",
+                    value: "{name}",
+                    oninput: move |ev| name.set(ev.value.clone())
+                }
+                name_error.map(|e| rsx! { div { class: "err-text-color", "{e}" } })
+            }
+            scopes_el
+            Button::<fn()> { r#type: BtnTypes::Submit, disabled: !can_submit, "Create Token" }
+            new_secret_el
+        }
+        tokens_el.into_iter()
+    })
+}
@@ -0,0 +1,50 @@
+#![allow(non_snake_case)]
+
+use crate::{prelude::*, toasty};
+use dioxus::prelude::*;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Confirmation {
+    Pending,
+    Confirmed(String),
+    Failed(String),
+}
+
+pub fn ConfirmEmail(cx: Scope) -> Element {
+    let api_client = ApiClient::global();
+    let route = use_route(cx);
+    let toaster = use_toaster(cx);
+    let confirmation = use_state(cx, || Confirmation::Pending);
+
+    let token = route.segment("token").unwrap_or_default().to_owned();
+
+    let _confirm_email = {
+        to_owned![api_client, toaster, confirmation];
+        use_future(cx, (), |_| async move {
+            use uchat_endpoint::user::endpoint::{ConfirmEmail, ConfirmEmailOk};
+
+            let request = ConfirmEmail { token };
+            match fetch_json!(<ConfirmEmailOk>, api_client, request) {
+                Ok(res) => {
+                    toasty!(toaster => success: "Email confirmed!", 3);
+                    confirmation.set(Confirmation::Confirmed(res.email));
+                }
+                Err(e) => confirmation.set(Confirmation::Failed(e.to_string())),
+            }
+        })
+    };
+
+    let status_el = match confirmation.get() {
+        Confirmation::Pending => rsx! { div { "Confirming your email..." } },
+        Confirmation::Confirmed(email) => rsx! { div { "{email} is now confirmed." } },
+        Confirmation::Failed(e) => rsx! {
+            div { class: "err-text-color", "Failed to confirm your email: {e}" }
+            div { "You can request a new link from the Edit Profile page." }
+        },
+    };
+
+    cx.render(rsx! {
+        AppBar { title: "Confirm Email" }
+        div { class: "flex flex-col gap-3 text-center", status_el }
+    })
+}
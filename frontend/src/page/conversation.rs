@@ -0,0 +1,153 @@
+#![allow(non_snake_case)]
+
+use std::str::FromStr;
+
+use crate::{prelude::*, toasty};
+use dioxus::prelude::*;
+use uchat_domain::ids::UserId;
+use uchat_endpoint::{message::types::DirectMessage, user::types::PublicUserProfile};
+
+#[derive(Clone, Default, Debug)]
+pub struct PageState {
+    pub with_user: Option<PublicUserProfile>,
+    pub messages: Vec<DirectMessage>,
+    pub message: String,
+}
+
+impl PageState {
+    pub fn can_submit(&self) -> bool {
+        use uchat_domain::post::Message;
+
+        Message::new(&self.message).is_ok()
+    }
+}
+
+#[inline_props]
+pub fn MessageBubble(cx: Scope, message: DirectMessage, is_mine: bool) -> Element {
+    let alignment = if *is_mine {
+        "self-end bg-slate-600 text-white"
+    } else {
+        "self-start bg-slate-200"
+    };
+    let time_sent = message.time_sent.format("%Y-%m-%d %H:%M");
+
+    cx.render(rsx! {
+        div { class: "flex flex-col max-w-[80%] rounded-md px-3 py-2 {alignment}",
+            span { "{message.message.as_ref()}" }
+            span { class: "text-xs font-light text-right", "{time_sent}" }
+        }
+    })
+}
+
+pub fn Conversation(cx: Scope) -> Element {
+    let route = use_route(cx);
+    let with_user = route.segment("id").and_then(|id| UserId::from_str(id).ok());
+
+    let Some(with_user) = with_user else {
+        return cx.render(rsx! {
+            "User not found!"
+        });
+    };
+
+    let api_client = ApiClient::global();
+    let router = use_router(cx);
+    let toaster = use_toaster(cx);
+    let local_profile = use_local_profile(cx);
+    let page_state = use_ref(cx, PageState::default);
+
+    use_effect(cx, (&with_user,), |(with_user,)| {
+        to_owned![api_client, toaster, page_state];
+        async move {
+            use uchat_endpoint::message::endpoint::{ViewConversation, ViewConversationOk};
+
+            page_state.with_mut(|state| *state = PageState::default());
+            let response =
+                fetch_json!(<ViewConversationOk>, api_client, ViewConversation { with_user });
+            match response {
+                Ok(res) => page_state.with_mut(|state| {
+                    state.with_user = Some(res.with_user);
+                    state.messages = res.messages;
+                }),
+                Err(e) => {
+                    toasty!(toaster => error: format!("Failed to retrieve messages: {e}"))
+                }
+            }
+        }
+    });
+
+    let form_onsubmit = async_handler!(
+        &cx,
+        [api_client, toaster, page_state],
+        move |_| async move {
+            use uchat_domain::post::Message;
+            use uchat_endpoint::message::endpoint::{SendDirectMessage, SendDirectMessageOk};
+
+            let Ok(message) = Message::new(&page_state.read().message) else {
+                return;
+            };
+            let request = SendDirectMessage {
+                to: with_user,
+                message,
+            };
+
+            let response = fetch_json!(<SendDirectMessageOk>, api_client, request);
+            match response {
+                Ok(res) => page_state.with_mut(|state| {
+                    state.messages.push(res.message);
+                    state.message.clear();
+                }),
+                Err(e) => toasty!(toaster => error: format!("Failed to send message: {e}")),
+            }
+        }
+    );
+
+    let my_id = local_profile.read().user_id;
+    let messages_el = page_state
+        .read()
+        .messages
+        .clone()
+        .into_iter()
+        .map(|message| {
+            let is_mine = Some(message.from) == my_id;
+            rsx! {
+                MessageBubble { key: "{message.id.to_string()}", message: message, is_mine: is_mine }
+            }
+        })
+        .collect::<Vec<LazyNodes>>();
+
+    let title = match &page_state.read().with_user {
+        Some(user) => user
+            .display_name
+            .as_ref()
+            .map(|name| name.as_ref().to_string())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| user.handle.clone()),
+        None => "Messages".to_string(),
+    };
+
+    let is_invalid = !page_state.read().can_submit();
+
+    cx.render(rsx! {
+        AppBar { title: "{title}",
+            AppBarImgButton {
+                handle_onclick: move |_| router.pop_route(),
+                img: "/static/icons/icon-back.svg",
+                label: "Back",
+                title: "Go to the previous page"
+            }
+        }
+        div { class: "flex flex-col gap-2 mb-4", messages_el.into_iter() }
+        form { class: "flex flex-row gap-2", onsubmit: form_onsubmit, prevent_default: "onsubmit",
+            input {
+                class: "input-field",
+                id: "message",
+                placeholder: "Write a message",
+                value: "{page_state.read().message}",
+                oninput: move |ev| {
+                    page_state.with_mut(|state| state.message = ev.data.value.clone());
+                }
+            }
+            Button::<fn()> { r#type: BtnTypes::Submit, disabled: is_invalid, "Send" }
+        }
+    })
+}
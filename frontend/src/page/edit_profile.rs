@@ -21,6 +21,8 @@ pub struct PageState {
 
     display_name: String,
     email: String,
+    // ? The email the profile was loaded with, a changed email has to be confirmed again
+    confirmed_email: Option<String>,
     password: String,
     password_confirm: String,
     profile_image: Option<PreviewImageData>,
@@ -155,10 +157,41 @@ pub fn PasswordInput(cx: Scope, page_state: UseRef<PageState>) -> Element {
 pub fn EmailInput(cx: Scope, page_state: UseRef<PageState>) -> Element {
     use uchat_domain::user::Email;
 
+    let api_client = ApiClient::global();
+    let toaster = use_toaster(cx);
+
+    let resend_onclick = async_handler!(&cx, [api_client, toaster], move |_| async move {
+        use uchat_endpoint::user::endpoint::{SendEmailConfirmation, SendEmailConfirmationOk};
+
+        let response = fetch_json!(<SendEmailConfirmationOk>, api_client, SendEmailConfirmation);
+        match response {
+            Ok(_) => toasty!(toaster => success: "Confirmation link sent, check your email!", 3),
+            Err(e) => toasty!(toaster => error: format!("Failed to send confirmation link: {e}")),
+        }
+    });
+
+    let status_el = page_state.with(|state| {
+        if state.email.is_empty() {
+            None
+        } else if state.confirmed_email.as_deref() == Some(state.email.as_str()) {
+            Some(rsx! { span { class: "text-right", "verified" } })
+        } else {
+            Some(rsx! {
+                span { class: "text-right err-text-color",
+                    "not verified "
+                    a { class: "link", onclick: resend_onclick, "(resend link)" }
+                }
+            })
+        }
+    });
+
     cx.render(rsx! {
         div {
             label { r#for: "email",
-                div { class: "flex flex-row justify-between", span { "Email Address" } }
+                div { class: "flex flex-row justify-between",
+                    span { "Email Address" }
+                    status_el
+                }
             }
             input {
                 class: "input-field",
@@ -245,7 +278,8 @@ pub fn EditProfile(cx: Scope) -> Element {
             match response {
                 Ok(res) => page_state.with_mut(|state| {
                     state.display_name = res.display_name.unwrap_or_default();
-                    state.email = res.email.unwrap_or_default();
+                    state.email = res.email.clone().unwrap_or_default();
+                    state.confirmed_email = res.email.filter(|_| res.email_confirmed);
                     state.profile_image = res
                         .profile_image
                         .map(|img| PreviewImageData::Remote(img.to_string()));
@@ -262,7 +296,7 @@ pub fn EditProfile(cx: Scope) -> Element {
             use uchat_endpoint::user::endpoint::{UpdateProfile, UpdateProfileOk};
             use uchat_endpoint::Update;
             let request_data = {
-                use uchat_domain::user::Password;
+                use uchat_domain::user::{Email, Password};
                 UpdateProfile {
                     display_name: {
                         let name = page_state.with(|state| state.display_name.clone());
@@ -277,7 +311,16 @@ pub fn EditProfile(cx: Scope) -> Element {
                         if email.is_empty() {
                             Update::SetNull
                         } else {
-                            Update::Change(email)
+                            // ? The input only flags a bad email as it's typed, so it's checked again here
+                            match Email::new(email) {
+                                Ok(email) => Update::Change(email),
+                                Err(e) => {
+                                    page_state.with_mut(|state| {
+                                        state.form_errors.set("bad-email", e.formatted_error())
+                                    });
+                                    return;
+                                }
+                            }
                         }
                     },
                     password: {
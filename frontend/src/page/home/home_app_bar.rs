@@ -9,6 +9,7 @@ pub fn HomeAppBar(cx: Scope, title: String, active_page: HomePages) -> Element {
     let router = use_router(cx);
     let is_liked = *active_page == HomePages::Liked;
     let is_bookmarked = *active_page == HomePages::Bookmarked;
+    let is_scheduled = *active_page == HomePages::Scheduled;
     let is_home = *active_page == HomePages::Home;
 
     cx.render(rsx! {
@@ -29,6 +30,14 @@ pub fn HomeAppBar(cx: Scope, title: String, active_page: HomePages) -> Element {
                 disabled: is_bookmarked,
                 append_class: maybe_class!(app_bar::BUTTON_SELECTED, is_bookmarked)
             }
+            AppBarImgButton {
+                handle_onclick: move |_| router.replace_route(page::HOME_SCHEDULED, None, None),
+                img: "/static/icons/icon-scheduled.svg",
+                label: "Scheduled",
+                title: "Show scheduled posts",
+                disabled: is_scheduled,
+                append_class: maybe_class!(app_bar::BUTTON_SELECTED, is_scheduled)
+            }
             AppBarImgButton {
                 handle_onclick: move |_| router.replace_route(page::HOME, None, None),
                 img: "/static/icons/icon-home.svg",
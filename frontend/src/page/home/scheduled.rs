@@ -0,0 +1,136 @@
+#![allow(non_snake_case)]
+
+use super::HomePages;
+use crate::{
+    components::post::{posts_list::use_feed, use_post_manager},
+    page::home::home_app_bar::HomeAppBar,
+    page::new_post::schedule::{format_local_datetime, is_valid_schedule, parse_local_datetime},
+    prelude::*,
+};
+use chrono::Utc;
+use dioxus::prelude::*;
+use uchat_domain::ids::PostId;
+
+#[inline_props]
+pub fn ScheduleControls(cx: Scope, post_id: PostId) -> Element {
+    let api_client = ApiClient::global();
+    let toaster = use_toaster(cx);
+    let post_manager = use_post_manager(cx);
+
+    let schedule = {
+        let time_posted = post_manager
+            .read()
+            .get(post_id)
+            .map(|post| post.time_posted);
+        use_state(cx, || {
+            time_posted.map(format_local_datetime).unwrap_or_default()
+        })
+    };
+
+    let reschedule = move |time_posted| {
+        to_owned![api_client, toaster, post_manager, post_id];
+        cx.spawn(async move {
+            use uchat_endpoint::post::endpoint::{ReschedulePost, ReschedulePostOk};
+
+            let request = ReschedulePost {
+                post_id,
+                time_posted,
+            };
+            match fetch_json!(<ReschedulePostOk>, api_client, request) {
+                Ok(res) if res.time_posted > Utc::now() => {
+                    post_manager.write().update(post_id, |post| {
+                        post.time_posted = res.time_posted;
+                    });
+                    toasty!(toaster => success: "Post rescheduled!", 3);
+                }
+                Ok(_) => {
+                    post_manager.write().remove(&post_id);
+                    toasty!(toaster => success: "Posted!", 3);
+                }
+                Err(e) => toasty!(toaster => error: format!("Failed to reschedule post: {e}")),
+            }
+        });
+    };
+
+    let cancel_onclick = async_handler!(
+        &cx,
+        [api_client, toaster, post_manager, post_id],
+        move |_| async move {
+            use uchat_endpoint::post::endpoint::{CancelScheduledPost, CancelScheduledPostOk};
+
+            let request = CancelScheduledPost { post_id };
+            match fetch_json!(<CancelScheduledPostOk>, api_client, request) {
+                Ok(_) => {
+                    post_manager.write().remove(&post_id);
+                    toasty!(toaster => success: "Scheduled post cancelled", 3);
+                }
+                Err(e) => toasty!(toaster => error: format!("Failed to cancel post: {e}")),
+            }
+        }
+    );
+
+    let can_reschedule = !schedule.get().is_empty() && is_valid_schedule(schedule.get());
+
+    cx.render(rsx! {
+        div { class: "flex flex-col gap-2 mb-8",
+            input {
+                class: "input-field",
+                r#type: "datetime-local",
+                value: "{schedule}",
+                oninput: move |ev| schedule.set(ev.value.clone())
+            }
+            div { class: "flex justify-between",
+                Button {
+                    r#type: BtnTypes::Button,
+                    disabled: !can_reschedule,
+                    handle_onclick: move || {
+                        if let Some(time_posted) = parse_local_datetime(schedule.get()) {
+                            reschedule(time_posted);
+                        }
+                    },
+                    "Reschedule"
+                }
+                Button { r#type: BtnTypes::Button, handle_onclick: move || reschedule(Utc::now()), "Publish now" }
+                Button { r#type: BtnTypes::Button, handle_onclick: move || cancel_onclick(()), "Cancel" }
+            }
+        }
+    })
+}
+
+pub fn Scheduled(cx: Scope) -> Element {
+    let api_client = ApiClient::global();
+    let post_manager = use_post_manager(cx);
+
+    // NOTE Users only have a handful of pending posts, so they all come in a single page
+    use_feed(cx, (), move |_| async move {
+        use uchat_endpoint::post::endpoint::{ScheduledPosts, ScheduledPostsOk};
+
+        let request = ScheduledPosts;
+        fetch_json!(<ScheduledPostsOk>, api_client, request).map(|res| (res.posts, None))
+    });
+
+    let posts_el = post_manager
+        .read()
+        .posts
+        .keys()
+        .map(|&id| {
+            rsx! {
+                div {
+                    PublicPostEntry { post_id: id }
+                    ScheduleControls { post_id: id }
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let posts_el = if posts_el.is_empty() {
+        rsx! { div { class: "text-center mt-8", "You don't have any scheduled posts" } }
+    } else {
+        rsx! { posts_el.into_iter() }
+    };
+
+    cx.render(rsx! {
+        HomeAppBar { title: "Scheduled Posts".to_owned(), active_page: HomePages::Scheduled }
+        posts_el
+    })
+}
@@ -0,0 +1,93 @@
+#![allow(non_snake_case)]
+
+use crate::{prelude::*, toasty};
+use dioxus::prelude::*;
+use uchat_endpoint::{
+    app_url::user_content::{self, ImageSize},
+    message::types::Conversation,
+};
+
+pub fn Inbox(cx: Scope) -> Element {
+    let api_client = ApiClient::global();
+    let router = use_router(cx);
+    let toaster = use_toaster(cx);
+    let conversations = use_ref(cx, Vec::<Conversation>::new);
+
+    let _fetch_conversations = {
+        to_owned![api_client, toaster, conversations];
+        use_future(cx, (), |_| async move {
+            use uchat_endpoint::message::endpoint::{Conversations, ConversationsOk};
+
+            let response = fetch_json!(<ConversationsOk>, api_client, Conversations);
+            match response {
+                Ok(res) => conversations.with_mut(|c| *c = res.conversations),
+                Err(e) => {
+                    toasty!(toaster => error: format!("Failed to retrieve conversations: {e}"))
+                }
+            }
+        })
+    };
+
+    let conversations_el = conversations
+        .read()
+        .clone()
+        .into_iter()
+        .map(|conversation| {
+            let user = conversation.with_user;
+            let user_id = user.id;
+            let display_name = user
+                .display_name
+                .map(|name| name.into_inner())
+                .unwrap_or_default();
+            let profile_image = user
+                .profile_image
+                .map(|url| user_content::sized(&url, ImageSize::Avatar).to_string())
+                .unwrap_or_default();
+            let last_message = conversation.last_message.message.into_inner();
+            let time_sent = conversation
+                .last_message
+                .time_sent
+                .format("%Y-%m-%d %H:%M")
+                .to_string();
+
+            rsx! {
+                div {
+                    key: "{user_id.to_string()}",
+                    class: "grid grid-cols-[50px_1fr] gap-2 mb-4 cursor-pointer",
+                    onclick: move |_| {
+                        let route = page::messages_view(user_id);
+                        router.navigate_to(&route);
+                    },
+                    img { class: "profile-portrait", src: "{profile_image}" }
+                    div { class: "flex flex-col gap-1 overflow-hidden",
+                        div { class: "flex justify-between",
+                            div { "{display_name} " span { class: "font-light", "{user.handle}" } }
+                            div { class: "font-light text-sm", "{time_sent}" }
+                        }
+                        div { class: "truncate", "{last_message}" }
+                        hr {}
+                    }
+                }
+            }
+        })
+        .collect::<Vec<LazyNodes>>();
+
+    let empty_el = conversations.read().is_empty().then(|| {
+        rsx! {
+            div { class: "text-center", "No messages yet. Visit a profile to start a conversation!" }
+        }
+    });
+
+    cx.render(rsx! {
+        AppBar { title: "Messages",
+            AppBarImgButton {
+                handle_onclick: move |_| router.pop_route(),
+                img: "/static/icons/icon-back.svg",
+                label: "Back",
+                title: "Go to the previous page"
+            }
+        }
+        empty_el,
+        conversations_el.into_iter()
+    })
+}
@@ -3,18 +3,21 @@
 use dioxus::prelude::*;
 use dioxus_router::Link;
 use uchat_domain::{self, UserFacingError};
+use uchat_endpoint::ErrorCode;
 
 use crate::{
     components::keyed_notification_box::{KeyedNotificationBox, KeyedNotifications},
     fetch_json,
     prelude::*,
     toasty,
-    util::ApiClient,
+    util::{ApiClient, RequestError},
 };
 
 pub struct PageState {
     username: UseState<String>,
     password: UseState<String>,
+    totp_code: UseState<String>,
+    totp_challenge: UseState<Option<String>>,
     form_errors: KeyedNotifications,
     server_errors: KeyedNotifications,
 }
@@ -24,6 +27,8 @@ impl PageState {
         Self {
             username: use_state(cx, String::new).clone(),
             password: use_state(cx, String::new).clone(),
+            totp_code: use_state(cx, String::new).clone(),
+            totp_challenge: use_state(cx, || None).clone(),
             form_errors: KeyedNotifications::default(),
             server_errors: KeyedNotifications::default(),
         }
@@ -34,10 +39,36 @@ impl PageState {
             self.username.current().is_empty() || self.password.current().is_empty();
         let has_errors = self.form_errors.has_messages();
 
+        if self.totp_challenge.current().is_some() {
+            return !self.totp_code.current().trim().is_empty();
+        }
+
         !is_form_empty && !has_errors
     }
 }
 
+#[inline_props]
+pub fn TotpCodeInput<'a>(
+    cx: Scope<'a>,
+    state: UseState<String>,
+    oninput: EventHandler<'a, FormEvent>,
+) -> Element<'a> {
+    cx.render(rsx! {
+        div {
+            label { r#for: "totp-code", "Authentication Code" }
+            input {
+                id: "totp-code",
+                name: "totp-code",
+                class: "input-field",
+                placeholder: "Code from your app or a recovery code",
+                autocomplete: "one-time-code",
+                value: "{state.current()}",
+                oninput: move |ev| oninput.call(ev)
+            }
+        }
+    })
+}
+
 #[inline_props]
 pub fn UsernameInput<'a>(
     cx: Scope<'a>,
@@ -87,6 +118,22 @@ pub fn RegisterLink(cx: Scope) -> Element {
     })
 }
 
+pub fn ForgotPasswordLink(cx: Scope) -> Element {
+    cx.render(rsx! {
+        Link { class: "link text-center", to: page::ACCOUNT_FORGOT_PASSWORD, "Forgot Password?" }
+    })
+}
+
+fn login_error(e: &RequestError) -> String {
+    match e.code() {
+        Some(ErrorCode::InvalidCredentials) => "Wrong user name or password".to_string(),
+        Some(ErrorCode::TooManyRequests) => {
+            "Too many failed attempts, wait a bit before trying again".to_string()
+        }
+        _ => e.to_string(),
+    }
+}
+
 pub fn Login(cx: Scope) -> Element {
     let api_client = ApiClient::global();
     let page_state = PageState::new(cx);
@@ -100,7 +147,34 @@ pub fn Login(cx: Scope) -> Element {
         [api_client, page_state, router, toaster, local_profile],
         move |_| async move {
             // NOTE Using the `Login` here will shadow the `Login` from the upper scope
-            use uchat_endpoint::user::endpoint::{Login, LoginOk};
+            use uchat_endpoint::user::endpoint::{
+                Login, LoginOk, LoginSession, VerifyLoginTotp, VerifyLoginTotpOk,
+            };
+
+            let start_session = |session: LoginSession| {
+                crate::util::cookie::set_logged_in(session.session_expires, &session.csrf_token);
+                local_profile.write().image = session.profile_image;
+                local_profile.write().user_id = Some(session.user_id);
+                router.navigate_to(page::HOME);
+            };
+
+            let totp_challenge = page_state.with(|state| (*state.totp_challenge.current()).clone());
+            if let Some(challenge) = totp_challenge {
+                let request_data = VerifyLoginTotp {
+                    challenge,
+                    code: page_state.with(|state| state.totp_code.current().trim().to_string()),
+                };
+                match fetch_json!(<VerifyLoginTotpOk>, api_client, request_data) {
+                    Ok(res) => start_session(res.session),
+                    Err(e) => {
+                        let msg = login_error(&e);
+                        toasty!(toaster => error: format!("Failed to login: {msg}"));
+                        page_state.with_mut(|state| state.server_errors.set("login", msg));
+                    }
+                }
+                return;
+            }
+
             let request_data = {
                 use uchat_domain::{Password, Username};
                 Login {
@@ -117,25 +191,17 @@ pub fn Login(cx: Scope) -> Element {
 
             let response = fetch_json!(<LoginOk>, api_client, request_data);
             match response {
-                Ok(res) => {
-                    let LoginOk {
-                        session_expires,
-                        session_id,
-                        session_signature,
-                        ..
-                    } = res;
-                    crate::util::cookie::set_session(
-                        session_signature,
-                        session_id,
-                        session_expires,
-                    );
-                    local_profile.write().image = res.profile_image;
-                    local_profile.write().user_id = Some(res.user_id);
-                    router.navigate_to(page::HOME);
+                Ok(LoginOk::LoggedIn(session)) => start_session(session),
+                Ok(LoginOk::TotpRequired { challenge }) => {
+                    page_state.with_mut(|state| {
+                        state.server_errors.remove("login");
+                        state.totp_challenge.set(Some(challenge));
+                    });
                 }
                 Err(e) => {
-                    page_state.with_mut(|state| state.server_errors.set("login", e.to_string()));
-                    toasty!(toaster => error: format!("Failed to login: {e}"));
+                    let msg = login_error(&e);
+                    toasty!(toaster => error: format!("Failed to login: {msg}"));
+                    page_state.with_mut(|state| state.server_errors.set("login", msg));
                 }
             }
         }
@@ -161,12 +227,21 @@ pub fn Login(cx: Scope) -> Element {
         page_state.with_mut(|state| state.password.set(ev.value.clone()));
     });
 
-    cx.render(rsx! {
-        form { class: "flex flex-col gap-5", prevent_default: "onsubmit", onsubmit: form_onsubmit,
-            KeyedNotificationBox {
-                legend: "Login Errors",
-                notifications: page_state.with(|state| state.server_errors.clone())
+    let totp_code_oninput = sync_handler!([page_state], move |ev: FormEvent| {
+        page_state.with_mut(|state| state.totp_code.set(ev.value.clone()));
+    });
+
+    let needs_totp = page_state.with(|state| state.totp_challenge.current().is_some());
+
+    let credentials_el = if needs_totp {
+        rsx! {
+            TotpCodeInput {
+                state: page_state.with(|state| state.totp_code.clone()),
+                oninput: totp_code_oninput
             }
+        }
+    } else {
+        rsx! {
             UsernameInput {
                 state: page_state.with(|state| state.username.clone()),
                 oninput: username_oninput
@@ -175,7 +250,18 @@ pub fn Login(cx: Scope) -> Element {
                 state: page_state.with(|state| state.password.clone()),
                 oninput: password_oninput
             }
+        }
+    };
+
+    cx.render(rsx! {
+        form { class: "flex flex-col gap-5", prevent_default: "onsubmit", onsubmit: form_onsubmit,
+            KeyedNotificationBox {
+                legend: "Login Errors",
+                notifications: page_state.with(|state| state.server_errors.clone())
+            }
+            credentials_el
             RegisterLink {}
+            ForgotPasswordLink {}
             KeyedNotificationBox {
                 legend: "Form Errors",
                 notifications: page_state.with(|state| state.form_errors.clone())
@@ -2,6 +2,7 @@ pub mod chat;
 pub mod image;
 pub mod new_post_app_bar;
 pub mod poll;
+pub mod schedule;
 
 pub use chat::NewChat;
 pub use image::NewImage;
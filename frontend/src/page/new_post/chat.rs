@@ -1,9 +1,9 @@
 #![allow(non_snake_case)]
 
+use super::schedule::{is_valid_schedule, new_post_options, ScheduleInput};
 use crate::{fetch_json, page::new_post_app_bar::NewPostAppBar, prelude::*, ret_if, toasty};
 use dioxus::prelude::*;
 use serde::{Deserialize, Serialize};
-use uchat_endpoint::post::types::NewPostOptions;
 
 #[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub struct PageState {
@@ -87,14 +87,15 @@ pub fn HeadlineInput(cx: Scope, page_state: UseRef<PageState>) -> Element {
 
 pub fn NewChat(cx: Scope) -> Element {
     let page_state = use_ref(cx, PageState::default);
-    let is_invalid = !page_state.read().can_submit();
+    let schedule = use_state(cx, String::new);
+    let is_invalid = !page_state.read().can_submit() || !is_valid_schedule(schedule.get());
     let toaster = use_toaster(cx);
     let router = use_router(cx);
     let api_client = ApiClient::global();
 
     let form_onsubmit = async_handler!(
         &cx,
-        [toaster, api_client, page_state, router],
+        [toaster, api_client, page_state, router, schedule],
         move |_| async move {
             use uchat_domain::post::{Headline, Message};
             use uchat_endpoint::post::endpoint::{NewPost, NewPostOk};
@@ -115,7 +116,7 @@ pub fn NewChat(cx: Scope) -> Element {
                     message: Message::new(&read_ps.message).unwrap(),
                 }
                 .into(),
-                options: NewPostOptions::default(),
+                options: new_post_options(schedule.get()),
             };
 
             let response = fetch_json!(<NewPostOk>, api_client, request);
@@ -136,6 +137,7 @@ pub fn NewChat(cx: Scope) -> Element {
         form { class: "flex flex-col gap-4", onsubmit: form_onsubmit, prevent_default: "onsubmit",
             MessageInput { page_state: page_state.clone() }
             HeadlineInput { page_state: page_state.clone() }
+            ScheduleInput { schedule: schedule.clone() }
             Button::<fn()> { r#type: BtnTypes::Submit, disabled: is_invalid, "Post" }
         }
     })
@@ -1,5 +1,6 @@
 #![allow(non_snake_case)]
 
+use super::schedule::{is_valid_schedule, new_post_options, ScheduleInput};
 use crate::{
     fetch_json,
     page::new_post_app_bar::NewPostAppBar,
@@ -9,24 +10,44 @@ use crate::{
 };
 use dioxus::prelude::*;
 use serde::{Deserialize, Serialize};
-use uchat_endpoint::post::types::{ImageKind, NewPostOptions};
+use uchat_endpoint::post::types::ImageKind;
 use web_sys::HtmlInputElement;
 
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct PageImage {
+    pub data: String,
+    pub alt: String,
+}
+
 #[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub struct PageState {
     pub caption: String,
-    pub image: Option<String>,
+    pub images: Vec<PageImage>,
 }
 
 impl PageState {
+    /// Several images are posted as a gallery, which has alt text instead of a caption
+    pub fn is_gallery(&self) -> bool {
+        self.images.len() > 1
+    }
+
     pub fn can_submit(&self) -> bool {
-        use uchat_domain::post::Caption;
+        use uchat_domain::post::{validate_gallery_len, AltText, Caption};
 
-        ret_if!(
-            !self.caption.is_empty() && Caption::new(&self.caption).is_err(),
-            false
-        );
-        ret_if!(self.image.is_none(), false);
+        ret_if!(validate_gallery_len(self.images.len()).is_err(), false);
+        if self.is_gallery() {
+            ret_if!(
+                self.images
+                    .iter()
+                    .any(|image| !image.alt.is_empty() && AltText::new(&image.alt).is_err()),
+                false
+            );
+        } else {
+            ret_if!(
+                !self.caption.is_empty() && Caption::new(&self.caption).is_err(),
+                false
+            );
+        }
 
         true
     }
@@ -39,31 +60,48 @@ pub fn ImageInput(cx: Scope, page_state: UseRef<PageState>) -> Element {
         to_owned![page_state, toaster];
         async move {
             use gloo_file::{futures::read_as_data_url, File};
+            use uchat_domain::post::MAX_GALLERY_IMAGES;
             use wasm_bindgen::JsCast;
 
             let el = util::document()
                 .get_element_by_id("image-input")
                 .unwrap()
                 .unchecked_into::<HtmlInputElement>();
-            let file: File = el.files().unwrap().get(0).unwrap().into();
+            let files = el.files().unwrap();
 
-            match read_as_data_url(&file).await {
-                Ok(data) => page_state.with_mut(|state| state.image = Some(data)),
-                Err(e) => {
-                    toasty!(toaster => error: format!("Error loading file: {e}"));
+            for i in 0..files.length() {
+                if page_state.read().images.len() >= MAX_GALLERY_IMAGES {
+                    toasty!(toaster => error: format!("A post can have at most {MAX_GALLERY_IMAGES} images"));
+                    break;
+                }
+
+                let file: File = files.get(i).unwrap().into();
+                match read_as_data_url(&file).await {
+                    Ok(data) => page_state.with_mut(|state| {
+                        state.images.push(PageImage {
+                            data,
+                            alt: String::new(),
+                        })
+                    }),
+                    Err(e) => {
+                        toasty!(toaster => error: format!("Error loading file: {e}"));
+                    }
                 }
             }
+            // ? Lets the same file be picked again after it's removed
+            el.set_value("");
         }
     };
 
     cx.render(rsx! {
         div {
-            label { r#for: "image-input", "Upload Image" }
+            label { r#for: "image-input", "Upload Images" }
             input {
                 class: "w-full",
                 id: "image-input",
                 r#type: "file",
                 accept: "image/*",
+                multiple: true,
                 oninput: handle_oninput
             }
         }
@@ -71,19 +109,81 @@ pub fn ImageInput(cx: Scope, page_state: UseRef<PageState>) -> Element {
 }
 
 #[inline_props]
-pub fn ImagePreview(cx: Scope, page_state: UseRef<PageState>) -> Element {
-    let image_data = page_state.read().image.clone();
-    let preview_el = if let Some(ref image) = image_data {
-        rsx! {img {
-            class: "max-w-[calc(var(--content-max-width)/2)] max-h-[40vh]",
-            src: "{image}"
+pub fn AltTextInput(cx: Scope, page_state: UseRef<PageState>, index: usize) -> Element {
+    use uchat_domain::post::AltText;
+
+    let max_chars = AltText::MAX_CHARS;
+    let alt = page_state
+        .read()
+        .images
+        .get(*index)
+        .map(|image| image.alt.clone())
+        .unwrap_or_default();
+    let wrong_len = maybe_class!("err-text-color", alt.len() > max_chars);
+    let id = format!("alt-text-{index}");
+
+    cx.render(rsx! {
+        div { class: "w-full",
+            label { r#for: "{id}",
+                div { class: "flex flex-row justify-between",
+                    span { "Alt text" }
+                    span { class: "text-right {wrong_len}", "{alt.len()}/{max_chars}" }
+                }
+            }
+            input {
+                class: "input-field",
+                id: "{id}",
+                value: "{alt}",
+                oninput: move |ev| {
+                    page_state.with_mut(|state| {
+                        if let Some(image) = state.images.get_mut(*index) {
+                            image.alt = ev.data.value.clone();
+                        }
+                    });
+                }
+            }
         }
+    })
+}
+
+#[inline_props]
+pub fn ImagePreview(cx: Scope, page_state: UseRef<PageState>) -> Element {
+    let images = page_state.read().images.clone();
+    let is_gallery = page_state.read().is_gallery();
+
+    if images.is_empty() {
+        return cx.render(rsx! { div { "no image uploaded" } });
+    }
+
+    let images_el = images.into_iter().enumerate().map(|(i, image)| {
+        let alt_el = is_gallery.then(|| {
+            rsx! { AltTextInput { page_state: page_state.clone(), index: i } }
+        });
+
+        rsx! {
+            div { key: "{i}", class: "flex flex-col items-center gap-2",
+                img {
+                    class: "max-w-[calc(var(--content-max-width)/2)] max-h-[40vh]",
+                    src: "{image.data}"
+                }
+                alt_el
+                button {
+                    class: "px-2 rounded border",
+                    r#type: "button",
+                    onclick: move |_| {
+                        page_state.with_mut(|state| {
+                            state.images.remove(i);
+                        });
+                    },
+                    "Remove"
+                }
+            }
         }
-    } else {
-        rsx! { div { "no image uploaded" } }
-    };
+    });
 
-    cx.render(rsx! {preview_el})
+    cx.render(rsx! {
+        div { class: "flex flex-col gap-4", images_el }
+    })
 }
 
 #[inline_props]
@@ -118,23 +218,40 @@ pub fn CaptionInput(cx: Scope, page_state: UseRef<PageState>) -> Element {
 
 pub fn NewImage(cx: Scope) -> Element {
     let page_state = use_ref(cx, PageState::default);
-    let is_invalid = !page_state.read().can_submit();
+    let schedule = use_state(cx, String::new);
+    let is_invalid = !page_state.read().can_submit() || !is_valid_schedule(schedule.get());
     let toaster = use_toaster(cx);
     let router = use_router(cx);
     let api_client = ApiClient::global();
 
     let form_onsubmit = async_handler!(
         &cx,
-        [toaster, api_client, page_state, router],
+        [toaster, api_client, page_state, router, schedule],
         move |_| async move {
-            use uchat_domain::post::Caption;
+            use uchat_domain::post::{AltText, Caption};
             use uchat_endpoint::post::endpoint::{NewPost, NewPostOk};
-            use uchat_endpoint::post::types::Image;
+            use uchat_endpoint::post::types::{Gallery, GalleryImage, Image};
 
             let read_ps = &page_state.read();
 
-            let request = NewPost {
-                content: Image {
+            let content = if read_ps.is_gallery() {
+                Gallery {
+                    images: read_ps
+                        .images
+                        .iter()
+                        .map(|image| GalleryImage {
+                            kind: ImageKind::DataUrl(image.data.clone()),
+                            alt: if image.alt.is_empty() {
+                                None
+                            } else {
+                                AltText::new(&image.alt).ok()
+                            },
+                        })
+                        .collect(),
+                }
+                .into()
+            } else {
+                Image {
                     caption: {
                         let caption = &read_ps.caption;
                         if caption.is_empty() {
@@ -144,12 +261,16 @@ pub fn NewImage(cx: Scope) -> Element {
                         }
                     },
                     kind: {
-                        let image = &read_ps.image;
-                        ImageKind::DataUrl(image.clone().unwrap())
+                        let image = &read_ps.images[0];
+                        ImageKind::DataUrl(image.data.clone())
                     },
                 }
-                .into(),
-                options: NewPostOptions::default(),
+                .into()
+            };
+
+            let request = NewPost {
+                content,
+                options: new_post_options(schedule.get()),
             };
 
             let response = fetch_json!(<NewPostOk>, api_client, request);
@@ -165,12 +286,17 @@ pub fn NewImage(cx: Scope) -> Element {
         }
     );
 
+    let caption_el = (!page_state.read().is_gallery()).then(|| {
+        rsx! { CaptionInput { page_state: page_state.clone() } }
+    });
+
     cx.render(rsx! {
         NewPostAppBar { title: "New Image".to_owned(), active_page: super::NewPostPages::Image }
         form { class: "flex flex-col gap-4", onsubmit: form_onsubmit, prevent_default: "onsubmit",
             ImageInput { page_state: page_state.clone() }
             ImagePreview { page_state: page_state.clone() }
-            CaptionInput { page_state: page_state.clone() }
+            caption_el
+            ScheduleInput { schedule: schedule.clone() }
             Button::<fn()> { r#type: BtnTypes::Submit, disabled: is_invalid, "Post" }
         }
     })
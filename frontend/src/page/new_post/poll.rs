@@ -2,6 +2,7 @@
 
 use std::collections::BTreeMap;
 
+use super::schedule::{is_valid_schedule, new_post_options, ScheduleInput};
 use crate::{
     fetch_json, new_btree_map, page::new_post_app_bar::NewPostAppBar, prelude::*, ret_if, toasty,
 };
@@ -11,7 +12,7 @@ use uchat_domain::{
     ids::PollChoiceId,
     post::{PollChoiceDescription, PollHeadline},
 };
-use uchat_endpoint::post::types::{NewPostOptions, PollChoice};
+use uchat_endpoint::post::types::PollChoice;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PageState {
@@ -144,14 +145,15 @@ pub fn PollChoices(cx: Scope, page_state: UseRef<PageState>) -> Element {
 
 pub fn NewPoll(cx: Scope) -> Element {
     let page_state = use_ref(cx, PageState::default);
-    let is_invalid = !page_state.read().can_submit();
+    let schedule = use_state(cx, String::new);
+    let is_invalid = !page_state.read().can_submit() || !is_valid_schedule(schedule.get());
     let toaster = use_toaster(cx);
     let router = use_router(cx);
     let api_client = ApiClient::global();
 
     let form_onsubmit = async_handler!(
         &cx,
-        [toaster, api_client, page_state, router],
+        [toaster, api_client, page_state, router, schedule],
         move |_| async move {
             use uchat_domain::post::PollHeadline;
             use uchat_endpoint::post::endpoint::{NewPost, NewPostOk};
@@ -180,7 +182,7 @@ pub fn NewPoll(cx: Scope) -> Element {
                     voted: None,
                 }
                 .into(),
-                options: NewPostOptions::default(),
+                options: new_post_options(schedule.get()),
             };
 
             let response = fetch_json!(<NewPostOk>, api_client, request);
@@ -201,6 +203,7 @@ pub fn NewPoll(cx: Scope) -> Element {
         form { class: "flex flex-col gap-4", onsubmit: form_onsubmit, prevent_default: "onsubmit",
             HeadlineInput { page_state: page_state.clone() }
             PollChoices { page_state: page_state.clone() }
+            ScheduleInput { schedule: schedule.clone() }
             Button::<fn()> { r#type: BtnTypes::Submit, disabled: is_invalid, "Post" }
         }
     })
@@ -0,0 +1,62 @@
+#![allow(non_snake_case)]
+
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use dioxus::prelude::*;
+use uchat_endpoint::post::types::NewPostOptions;
+
+use crate::prelude::*;
+
+// NOTE The format used by `datetime-local` inputs, which hold the user's local time
+const DATETIME_LOCAL_FORMAT: &str = "%Y-%m-%dT%H:%M";
+
+pub fn parse_local_datetime(value: &str) -> Option<DateTime<Utc>> {
+    let naive = NaiveDateTime::parse_from_str(value, DATETIME_LOCAL_FORMAT).ok()?;
+    Local
+        .from_local_datetime(&naive)
+        .single()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+pub fn format_local_datetime(time: DateTime<Utc>) -> String {
+    time.with_timezone(&Local)
+        .format(DATETIME_LOCAL_FORMAT)
+        .to_string()
+}
+
+/// An empty schedule means the post goes out right away
+pub fn is_valid_schedule(value: &str) -> bool {
+    value.is_empty() || parse_local_datetime(value).map_or(false, |time| time > Utc::now())
+}
+
+pub fn new_post_options(schedule: &str) -> NewPostOptions {
+    match parse_local_datetime(schedule) {
+        Some(time_posted) => NewPostOptions {
+            time_posted,
+            ..Default::default()
+        },
+        None => NewPostOptions::default(),
+    }
+}
+
+#[inline_props]
+pub fn ScheduleInput(cx: Scope, schedule: UseState<String>) -> Element {
+    let invalid = maybe_class!("err-text-color", !is_valid_schedule(schedule.get()));
+
+    cx.render(rsx! {
+        div {
+            label { r#for: "schedule",
+                div { class: "flex flex-row justify-between",
+                    span { "Schedule" }
+                    span { class: "text-right {invalid}", "leave empty to post now" }
+                }
+            }
+            input {
+                class: "input-field",
+                id: "schedule",
+                r#type: "datetime-local",
+                value: "{schedule}",
+                oninput: move |ev| schedule.set(ev.value.clone())
+            }
+        }
+    })
+}
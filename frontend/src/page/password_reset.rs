@@ -0,0 +1,245 @@
+#![allow(non_snake_case)]
+
+use dioxus::prelude::*;
+use dioxus_router::Link;
+use uchat_domain::{
+    user::{Email, Password},
+    UserFacingError,
+};
+
+use crate::{
+    components::keyed_notification_box::{KeyedNotificationBox, KeyedNotifications},
+    fetch_json,
+    prelude::*,
+    toasty,
+    util::ApiClient,
+};
+
+pub struct ForgotPasswordState {
+    email: UseState<String>,
+    sent: UseState<bool>,
+    form_errors: KeyedNotifications,
+    server_errors: KeyedNotifications,
+}
+
+impl ForgotPasswordState {
+    pub fn new(cx: Scope) -> Self {
+        Self {
+            email: use_state(cx, String::new).clone(),
+            sent: use_state(cx, || false).clone(),
+            form_errors: KeyedNotifications::default(),
+            server_errors: KeyedNotifications::default(),
+        }
+    }
+
+    pub fn can_submit(&self) -> bool {
+        !self.email.current().is_empty() && !self.form_errors.has_messages()
+    }
+}
+
+pub fn ForgotPassword(cx: Scope) -> Element {
+    let api_client = ApiClient::global();
+    let page_state = ForgotPasswordState::new(cx);
+    let page_state = use_ref(cx, || page_state);
+
+    let form_onsubmit = async_handler!(&cx, [api_client, page_state], move |_| async move {
+        use uchat_endpoint::user::endpoint::{RequestPasswordReset, RequestPasswordResetOk};
+
+        let Ok(email) = page_state.with(|s| Email::new(s.email.current().to_string())) else {
+            return;
+        };
+        let request = RequestPasswordReset { email };
+        match fetch_json!(<RequestPasswordResetOk>, api_client, request) {
+            Ok(_) => page_state.with_mut(|s| {
+                s.server_errors.remove("reset");
+                s.sent.set(true);
+            }),
+            Err(e) => page_state.with_mut(|s| {
+                s.server_errors
+                    .set("reset", format!("Failed to request password reset: {e}"))
+            }),
+        }
+    });
+
+    let email_oninput = sync_handler!([page_state], move |ev: FormEvent| {
+        page_state.with_mut(|s| {
+            match Email::new(&ev.value) {
+                Err(e) if !ev.value.is_empty() => s.form_errors.set("email", e.formatted_error()),
+                _ => s.form_errors.remove("email"),
+            };
+            s.email.set(ev.value.clone());
+        });
+    });
+
+    let email = page_state.with(|s| s.email.current().to_string());
+    if page_state.with(|s| *s.sent.current()) {
+        return cx.render(rsx! {
+            div { class: "flex flex-col gap-5 text-center",
+                div { "If {email} is a confirmed email of an account, a link to reset its password is on its way." }
+                Link { class: "link", to: page::ACCOUNT_LOGIN, "Back to Login" }
+            }
+        });
+    }
+
+    cx.render(rsx! {
+        form { class: "flex flex-col gap-5", prevent_default: "onsubmit", onsubmit: form_onsubmit,
+            KeyedNotificationBox {
+                legend: "Reset Errors",
+                notifications: page_state.with(|state| state.server_errors.clone())
+            }
+            div { class: "flex flex-col",
+                label { r#for: "email", "Email Address" }
+                input {
+                    id: "email",
+                    class: "input-field",
+                    placeholder: "Email Address",
+                    value: "{email}",
+                    oninput: email_oninput
+                }
+            }
+            Link { class: "link text-center", to: page::ACCOUNT_LOGIN, "Back to Login" }
+            KeyedNotificationBox {
+                legend: "Form Errors",
+                notifications: page_state.with(|state| state.form_errors.clone())
+            }
+            Button::<fn()> {
+                r#type: BtnTypes::Submit,
+                disabled: !page_state.with(|state| state.can_submit()),
+                "Send Reset Link"
+            }
+        }
+    })
+}
+
+pub struct ResetPasswordState {
+    password: UseState<String>,
+    password_confirm: UseState<String>,
+    form_errors: KeyedNotifications,
+    server_errors: KeyedNotifications,
+}
+
+impl ResetPasswordState {
+    pub fn new(cx: Scope) -> Self {
+        Self {
+            password: use_state(cx, String::new).clone(),
+            password_confirm: use_state(cx, String::new).clone(),
+            form_errors: KeyedNotifications::default(),
+            server_errors: KeyedNotifications::default(),
+        }
+    }
+
+    pub fn can_submit(&self) -> bool {
+        !self.password.current().is_empty() && !self.form_errors.has_messages()
+    }
+
+    /// Both inputs are checked together, since either one can make the passwords match
+    fn validate(&mut self) {
+        match Password::new(self.password.current().as_str()) {
+            Err(e) if !self.password.current().is_empty() => {
+                self.form_errors.set("password", e.formatted_error())
+            }
+            _ => self.form_errors.remove("password"),
+        };
+        if self.password.current() != self.password_confirm.current() {
+            self.form_errors
+                .set("password-confirm", "Passwords must match")
+        } else {
+            self.form_errors.remove("password-confirm")
+        };
+    }
+}
+
+pub fn ResetPassword(cx: Scope) -> Element {
+    let api_client = ApiClient::global();
+    let route = use_route(cx);
+    let router = use_router(cx);
+    let toaster = use_toaster(cx);
+    let page_state = ResetPasswordState::new(cx);
+    let page_state = use_ref(cx, || page_state);
+
+    let token = route.segment("token").unwrap_or_default().to_owned();
+    let (password, password_confirm) = page_state.with(|s| {
+        (
+            s.password.current().to_string(),
+            s.password_confirm.current().to_string(),
+        )
+    });
+
+    let form_onsubmit = async_handler!(
+        &cx,
+        [api_client, page_state, router, toaster, token],
+        move |_| async move {
+            use uchat_endpoint::user::endpoint::{ResetPassword, ResetPasswordOk};
+
+            let Ok(password) = page_state.with(|s| Password::new(s.password.current().to_string()))
+            else {
+                return;
+            };
+            let request = ResetPassword { token, password };
+            match fetch_json!(<ResetPasswordOk>, api_client, request) {
+                Ok(_) => {
+                    toasty!(toaster => success: "Password changed, log in with your new password", 5);
+                    router.navigate_to(page::ACCOUNT_LOGIN);
+                }
+                Err(e) => page_state.with_mut(|s| {
+                    s.server_errors
+                        .set("reset", format!("Failed to reset password: {e}"))
+                }),
+            }
+        }
+    );
+
+    let password_oninput = sync_handler!([page_state], move |ev: FormEvent| {
+        page_state.with_mut(|s| {
+            s.password.set(ev.value.clone());
+            s.validate();
+        });
+    });
+
+    let password_confirm_oninput = sync_handler!([page_state], move |ev: FormEvent| {
+        page_state.with_mut(|s| {
+            s.password_confirm.set(ev.value.clone());
+            s.validate();
+        });
+    });
+
+    cx.render(rsx! {
+        form { class: "flex flex-col gap-5", prevent_default: "onsubmit", onsubmit: form_onsubmit,
+            KeyedNotificationBox {
+                legend: "Reset Errors",
+                notifications: page_state.with(|state| state.server_errors.clone())
+            }
+            div { class: "flex flex-col",
+                label { r#for: "password", "New Password" }
+                input {
+                    id: "password",
+                    r#type: "password",
+                    class: "input-field",
+                    placeholder: "New Password",
+                    value: "{password}",
+                    oninput: password_oninput
+                }
+            }
+            div { class: "flex flex-col",
+                label { r#for: "password-confirm", "Confirm Password" }
+                input {
+                    id: "password-confirm",
+                    r#type: "password",
+                    class: "input-field",
+                    placeholder: "Confirm Password",
+                    value: "{password_confirm}",
+                    oninput: password_confirm_oninput
+                }
+            }
+            KeyedNotificationBox {
+                legend: "Form Errors",
+                notifications: page_state.with(|state| state.form_errors.clone())
+            }
+            Button::<fn()> {
+                r#type: BtnTypes::Submit,
+                disabled: !page_state.with(|state| state.can_submit()),
+                "Reset Password"
+            }
+        }
+    })
+}
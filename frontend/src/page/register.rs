@@ -3,6 +3,7 @@
 use dioxus::prelude::*;
 use dioxus_router::Link;
 use uchat_domain::{self, UserFacingError};
+use uchat_endpoint::ErrorCode;
 
 use crate::{
     components::{
@@ -119,16 +120,21 @@ pub fn Register(cx: Scope) -> Element {
 
             match response {
                 Ok(res) => {
-                    crate::util::cookie::set_session(
-                        res.session_signature,
-                        res.session_id,
-                        res.session_expires,
-                    );
+                    crate::util::cookie::set_logged_in(res.session_expires, &res.csrf_token);
                     local_profile.write().user_id = Some(res.user_id);
                     router.navigate_to(page::HOME);
                 }
                 Err(e) => {
-                    page_state.with_mut(|state| state.server_errors.set("register", e.to_string()));
+                    let msg = match e.code() {
+                        Some(ErrorCode::AccountExists) => {
+                            "That user name is already taken".to_string()
+                        }
+                        Some(ErrorCode::TooManyRequests) => {
+                            "Too many accounts created, try again later".to_string()
+                        }
+                        _ => e.to_string(),
+                    };
+                    page_state.with_mut(|state| state.server_errors.set("register", msg));
                 }
             };
         }
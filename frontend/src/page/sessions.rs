@@ -0,0 +1,98 @@
+#![allow(non_snake_case)]
+
+use crate::{prelude::*, toasty};
+use dioxus::prelude::*;
+use uchat_domain::ids::SessionId;
+use uchat_endpoint::user::types::ActiveSession;
+
+pub fn Sessions(cx: Scope) -> Element {
+    let api_client = ApiClient::global();
+    let router = use_router(cx);
+    let toaster = use_toaster(cx);
+    let sessions = use_ref(cx, Vec::<ActiveSession>::new);
+
+    let _fetch_sessions = {
+        to_owned![api_client, toaster, sessions];
+        use_future(cx, (), |_| async move {
+            use uchat_endpoint::user::endpoint::{ActiveSessions, ActiveSessionsOk};
+
+            let response = fetch_json!(<ActiveSessionsOk>, api_client, ActiveSessions);
+            match response {
+                Ok(res) => sessions.with_mut(|s| *s = res.sessions),
+                Err(e) => toasty!(toaster => error: format!("Failed to retrieve sessions: {e}")),
+            }
+        })
+    };
+
+    let revoke = move |session_id: SessionId| {
+        to_owned![api_client, toaster, sessions];
+        cx.spawn(async move {
+            use uchat_endpoint::user::endpoint::{RevokeSession, RevokeSessionOk};
+
+            let request = RevokeSession { session_id };
+            match fetch_json!(<RevokeSessionOk>, api_client, request) {
+                Ok(_) => {
+                    sessions.with_mut(|s| s.retain(|session| session.id != session_id));
+                    toasty!(toaster => success: "Session revoked", 3);
+                }
+                Err(e) => toasty!(toaster => error: format!("Failed to revoke session: {e}")),
+            }
+        });
+    };
+
+    let sessions_el = sessions
+        .read()
+        .clone()
+        .into_iter()
+        .map(|session| {
+            let session_id = session.id;
+            let device = session
+                .user_agent
+                .unwrap_or_else(|| "Unknown device".to_string());
+            let ip_address = session
+                .ip_address
+                .unwrap_or_else(|| "unknown location".to_string());
+            let created_at = session.created_at.format("%Y-%m-%d %H:%M").to_string();
+            let last_seen_at = session.last_seen_at.format("%Y-%m-%d %H:%M").to_string();
+
+            // ? The current session is ended by logging out instead
+            let action_el = if session.is_current {
+                rsx! { div { class: "font-bold", "This device" } }
+            } else {
+                rsx! {
+                    Button {
+                        r#type: BtnTypes::Button,
+                        handle_onclick: move || revoke(session_id),
+                        "Revoke"
+                    }
+                }
+            };
+
+            rsx! {
+                div {
+                    key: "{session_id.to_string()}",
+                    class: "flex flex-col gap-1 mb-4",
+                    div { class: "flex justify-between items-center gap-2",
+                        div { class: "truncate", "{device}" }
+                        action_el
+                    }
+                    div { class: "font-light text-sm", "{ip_address}" }
+                    div { class: "font-light text-sm", "Logged in {created_at}, last seen {last_seen_at}" }
+                    hr {}
+                }
+            }
+        })
+        .collect::<Vec<LazyNodes>>();
+
+    cx.render(rsx! {
+        AppBar { title: "Active Sessions",
+            AppBarImgButton {
+                handle_onclick: move |_| router.pop_route(),
+                img: "/static/icons/icon-back.svg",
+                label: "Back",
+                title: "Go to the previous page"
+            }
+        }
+        sessions_el.into_iter()
+    })
+}
@@ -0,0 +1,189 @@
+#![allow(non_snake_case)]
+
+use crate::{prelude::*, toasty};
+use dioxus::prelude::*;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+enum Enrollment {
+    #[default]
+    Loading,
+    Disabled,
+    Pending {
+        secret: String,
+        provisioning_uri: String,
+    },
+    Enabled {
+        recovery_codes: Vec<String>,
+    },
+}
+
+pub fn TwoFactor(cx: Scope) -> Element {
+    let api_client = ApiClient::global();
+    let router = use_router(cx);
+    let toaster = use_toaster(cx);
+    let enrollment = use_ref(cx, Enrollment::default);
+    let totp_code = use_state(cx, String::new);
+
+    let _fetch_profile = {
+        to_owned![api_client, toaster, enrollment];
+        use_future(cx, (), |_| async move {
+            use uchat_endpoint::user::endpoint::{GetMyProfile, GetMyProfileOk};
+
+            let response = fetch_json!(<GetMyProfileOk>, api_client, GetMyProfile);
+            match response {
+                Ok(res) if res.totp_enabled => enrollment.with_mut(|e| {
+                    *e = Enrollment::Enabled {
+                        recovery_codes: vec![],
+                    }
+                }),
+                Ok(_) => enrollment.with_mut(|e| *e = Enrollment::Disabled),
+                Err(e) => toasty!(toaster => error: format!("Failed to retrieve profile: {e}")),
+            }
+        })
+    };
+
+    let enroll_onclick = async_handler!(
+        &cx,
+        [api_client, toaster, enrollment, totp_code],
+        move |_| async move {
+            use uchat_endpoint::user::endpoint::{EnrollTotp, EnrollTotpOk};
+
+            match fetch_json!(<EnrollTotpOk>, api_client, EnrollTotp) {
+                Ok(res) => {
+                    totp_code.set(String::new());
+                    enrollment.with_mut(|e| {
+                        *e = Enrollment::Pending {
+                            secret: res.secret,
+                            provisioning_uri: res.provisioning_uri,
+                        }
+                    });
+                }
+                Err(e) => toasty!(toaster => error: format!("Failed to set up authenticator: {e}")),
+            }
+        }
+    );
+
+    let confirm_onclick = async_handler!(
+        &cx,
+        [api_client, toaster, enrollment, totp_code],
+        move |_| async move {
+            use uchat_endpoint::user::endpoint::{ConfirmTotp, ConfirmTotpOk};
+
+            let request = ConfirmTotp {
+                code: totp_code.get().trim().to_string(),
+            };
+            match fetch_json!(<ConfirmTotpOk>, api_client, request) {
+                Ok(res) => {
+                    totp_code.set(String::new());
+                    enrollment.with_mut(|e| {
+                        *e = Enrollment::Enabled {
+                            recovery_codes: res.recovery_codes,
+                        }
+                    });
+                    toasty!(toaster => success: "Two-factor authentication enabled", 3);
+                }
+                Err(e) => toasty!(toaster => error: format!("Failed to confirm code: {e}")),
+            }
+        }
+    );
+
+    let disable_onclick = async_handler!(
+        &cx,
+        [api_client, toaster, enrollment, totp_code],
+        move |_| async move {
+            use uchat_endpoint::user::endpoint::{DisableTotp, DisableTotpOk};
+
+            let request = DisableTotp {
+                code: totp_code.get().trim().to_string(),
+            };
+            match fetch_json!(<DisableTotpOk>, api_client, request) {
+                Ok(_) => {
+                    totp_code.set(String::new());
+                    enrollment.with_mut(|e| *e = Enrollment::Disabled);
+                    toasty!(toaster => success: "Two-factor authentication disabled", 3);
+                }
+                Err(e) => toasty!(toaster => error: format!("Failed to disable: {e}")),
+            }
+        }
+    );
+
+    let code_input_el = rsx! {
+        input {
+            class: "input-field",
+            id: "totp-code",
+            placeholder: "Authentication code",
+            autocomplete: "one-time-code",
+            value: "{totp_code}",
+            oninput: move |ev| totp_code.set(ev.value.clone())
+        }
+    };
+
+    let content_el = match enrollment.read().clone() {
+        Enrollment::Loading => rsx! { div { class: "text-center mt-8", "Loading..." } },
+        Enrollment::Disabled => rsx! {
+            div { class: "flex flex-col gap-3",
+                p { "Protect your account by also asking for a code from an authenticator app when you log in." }
+                Button { r#type: BtnTypes::Button, handle_onclick: move || enroll_onclick(()), "Set up authenticator app" }
+            }
+        },
+        Enrollment::Pending {
+            secret,
+            provisioning_uri,
+        } => rsx! {
+            div { class: "flex flex-col gap-3",
+                p { "Add this key to your authenticator app, then enter the code it shows to finish." }
+                a { class: "link break-all", href: "{provisioning_uri}", "Open in authenticator app" }
+                code { class: "break-all", "{secret}" }
+                code_input_el
+                Button {
+                    r#type: BtnTypes::Button,
+                    disabled: totp_code.get().trim().is_empty(),
+                    handle_onclick: move || confirm_onclick(()),
+                    "Confirm"
+                }
+            }
+        },
+        Enrollment::Enabled { recovery_codes } => {
+            // ? Recovery codes are only ever shown right after enabling
+            let recovery_codes_el = if recovery_codes.is_empty() {
+                None
+            } else {
+                Some(rsx! {
+                    div { class: "flex flex-col gap-1",
+                        p { class: "font-bold", "Save these recovery codes, each one can be used once if you lose your device:" }
+                        recovery_codes.into_iter().map(|recovery_code| rsx! {
+                            code { key: "{recovery_code}", "{recovery_code}" }
+                        })
+                    }
+                })
+            };
+
+            rsx! {
+                div { class: "flex flex-col gap-3",
+                    p { "Two-factor authentication is enabled." }
+                    recovery_codes_el
+                    p { "Enter a code or a recovery code to turn it off." }
+                    code_input_el
+                    Button {
+                        r#type: BtnTypes::Button,
+                        disabled: totp_code.get().trim().is_empty(),
+                        handle_onclick: move || disable_onclick(()),
+                        "Disable"
+                    }
+                }
+            }
+        }
+    };
+
+    cx.render(rsx! {
+        AppBar { title: "Two-Factor Authentication",
+            AppBarImgButton {
+                handle_onclick: move |_| router.pop_route(),
+                img: "/static/icons/icon-back.svg",
+                label: "Back",
+                title: "Go to the previous page"
+            }
+        }
+        content_el
+    })
+}
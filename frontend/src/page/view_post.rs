@@ -0,0 +1,74 @@
+#![allow(non_snake_case)]
+
+use std::str::FromStr;
+
+use crate::{
+    components::post::{posts_list::use_feed, use_post_manager},
+    prelude::*,
+};
+use dioxus::prelude::*;
+
+use uchat_domain::ids::PostId;
+
+pub fn ViewPost(cx: Scope) -> Element {
+    let route = use_route(cx);
+    let post_id = route.segment("id").and_then(|id| PostId::from_str(id).ok());
+
+    let Some(post_id) = post_id else {
+        return cx.render(rsx! {
+            "Post not found!"
+        });
+    };
+
+    let api_client = ApiClient::global();
+    let router = use_router(cx);
+    let post_manager = use_post_manager(cx);
+
+    {
+        to_owned![post_manager];
+        use_feed(cx, post_id, move |cursor| async move {
+            use uchat_endpoint::post::endpoint::{ViewPost, ViewPostOk};
+
+            let is_first_page = cursor.is_none();
+            let request = ViewPost { post_id, cursor };
+            fetch_json!(<ViewPostOk>, api_client, request).map(|res| {
+                // NOTE The thread leading up to the post is only sent with the first page of replies
+                if is_first_page {
+                    let thread = res.ancestors.into_iter().chain(std::iter::once(res.post));
+                    post_manager.write().append(thread);
+                }
+                (res.descendants, res.next_cursor)
+            })
+        });
+    }
+
+    let posts_el = post_manager
+        .read()
+        .posts
+        .keys()
+        .map(|&id| {
+            if id == post_id {
+                rsx! {
+                    div { class: "border-l-4 border-slate-400 pl-2", PublicPostEntry { post_id: id } }
+                    div { class: "font-bold text-center my-6", "Replies" }
+                }
+            } else {
+                rsx! {
+                    div { PublicPostEntry { post_id: id } }
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    cx.render(rsx! {
+        AppBar { title: "Thread",
+            AppBarImgButton {
+                handle_onclick: move |_| router.pop_route(),
+                img: "/static/icons/icon-back.svg",
+                label: "Back",
+                title: "Go to the previous page"
+            }
+        }
+        posts_el.into_iter()
+    })
+}
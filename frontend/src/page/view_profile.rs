@@ -2,7 +2,10 @@
 
 use std::str::FromStr;
 
-use crate::{components::post::use_post_manager, prelude::*};
+use crate::{
+    components::post::{posts_list::use_feed, use_post_manager},
+    prelude::*,
+};
 use dioxus::prelude::{GlobalAttributes, *};
 
 use uchat_domain::ids::UserId;
@@ -39,25 +42,21 @@ pub fn ViewProfile(cx: Scope) -> Element {
     let profile = use_ref(cx, || None);
     let toaster = use_toaster(cx);
 
-    use_effect(cx, (&user_id,), |(user_id,)| {
-        to_owned![api_client, toaster, post_manager, user_id, profile];
-        async move {
+    {
+        to_owned![profile];
+        use_feed(cx, user_id, move |cursor| async move {
             use uchat_endpoint::user::endpoint::{ViewProfile, ViewProfileOk};
 
-            post_manager.write().clear();
-            let view_res =
-                fetch_json!(<ViewProfileOk>, api_client, ViewProfile { user_id });
-            match view_res {
-                Ok(view_res) => {
-                    post_manager.write().populate(view_res.posts.into_iter());
+            let is_first_page = cursor.is_none();
+            let request = ViewProfile { user_id, cursor };
+            fetch_json!(<ViewProfileOk>, api_client, request).map(|view_res| {
+                if is_first_page {
                     profile.with_mut(|profile| *profile = Some(view_res.profile));
                 }
-                Err(e) => {
-                    toasty!(toaster => error: format!("Failed to retrieve profile: {e}"))
-                }
-            };
-        }
-    });
+                (view_res.posts, view_res.next_cursor)
+            })
+        });
+    }
 
     let follow_onclick = async_handler!(
         &cx,
@@ -119,7 +118,11 @@ pub fn ViewProfile(cx: Scope) -> Element {
                     rsx! {""}
                 } else {
                     rsx! {
-                        Button { r#type: BtnTypes::Button, handle_onclick: || router.pop_route(), "Send Message" }
+                        Button {
+                            r#type: BtnTypes::Button,
+                            handle_onclick: move || router.navigate_to(&page::messages_view(p.id)),
+                            "Send Message"
+                        }
                         Button { r#type: BtnTypes::Button, handle_onclick: move || follow_onclick(()), follow_btn_label }
                     }
                 }
@@ -1,5 +1,6 @@
 pub mod api_client;
 pub mod cookie;
+pub mod scroll;
 pub use api_client::ApiClient;
 
 use serde::Deserialize;
@@ -18,6 +19,16 @@ pub enum RequestError {
     BadRequest(#[from] uchat_endpoint::RequestFailed),
 }
 
+impl RequestError {
+    /// The reason the server gave for rejecting the request, if any
+    pub fn code(&self) -> Option<uchat_endpoint::ErrorCode> {
+        match self {
+            Self::BadRequest(failed) => failed.code,
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone, Deserialize, PartialEq)]
 pub struct ApiResponse {
     message: String,
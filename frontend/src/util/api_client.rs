@@ -60,15 +60,17 @@ where
 {
     let url = make_absolute_url(endpoint);
 
-    let api_request = async {
-        client
-            .inner
-            .post(url)
-            .fetch_credentials_include()
-            .json(json)
-            .send()
-            .await
-    };
+    let mut request = client
+        .inner
+        .post(url)
+        .fetch_credentials_include()
+        .json(json);
+    // ? The session cookies are only accepted along with the token they were issued with
+    if let Some(token) = super::cookie::csrf_token() {
+        request = request.header(uchat_endpoint::CSRF_HEADER, token);
+    }
+
+    let api_request = async { request.send().await };
     make_request(api_request, timeout).await
 }
 
@@ -135,14 +137,13 @@ macro_rules! fetch_json {
                     let status = res.status();
                     match res.json::<uchat_endpoint::RequestFailed>().await {
                         Ok(payload) => Err(RequestError::BadRequest(payload)),
-                        Err(_) => Err(RequestError::BadRequest(uchat_endpoint::RequestFailed {
-                            msg: {
+                        Err(_) => Err(RequestError::BadRequest(
+                            uchat_endpoint::RequestFailed::new(
                                 status
                                     .canonical_reason()
-                                    .unwrap_or_else(|| "An error ocurred.")
-                                    .to_string()
-                            },
-                        })),
+                                    .unwrap_or_else(|| "An error ocurred."),
+                            ),
+                        )),
                     }
                 }
             }
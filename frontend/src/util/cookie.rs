@@ -1,12 +1,11 @@
-#![allow(dead_code)]
-
-use std::str::FromStr;
-
-use chrono::{DateTime, Duration, Utc};
-use uchat_domain::ids::SessionId;
+use chrono::{DateTime, Utc};
+use uchat_cookie::{Cookie, SameSite};
 
 use super::document;
 
+// NOTE The session cookies are set by the server as `HttpOnly`, so scripts only get to see this hint
+const LOGGED_IN_VALUE: &str = "1";
+
 macro_rules! set_cookie {
     ($cookie:expr) => {
         document()
@@ -15,59 +14,43 @@ macro_rules! set_cookie {
     };
 }
 
-pub fn get_session() -> Option<SessionId> {
-    let cookies = document().cookie().unwrap();
-    uchat_cookie::get_from_str(&cookies, "session_id").and_then(|id| SessionId::from_str(id).ok())
+fn hint_cookie<'a>(name: &'a str, value: &'a str) -> Cookie<'a> {
+    Cookie::new(name, value)
+        .secure(cfg!(not(debug_assertions)))
+        .same_site(SameSite::Strict)
 }
 
-pub fn remove_session() {
-    let cookie = format_cookie(
-        format_kv(uchat_cookie::SESSION_ID, ""),
-        Utc::now() - Duration::days(1),
-    );
-    set_cookie!(&cookie);
-}
-
-pub fn set_session(signature: String, id: SessionId, expires: DateTime<Utc>) {
-    let cookie = format_cookie(format_kv(uchat_cookie::SESSION_ID, id.to_string()), expires);
-    set_cookie!(&cookie);
-
-    let cookie = format_cookie(
-        format_kv(uchat_cookie::SESSION_SIGNATURE, signature),
-        expires,
-    );
-    set_cookie!(&cookie);
+/// Whether a session was started in this browser, it may have still expired or been revoked on the server
+pub fn is_logged_in() -> bool {
+    let cookies = document().cookie().unwrap_or_default();
+    uchat_cookie::get_from_str(&cookies, uchat_cookie::LOGGED_IN) == Some(LOGGED_IN_VALUE)
 }
 
-#[cfg(not(debug_assertions))]
-fn standard_options() -> &'static str {
-    "SameSite=Strict; Path=/; Secure"
+/// The token sent with authorized requests, issued along with the session
+pub fn csrf_token() -> Option<String> {
+    let cookies = document().cookie().unwrap_or_default();
+    uchat_cookie::get_from_str(&cookies, uchat_cookie::CSRF_TOKEN)
+        .filter(|token| !token.is_empty())
+        .map(ToOwned::to_owned)
 }
 
-#[cfg(debug_assertions)]
-fn standard_options() -> &'static str {
-    "SameSite=Strict; Path=/;"
-}
+pub fn set_logged_in(expires: DateTime<Utc>, csrf_token: &str) {
+    let max_age = (expires - Utc::now()).num_seconds();
 
-fn format_expiration(expires: DateTime<Utc>) -> String {
-    expires.format("expires=%a, %d %b %Y %T GMT").to_string()
-}
+    let cookie = hint_cookie(uchat_cookie::LOGGED_IN, LOGGED_IN_VALUE)
+        .max_age(max_age)
+        .to_string();
+    set_cookie!(&cookie);
 
-fn format_kv<K, V>(key: K, value: V) -> String
-where
-    K: AsRef<str>,
-    V: AsRef<str>,
-{
-    let key = key.as_ref();
-    let value = value.as_ref();
-    format!("{key}={value}")
+    let cookie = hint_cookie(uchat_cookie::CSRF_TOKEN, csrf_token)
+        .max_age(max_age)
+        .to_string();
+    set_cookie!(&cookie);
 }
 
-fn format_cookie<S: AsRef<str>>(payload: S, expires: DateTime<Utc>) -> String {
-    let expires = format_expiration(expires);
-    let options = standard_options();
-    let payload = payload.as_ref();
-
-    let cookie = format!("{payload}; {expires}; {options}");
-    cookie
+pub fn clear_logged_in() {
+    for name in [uchat_cookie::LOGGED_IN, uchat_cookie::CSRF_TOKEN] {
+        let cookie = hint_cookie(name, "").max_age(0).to_string();
+        set_cookie!(&cookie);
+    }
 }
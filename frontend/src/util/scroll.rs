@@ -0,0 +1,59 @@
+use dioxus::prelude::ScopeState;
+use wasm_bindgen::{closure::Closure, JsCast};
+
+use super::{document, window};
+
+// ? How close to the bottom of the page, in pixels, counts as having reached it
+const BOTTOM_THRESHOLD: f64 = 300.0;
+
+/// Keeps the window scroll listener alive while the component is mounted
+pub struct ScrollListener {
+    callback: Closure<dyn FnMut()>,
+}
+
+impl Drop for ScrollListener {
+    fn drop(&mut self) {
+        let _ = window()
+            .remove_event_listener_with_callback("scroll", self.callback.as_ref().unchecked_ref());
+    }
+}
+
+pub fn is_scrolled_to_bottom() -> bool {
+    let window = window();
+    let Some(body) = document().body() else {
+        return false;
+    };
+
+    let viewport_height = window
+        .inner_height()
+        .ok()
+        .and_then(|height| height.as_f64())
+        .unwrap_or_default();
+    let scrolled = window.scroll_y().unwrap_or_default();
+
+    scrolled + viewport_height >= body.scroll_height() as f64 - BOTTOM_THRESHOLD
+}
+
+/// Calls `on_bottom` once every time the window is scrolled to the bottom of the page
+///
+/// NOTE The listener is registered on the first render only and removed when the component unmounts
+pub fn use_scroll_bottom<F>(cx: &ScopeState, on_bottom: F)
+where
+    F: Fn() + 'static,
+{
+    cx.use_hook(|| {
+        let mut was_at_bottom = false;
+        let callback = Closure::<dyn FnMut()>::new(move || {
+            let at_bottom = is_scrolled_to_bottom();
+            if at_bottom && !was_at_bottom {
+                on_bottom();
+            }
+            was_at_bottom = at_bottom;
+        });
+
+        let _ =
+            window().add_event_listener_with_callback("scroll", callback.as_ref().unchecked_ref());
+
+        ScrollListener { callback }
+    });
+}
@@ -1,19 +1,133 @@
+use std::fmt;
+
 pub const SESSION_ID: &str = "session_id";
 pub const SESSION_SIGNATURE: &str = "session_signature";
+/// Tells the frontend a session probably exists, the session cookies themselves can't be read by scripts
+pub const LOGGED_IN: &str = "logged_in";
+/// Kept by the frontend to send in the CSRF header, unlike the session cookies it's readable by scripts
+pub const CSRF_TOKEN: &str = "csrf_token";
 
 pub fn get_from_str<'a>(cookies: &'a str, key: &str) -> Option<&'a str> {
+    parse(cookies).find_map(|(k, v)| if k == key { Some(v) } else { None })
+}
+
+/// Splits a `Cookie` header into its name and value pairs
+pub fn parse(cookies: &str) -> impl Iterator<Item = (&str, &str)> {
     cookies
         .split(';')
-        .find_map(|cookie| match cookie.split_once('=') {
-            Some((k, v)) => {
-                if k.trim() == key {
-                    Some(v)
-                } else {
-                    None
-                }
-            }
-            _ => None,
-        })
+        .filter_map(|cookie| cookie.split_once('='))
+        .map(|(k, v)| (k.trim(), v.trim()))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SameSite {
+    Strict,
+    Lax,
+    None,
+}
+
+impl fmt::Display for SameSite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            Self::Strict => "Strict",
+            Self::Lax => "Lax",
+            Self::None => "None",
+        };
+        f.write_str(value)
+    }
+}
+
+/// Builds the value of a `Set-Cookie` header, or of `document.cookie`
+///
+/// # Example
+///
+/// ```
+/// use uchat_cookie::{Cookie, SameSite};
+///
+/// let cookie = Cookie::new("session_id", "123")
+///     .max_age(60)
+///     .http_only(true)
+///     .same_site(SameSite::Strict);
+///
+/// assert_eq!(
+///     cookie.to_string(),
+///     "session_id=123; Path=/; Max-Age=60; HttpOnly; SameSite=Strict"
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cookie<'a> {
+    name: &'a str,
+    value: &'a str,
+    path: &'a str,
+    max_age: Option<i64>,
+    http_only: bool,
+    secure: bool,
+    same_site: Option<SameSite>,
+}
+
+impl<'a> Cookie<'a> {
+    pub fn new(name: &'a str, value: &'a str) -> Self {
+        Self {
+            name,
+            value,
+            path: "/",
+            max_age: None,
+            http_only: false,
+            secure: false,
+            same_site: None,
+        }
+    }
+
+    /// A cookie that makes the browser delete the one with the same name
+    pub fn removal(name: &'a str) -> Self {
+        Self::new(name, "").max_age(0)
+    }
+
+    pub fn path(mut self, path: &'a str) -> Self {
+        self.path = path;
+        self
+    }
+
+    /// Seconds until the cookie expires, without it the cookie only lasts until the browser closes
+    pub fn max_age(mut self, seconds: i64) -> Self {
+        self.max_age = Some(seconds.max(0));
+        self
+    }
+
+    /// Hides the cookie from scripts, it's only sent along with requests
+    pub fn http_only(mut self, http_only: bool) -> Self {
+        self.http_only = http_only;
+        self
+    }
+
+    pub fn secure(mut self, secure: bool) -> Self {
+        self.secure = secure;
+        self
+    }
+
+    pub fn same_site(mut self, same_site: SameSite) -> Self {
+        self.same_site = Some(same_site);
+        self
+    }
+}
+
+impl fmt::Display for Cookie<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}; Path={}", self.name, self.value, self.path)?;
+        if let Some(max_age) = self.max_age {
+            write!(f, "; Max-Age={max_age}")?;
+        }
+        if self.http_only {
+            write!(f, "; HttpOnly")?;
+        }
+        if self.secure {
+            write!(f, "; Secure")?;
+        }
+        if let Some(same_site) = self.same_site {
+            write!(f, "; SameSite={same_site}")?;
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -30,4 +144,21 @@ mod tests {
             get_from_str(cookie_str, "some_other_cookie").expect("failed to get some_other_cookie");
         assert_eq!(other, "test");
     }
+
+    #[test]
+    fn builds_cookies() {
+        let cookie = Cookie::new(SESSION_ID, "abc")
+            .max_age(3600)
+            .http_only(true)
+            .secure(true)
+            .same_site(SameSite::Lax);
+        assert_eq!(
+            cookie.to_string(),
+            "session_id=abc; Path=/; Max-Age=3600; HttpOnly; Secure; SameSite=Lax"
+        );
+
+        let removal = Cookie::removal(SESSION_ID).to_string();
+        assert_eq!(removal, "session_id=; Path=/; Max-Age=0");
+        assert_eq!(get_from_str(&removal, SESSION_ID), Some(""));
+    }
 }
@@ -0,0 +1,68 @@
+use std::{fmt, str::FromStr};
+
+use nutype::nutype;
+use serde::{Deserialize, Serialize};
+
+use crate::UserFacingError;
+
+#[nutype(validate(present, max_len = 50))]
+#[derive(AsRef, Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct TokenName(String);
+
+impl TokenName {
+    pub const MAX_CHARS: usize = 50;
+}
+
+impl UserFacingError for TokenNameError {
+    fn formatted_error(&self) -> &'static str {
+        match self {
+            Self::Missing => "Token name cannot be empty",
+            Self::TooLong => "Token name must be at most 50 characters",
+        }
+    }
+}
+
+/// What a personal access token is allowed to do, browser sessions can do everything
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Scope {
+    /// read posts, profiles and messages
+    Read,
+    /// create, edit and react to posts
+    Post,
+    /// follow and unfollow users
+    Follow,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 3] = [Scope::Read, Scope::Post, Scope::Follow];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::Post => "post",
+            Self::Follow => "follow",
+        }
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("unknown scope: {0}")]
+pub struct UnknownScope(String);
+
+impl FromStr for Scope {
+    type Err = UnknownScope;
+
+    fn from_str(scope: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|known| known.as_str() == scope)
+            .ok_or_else(|| UnknownScope(scope.to_owned()))
+    }
+}
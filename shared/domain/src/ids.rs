@@ -28,9 +28,11 @@ macro_rules! new_id {
             pub fn as_uuid(&self) -> &uuid::Uuid {
                 &self.0
             }
+        }
 
-            pub fn to_string(&self) -> String {
-                self.0.to_string()
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                std::fmt::Display::fmt(&self.0, f)
             }
         }
 
@@ -70,3 +72,4 @@ new_id!(SessionId);
 new_id!(PostId);
 new_id!(ImageId);
 new_id!(PollChoiceId);
+new_id!(AccessTokenId);
@@ -3,6 +3,7 @@
 #[macro_use]
 extern crate diesel_derive_newtype;
 
+pub mod access_token;
 pub mod ids;
 pub mod post;
 pub mod user;
@@ -71,6 +71,52 @@ impl UserFacingError for CaptionError {
     }
 }
 
+#[nutype(validate(present, max_len = 120))]
+#[derive(AsRef, Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct AltText(String);
+
+impl AltText {
+    pub const MAX_CHARS: usize = 120;
+}
+
+impl UserFacingError for AltTextError {
+    fn formatted_error(&self) -> &'static str {
+        match self {
+            Self::Missing => "Alt text cannot be empty",
+            Self::TooLong => "Alt text must be at most 120 characters",
+        }
+    }
+}
+
+/// Most images a gallery post can hold
+pub const MAX_GALLERY_IMAGES: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum GalleryError {
+    #[error("gallery has no images")]
+    Empty,
+    #[error("gallery has too many images")]
+    TooManyImages,
+}
+
+impl UserFacingError for GalleryError {
+    fn formatted_error(&self) -> &'static str {
+        match self {
+            Self::Empty => "Gallery must have at least one image",
+            Self::TooManyImages => "Gallery can have at most 4 images",
+        }
+    }
+}
+
+/// Checks that a gallery of `len` images can be posted
+pub fn validate_gallery_len(len: usize) -> Result<(), GalleryError> {
+    match len {
+        0 => Err(GalleryError::Empty),
+        len if len > MAX_GALLERY_IMAGES => Err(GalleryError::TooManyImages),
+        _ => Ok(()),
+    }
+}
+
 #[nutype(validate(present, max_len = 80))]
 #[derive(AsRef, Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct PollChoiceDescription(String);
@@ -87,3 +133,22 @@ impl UserFacingError for PollChoiceDescriptionError {
         }
     }
 }
+
+/// Emoji that can be used to react to a post
+pub const REACTION_EMOJI: [&str; 6] = ["👍", "❤️", "😂", "😮", "😢", "🎉"];
+
+fn is_reaction_emoji(emoji: &str) -> bool {
+    REACTION_EMOJI.contains(&emoji)
+}
+
+#[nutype(validate(with = is_reaction_emoji))]
+#[derive(AsRef, Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Emoji(String);
+
+impl UserFacingError for EmojiError {
+    fn formatted_error(&self) -> &'static str {
+        match self {
+            Self::Invalid => "This emoji can't be used as a reaction",
+        }
+    }
+}
@@ -1,10 +1,14 @@
 use serde::{Deserialize, Serialize};
 
+pub mod message;
 pub mod post;
 pub mod user;
 
 use load_dotenv::load_dotenv;
 
+/// Header that carries the CSRF token on every authorized request
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
 pub trait Endpoint {
     const URL: &'static str;
     fn url(&self) -> &'static str {
@@ -12,10 +16,43 @@ pub trait Endpoint {
     }
 }
 
+/// Stable identifiers for failures that clients need to tell apart, `msg` is only meant for display
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    InvalidCredentials,
+    AccountExists,
+    TooManyRequests,
+    Unauthorized,
+    InvalidCsrfToken,
+    InsufficientScope,
+    // ? Codes added by newer servers shouldn't break older clients
+    #[serde(other)]
+    Unknown,
+}
+
 #[derive(thiserror::Error, Debug, Deserialize, Serialize)]
 #[error("{msg}")]
 pub struct RequestFailed {
     pub msg: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<ErrorCode>,
+}
+
+impl RequestFailed {
+    pub fn new<T: Into<String>>(msg: T) -> Self {
+        Self {
+            msg: msg.into(),
+            code: None,
+        }
+    }
+
+    pub fn with_code<T: Into<String>>(code: ErrorCode, msg: T) -> Self {
+        Self {
+            msg: msg.into(),
+            code: Some(code),
+        }
+    }
 }
 
 /// Macro for implementing Endpoint trait for a type
@@ -63,30 +100,100 @@ pub mod app_url {
     }
 
     pub mod user_content {
+        use serde::{Deserialize, Serialize};
+        use url::Url;
+
         pub const ROOT: &str = "usercontent/";
         pub const IMAGES: &str = "img/";
+        /// query parameter picking one of the sizes an image is served in
+        pub const SIZE_PARAM: &str = "size";
+
+        #[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq, Hash)]
+        #[serde(rename_all = "snake_case")]
+        pub enum ImageSize {
+            Avatar,
+            Feed,
+            #[default]
+            Full,
+        }
+
+        impl ImageSize {
+            /// The downscaled sizes, smallest first
+            pub const VARIANTS: [Self; 2] = [Self::Avatar, Self::Feed];
+
+            /// Longest side of the image in pixels, `None` when it's served as uploaded
+            pub fn max_dimension(&self) -> Option<u32> {
+                match self {
+                    Self::Avatar => Some(96),
+                    Self::Feed => Some(640),
+                    Self::Full => None,
+                }
+            }
+
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    Self::Avatar => "avatar",
+                    Self::Feed => "feed",
+                    Self::Full => "full",
+                }
+            }
+        }
+
+        /// Points an image url at one of its sizes, e.g. the avatar of a profile image
+        pub fn sized(url: &Url, size: ImageSize) -> Url {
+            let mut url = url.clone();
+            url.set_query(None);
+            if size != ImageSize::Full {
+                url.query_pairs_mut().append_pair(SIZE_PARAM, size.as_str());
+            }
+            url
+        }
     }
 }
 
 // public routes
 route!("/account/create" => user::endpoint::CreateUser);
 route!("/account/login" => user::endpoint::Login);
+route!("/account/login/totp" => user::endpoint::VerifyLoginTotp);
+route!("/account/email/confirm" => user::endpoint::ConfirmEmail);
+route!("/account/password/request_reset" => user::endpoint::RequestPasswordReset);
+route!("/account/password/reset" => user::endpoint::ResetPassword);
 route!("/profile/view" => user::endpoint::ViewProfile);
 
 // authorized routes
+route!("/account/logout" => user::endpoint::Logout);
+route!("/account/logout_all" => user::endpoint::LogoutAll);
+route!("/account/sessions" => user::endpoint::ActiveSessions);
+route!("/account/sessions/revoke" => user::endpoint::RevokeSession);
+route!("/account/tokens" => user::endpoint::AccessTokens);
+route!("/account/tokens/new" => user::endpoint::CreateAccessToken);
+route!("/account/tokens/revoke" => user::endpoint::RevokeAccessToken);
+route!("/account/email/send_confirmation" => user::endpoint::SendEmailConfirmation);
+route!("/account/totp/enroll" => user::endpoint::EnrollTotp);
+route!("/account/totp/confirm" => user::endpoint::ConfirmTotp);
+route!("/account/totp/disable" => user::endpoint::DisableTotp);
 route!("/post/new" => post::endpoint::NewPost);
 route!("/post/bookmark" => post::endpoint::Bookmark);
 route!("/post/boost" => post::endpoint::Boost);
 route!("/post/vote" => post::endpoint::Vote);
 route!("/post/react" => post::endpoint::React);
+route!("/post/view" => post::endpoint::ViewPost);
+route!("/post/reschedule" => post::endpoint::ReschedulePost);
+route!("/post/cancel" => post::endpoint::CancelScheduledPost);
+route!("/post/delete" => post::endpoint::DeletePost);
+route!("/post/edit" => post::endpoint::EditPost);
 route!("/posts/trending" => post::endpoint::TrendingPosts);
 route!("/posts/home" => post::endpoint::HomePosts);
 route!("/posts/liked" => post::endpoint::LikedPosts);
 route!("/posts/bookmarked" => post::endpoint::BookmarkedPosts);
+route!("/posts/scheduled" => post::endpoint::ScheduledPosts);
 route!("/profile/me" => user::endpoint::GetMyProfile);
 route!("/profile/update" => user::endpoint::UpdateProfile);
 route!("/profile/following" => user::endpoint::IsFollowing);
 route!("/user/follow" => user::endpoint::FollowUser);
+route!("/messages/send" => message::endpoint::SendDirectMessage);
+route!("/messages/conversations" => message::endpoint::Conversations);
+route!("/messages/view" => message::endpoint::ViewConversation);
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum Update<T> {
@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+use uchat_domain::{ids::UserId, post::Message};
+
+use crate::user::types::PublicUserProfile;
+
+use super::types::{Conversation, DirectMessage};
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct SendDirectMessage {
+    pub to: UserId,
+    pub message: Message,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct SendDirectMessageOk {
+    pub message: DirectMessage,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Conversations;
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct ConversationsOk {
+    pub conversations: Vec<Conversation>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct ViewConversation {
+    pub with_user: UserId,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct ViewConversationOk {
+    pub with_user: PublicUserProfile,
+    pub messages: Vec<DirectMessage>,
+}
@@ -0,0 +1,23 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uchat_domain::{
+    ids::{PostId, UserId},
+    post::Message,
+};
+
+use crate::user::types::PublicUserProfile;
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct DirectMessage {
+    pub id: PostId,
+    pub from: UserId,
+    pub to: UserId,
+    pub message: Message,
+    pub time_sent: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Conversation {
+    pub with_user: PublicUserProfile,
+    pub last_message: DirectMessage,
+}
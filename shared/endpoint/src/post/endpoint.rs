@@ -1,8 +1,10 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uchat_domain::ids::{PollChoiceId, PostId};
 
 use super::types::{
-    BookmarkAction, BoostAction, Content, LikeStatus, NewPostOptions, PublicPost, VoteCast,
+    BookmarkAction, BoostAction, Content, EmojiReaction, LikeStatus, NewPostOptions, PostCursor,
+    PublicPost, ReactAction, VoteCast,
 };
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
@@ -17,11 +19,14 @@ pub struct NewPostOk {
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
-pub struct TrendingPosts;
+pub struct TrendingPosts {
+    pub cursor: Option<PostCursor>,
+}
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct TrendingPostsOk {
     pub posts: Vec<PublicPost>,
+    pub next_cursor: Option<PostCursor>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
@@ -60,7 +65,7 @@ pub struct VoteOk {
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct React {
     pub post_id: PostId,
-    pub like_status: LikeStatus,
+    pub action: ReactAction,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
@@ -68,28 +73,105 @@ pub struct ReactOk {
     pub like_status: LikeStatus,
     pub likes: i64,
     pub dislikes: i64,
+    pub emoji_reactions: Vec<EmojiReaction>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
-pub struct HomePosts;
+pub struct HomePosts {
+    pub cursor: Option<PostCursor>,
+}
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct HomePostsOk {
     pub posts: Vec<PublicPost>,
+    pub next_cursor: Option<PostCursor>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
-pub struct LikedPosts;
+pub struct LikedPosts {
+    pub cursor: Option<PostCursor>,
+}
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct LikedPostsOk {
     pub posts: Vec<PublicPost>,
+    pub next_cursor: Option<PostCursor>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
-pub struct BookmarkedPosts;
+pub struct BookmarkedPosts {
+    pub cursor: Option<PostCursor>,
+}
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct BookmarkedPostsOk {
     pub posts: Vec<PublicPost>,
+    pub next_cursor: Option<PostCursor>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct ViewPost {
+    pub post_id: PostId,
+    /// NOTE Only the descendants are paginated, the ancestors are always returned in full
+    pub cursor: Option<PostCursor>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct ViewPostOk {
+    /// ordered from the thread's root post to the viewed post's parent
+    pub ancestors: Vec<PublicPost>,
+    pub post: PublicPost,
+    /// replies at any depth, ordered from oldest to newest
+    pub descendants: Vec<PublicPost>,
+    pub next_cursor: Option<PostCursor>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct ScheduledPosts;
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct ScheduledPostsOk {
+    /// the session user's pending posts, the ones going out soonest first
+    pub posts: Vec<PublicPost>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct ReschedulePost {
+    pub post_id: PostId,
+    /// NOTE A time that has already passed publishes the post right away
+    pub time_posted: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct ReschedulePostOk {
+    pub time_posted: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct CancelScheduledPost {
+    pub post_id: PostId,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct CancelScheduledPostOk;
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct DeletePost {
+    pub post_id: PostId,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct DeletePostOk;
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct EditPost {
+    pub post_id: PostId,
+    /// NOTE Must be the same kind of content the post already has, though an image and a gallery
+    /// can be swapped for one another
+    pub content: Content,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct EditPostOk {
+    pub post: PublicPost,
 }
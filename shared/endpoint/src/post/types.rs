@@ -2,7 +2,10 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uchat_domain::{
     ids::{ImageId, PollChoiceId, PostId, UserId},
-    post::{Caption, Headline, Message, PollChoiceDescription, PollHeadline},
+    post::{
+        validate_gallery_len, AltText, Caption, Emoji, GalleryError, Headline, Message,
+        PollChoiceDescription, PollHeadline,
+    },
     Username,
 };
 use url::Url;
@@ -27,7 +30,33 @@ impl From<Chat> for Content {
 pub enum ImageKind {
     DataUrl(String),
     Id(ImageId),
-    Url(Url),
+    Url(ImageSrcSet),
+}
+
+/// Where an image can be loaded from, in each of the sizes it was saved in
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct ImageSrcSet {
+    /// the full size image
+    pub src: Url,
+    /// every size including the full one, smallest first
+    pub variants: Vec<ImageVariant>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct ImageVariant {
+    pub url: Url,
+    pub width: u32,
+}
+
+impl ImageSrcSet {
+    /// Formats the variants for an `img` element's `srcset` attribute
+    pub fn srcset(&self) -> String {
+        self.variants
+            .iter()
+            .map(|variant| format!("{} {}w", variant.url, variant.width))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
@@ -42,6 +71,30 @@ impl From<Image> for Content {
     }
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct GalleryImage {
+    pub kind: ImageKind,
+    pub alt: Option<AltText>,
+}
+
+/// Several images shown together, each described by its own alt text
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Gallery {
+    pub images: Vec<GalleryImage>,
+}
+
+impl Gallery {
+    pub fn validate(&self) -> Result<(), GalleryError> {
+        validate_gallery_len(self.images.len())
+    }
+}
+
+impl From<Gallery> for Content {
+    fn from(value: Gallery) -> Self {
+        Content::Gallery(value)
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct PollChoice {
     pub id: PollChoiceId,
@@ -67,6 +120,7 @@ pub enum Content {
     Chat(Chat),
     Image(Image),
     Poll(Poll),
+    Gallery(Gallery),
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
@@ -93,12 +147,30 @@ pub enum LikeStatus {
     NoReaction,
 }
 
+/// What a `React` request changes, the like status and emoji reactions are independent
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub enum ReactAction {
+    LikeStatus(LikeStatus),
+    AddEmoji(Emoji),
+    RemoveEmoji(Emoji),
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct EmojiReaction {
+    pub emoji: Emoji,
+    pub count: i64,
+    /// whether the session user reacted with this emoji
+    pub reacted: bool,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct PublicPost {
     pub id: PostId,
     pub by_user: PublicUserProfile,
     pub content: Content,
     pub time_posted: DateTime<Utc>,
+    /// when the content was last changed, `None` if it was never edited
+    pub edited_at: Option<DateTime<Utc>>,
     pub reply_to: Option<(Username, UserId, PostId)>,
     pub like_status: LikeStatus,
     pub bookmarked: bool,
@@ -106,6 +178,57 @@ pub struct PublicPost {
     pub likes: i64,
     pub dislikes: i64,
     pub boosts: i64,
+    /// the most used emoji first
+    pub emoji_reactions: Vec<EmojiReaction>,
+}
+
+/// Opaque position in a feed of posts, the client should only send back what the server returned
+///
+/// NOTE Encodes the `time_posted` and `id` of the last post in a page (or its score, for ranked
+/// feeds), so that the next page starts right after it
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct PostCursor(String);
+
+impl PostCursor {
+    pub fn new(time_posted: DateTime<Utc>, id: PostId) -> Self {
+        Self(format!("{}_{id}", time_posted.timestamp_micros()))
+    }
+
+    /// Returns `None` if the cursor was tampered with
+    pub fn decode(&self) -> Option<(DateTime<Utc>, PostId)> {
+        use chrono::TimeZone;
+        use std::str::FromStr;
+
+        let (micros, id) = self.0.split_once('_')?;
+        let micros = micros.parse::<i64>().ok()?;
+        let time_posted = Utc
+            .timestamp_opt(
+                micros.div_euclid(1_000_000),
+                (micros.rem_euclid(1_000_000) * 1_000) as u32,
+            )
+            .single()?;
+        let id = PostId::from_str(id).ok()?;
+
+        Some((time_posted, id))
+    }
+
+    /// Cursor for feeds that are ordered by score instead of time, such as trending posts
+    ///
+    /// ? The bits of the score are encoded so that it's decoded without any rounding
+    pub fn from_score(score: f64, id: PostId) -> Self {
+        Self(format!("score_{:x}_{id}", score.to_bits()))
+    }
+
+    /// Returns `None` if the cursor was tampered with or isn't a scored cursor
+    pub fn decode_score(&self) -> Option<(f64, PostId)> {
+        use std::str::FromStr;
+
+        let (bits, id) = self.0.strip_prefix("score_")?.split_once('_')?;
+        let score = f64::from_bits(u64::from_str_radix(bits, 16).ok()?);
+        let id = PostId::from_str(id).ok()?;
+
+        Some((score, id))
+    }
 }
 
 #[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
@@ -1,11 +1,19 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use uchat_domain::{ids::*, Password, Username};
+use uchat_domain::{
+    access_token::{Scope, TokenName},
+    ids::*,
+    user::Email,
+    Password, Username,
+};
 use url::Url;
 
-use crate::{post::types::PublicPost, Update};
+use crate::{
+    post::types::{PostCursor, PublicPost},
+    Update,
+};
 
-use super::types::{FollowAction, PublicUserProfile};
+use super::types::{ActiveSession, FollowAction, PublicAccessToken, PublicUserProfile};
 
 #[derive(Clone, Deserialize, Serialize)]
 pub struct CreateUser {
@@ -18,9 +26,10 @@ pub struct CreateUserOk {
     pub user_id: UserId,
     pub username: Username,
 
-    pub session_signature: String,
-    pub session_id: SessionId,
+    // NOTE The session itself is only sent in `Set-Cookie` headers, out of reach of scripts
     pub session_expires: DateTime<Utc>,
+    /// sent back in the `CSRF_HEADER` of every authorized request
+    pub csrf_token: String,
 }
 
 #[derive(Clone, Deserialize, Serialize)]
@@ -29,11 +38,13 @@ pub struct Login {
     pub password: Password,
 }
 
+/// The session handed out once the user is fully logged in
 #[derive(Clone, Deserialize, Serialize)]
-pub struct LoginOk {
-    pub session_signature: String,
-    pub session_id: SessionId,
+pub struct LoginSession {
+    // NOTE The session itself is only sent in `Set-Cookie` headers, out of reach of scripts
     pub session_expires: DateTime<Utc>,
+    /// sent back in the `CSRF_HEADER` of every authorized request
+    pub csrf_token: String,
 
     pub display_name: Option<String>,
     pub email: Option<String>,
@@ -41,6 +52,119 @@ pub struct LoginOk {
     pub user_id: UserId,
 }
 
+#[derive(Clone, Deserialize, Serialize)]
+pub enum LoginOk {
+    LoggedIn(LoginSession),
+    /// the account has two-factor authentication, answer the challenge with `VerifyLoginTotp`
+    TotpRequired {
+        challenge: String,
+    },
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct VerifyLoginTotp {
+    pub challenge: String,
+    /// a code from the authenticator app or one of the recovery codes
+    pub code: String,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct VerifyLoginTotpOk {
+    pub session: LoginSession,
+}
+
+/// Starts enrolling an authenticator app, TOTP is only enabled after `ConfirmTotp`
+#[derive(Clone, Deserialize, Serialize)]
+pub struct EnrollTotp;
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct EnrollTotpOk {
+    /// base32 secret, for authenticator apps that can't scan the provisioning uri
+    pub secret: String,
+    pub provisioning_uri: String,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ConfirmTotp {
+    pub code: String,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ConfirmTotpOk {
+    /// only shown once, each one can be used instead of a TOTP code a single time
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct DisableTotp {
+    pub code: String,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct DisableTotpOk;
+
+/// Revokes the session used to make the request
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Logout;
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct LogoutOk;
+
+/// Revokes every session of the user, logging them out of all devices
+#[derive(Clone, Deserialize, Serialize)]
+pub struct LogoutAll;
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct LogoutAllOk {
+    pub sessions_revoked: usize,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ActiveSessions;
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ActiveSessionsOk {
+    pub sessions: Vec<ActiveSession>,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct RevokeSession {
+    pub session_id: SessionId,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct RevokeSessionOk;
+
+/// Creates a personal access token, for bots and scripts that authenticate with `Authorization: Bearer`
+#[derive(Clone, Deserialize, Serialize)]
+pub struct CreateAccessToken {
+    pub name: TokenName,
+    pub scopes: Vec<Scope>,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct CreateAccessTokenOk {
+    pub token: PublicAccessToken,
+    /// only returned once, it can't be recovered after this
+    pub secret: String,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct AccessTokens;
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct AccessTokensOk {
+    pub tokens: Vec<PublicAccessToken>,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct RevokeAccessToken {
+    pub id: AccessTokenId,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct RevokeAccessTokenOk;
+
 #[derive(Clone, Deserialize, Serialize)]
 pub struct GetMyProfile;
 
@@ -48,6 +172,8 @@ pub struct GetMyProfile;
 pub struct GetMyProfileOk {
     pub display_name: Option<String>,
     pub email: Option<String>,
+    pub email_confirmed: bool,
+    pub totp_enabled: bool,
     pub profile_image: Option<Url>,
     pub user_id: UserId,
 }
@@ -55,7 +181,7 @@ pub struct GetMyProfileOk {
 #[derive(Clone, Deserialize, Serialize)]
 pub struct UpdateProfile {
     pub display_name: Update<String>,
-    pub email: Update<String>,
+    pub email: Update<Email>,
     pub profile_image: Update<String>,
     pub password: Update<Password>,
 }
@@ -65,15 +191,52 @@ pub struct UpdateProfileOk {
     pub profile_image: Option<Url>,
 }
 
+/// Sends a new confirmation link to the user's current email
+#[derive(Clone, Deserialize, Serialize)]
+pub struct SendEmailConfirmation;
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct SendEmailConfirmationOk;
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ConfirmEmail {
+    pub token: String,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ConfirmEmailOk {
+    pub email: String,
+}
+
+/// Emails a password reset link to every account that confirmed the email
+#[derive(Clone, Deserialize, Serialize)]
+pub struct RequestPasswordReset {
+    pub email: Email,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct RequestPasswordResetOk;
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ResetPassword {
+    pub token: String,
+    pub password: Password,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ResetPasswordOk;
+
 #[derive(Clone, Deserialize, Serialize)]
 pub struct ViewProfile {
     pub user_id: UserId,
+    pub cursor: Option<PostCursor>,
 }
 
 #[derive(Clone, Deserialize, Serialize)]
 pub struct ViewProfileOk {
     pub profile: PublicUserProfile,
     pub posts: Vec<PublicPost>,
+    pub next_cursor: Option<PostCursor>,
 }
 
 #[derive(Clone, Deserialize, Serialize)]
@@ -1,6 +1,10 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use uchat_domain::{ids::UserId, user::DisplayName};
+use uchat_domain::{
+    access_token::Scope,
+    ids::{AccessTokenId, SessionId, UserId},
+    user::DisplayName,
+};
 use url::Url;
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
@@ -27,3 +31,25 @@ impl From<FollowAction> for bool {
         }
     }
 }
+
+/// A device the user is logged in from
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct ActiveSession {
+    pub id: SessionId,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// whether this is the session used to make the request
+    pub is_current: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct PublicAccessToken {
+    pub id: AccessTokenId,
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}